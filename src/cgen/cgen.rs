@@ -1,40 +1,30 @@
-use std::{collections::HashMap, fmt::Display, fs::File, io::Write, ops::Deref};
+use std::{collections::HashMap, io::Write};
 
 use crate::{
-    ctx::CompileContext,
-    parser::ast::{
-        class::{Class, Feature},
-        Type,
-    },
-    utils::table::{ClassTable, SymbolTable, Tables},
-    BOOL, DISPATCH_TABLE_OFFSET, FIELD_BASIC_OFFSET, INT, NULL_TAG_OFFSET, OBJECT, PRIMSLOT,
-    RUNTIME_ERR, STRING,
+    ir::{Method, MethodKind, Program},
+    parser::ast::{class::CONSTRUCTOR, is_primitive, non_null},
+    BOOL, DISPATCH_TABLE_OFFSET, FIELD_BASIC_OFFSET, INT, NULL_TAG_OFFSET, PRIMSLOT,
+    CAST_ERR, DIV_ERR, MATCH_ERR, RAW_BOOL, RUNTIME_ERR, STRING, STRING_CONST_VAL_OFFSET, UNCAUGHT_ERR,
 };
 
-use super::ast::CodeGenerate;
-
-#[derive(PartialEq, Eq, Clone)]
-pub struct Location {
-    pub reg: String,
-    pub offset: i32,
-    // pub type_: Type,
-}
-
-impl Display for Location {
-    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
-    }
+use super::expr::CodeGenerate;
+
+/// The stack slots of a `try` that is being generated, see `CodeGenerate for Try`.
+#[derive(Clone)]
+pub struct TryFrame {
+    /// handler record: previous handler, %rbp, %rbx, %rsp, catch label
+    pub record: i32,
+    /// 0 falls through the finally, 1 rethrows, 2 returns
+    pub mode: i32,
+    pub value: i32,
+    pub label_finally: usize,
 }
 
 #[derive(Default)]
 pub struct Environment {
-    pub env: HashMap<String, SymbolTable<String, Location>>,
-    // pub type_env: SymbolTable<String, Type>,
-    pub field_map: HashMap<(Type, Type), usize>,
-    pub curr_class: String,
-    pub var_offset: i32,
     pub label: usize,
     pub align_stack: usize,
+    pub try_stack: Vec<TryFrame>,
 }
 
 /// * Build constant
@@ -48,39 +38,19 @@ pub struct Environment {
 /// save self to stack
 ///
 pub struct CodeGenerator<'a> {
-    // pub classes: Vec<Class>,
-    // pub class_table: &'a mut ClassTable,
-    // pub tables: Tables,
-    pub ctx: CompileContext,
-    pub asm_file: &'a mut File,
+    pub program: &'a Program,
+    pub asm_file: &'a mut dyn Write,
 
     pub str_const_table: HashMap<String, usize>,
-    // pub int_const_table: HashMap<String, usize>,
-    pub dispatch_table: HashMap<(String, String), usize>,
     pub environment: Environment,
 }
 
 impl<'a> CodeGenerator<'a> {
-    pub fn new(
-        // classes_: Vec<Class>,
-        // class_table_: &'a mut ClassTable,
-        // tables_: Tables,
-        ctx: CompileContext,
-        asm_file_: &'a mut File,
-    ) -> CodeGenerator<'a> {
+    pub fn new(program: &'a Program, asm_file_: &'a mut dyn Write) -> CodeGenerator<'a> {
         CodeGenerator {
-            ctx,
+            program,
             asm_file: asm_file_,
             str_const_table: HashMap::new(),
-            dispatch_table: HashMap::new(),
-            // environment: Environment {
-            //     env: HashMap::new(),
-            //     field_map: HashMap::new(),
-            //     curr_class: "none".to_string(),
-            //     var_offset: 1,
-            //     label: 0,
-            //     align_stack: 0,
-            // },
             environment: Environment::default(),
         }
     }
@@ -98,7 +68,11 @@ impl<'a> CodeGenerator<'a> {
         // code for method
         self.code_method();
 
-        self.code_abort();
+        self.code_throw();
+        self.code_abort("abort", "NullError", RUNTIME_ERR);
+        self.code_abort("abort_cast", "CastError", CAST_ERR);
+        self.code_abort("abort_div", "ArithmeticError", DIV_ERR);
+        self.code_abort("abort_match", "MatchError", MATCH_ERR);
 
         // code for main
         self.code_main();
@@ -133,7 +107,7 @@ impl<'a> CodeGenerator<'a> {
         self.write("#   emit contants".to_string(), true);
         self.write(".section    .data".to_string(), true);
         let mut index = 0;
-        for str_ in &self.ctx.tables.string_table.clone() {
+        for str_ in &self.program.strings.clone() {
             self.write(".align 8".to_string(), true);
             self.write(format!("str_const_ascii_{}:", index), false);
             self.write(format!(".ascii \"{}\"", str_), true);
@@ -154,6 +128,12 @@ impl<'a> CodeGenerator<'a> {
             index += 1;
         }
 
+        // the innermost active handler of try
+        self.write(".align 8".to_string(), true);
+        self.write(format!("exception_handler:"), false);
+        self.write(format!(".quad 0"), true);
+        self.write("".to_string(), false);
+
         index = 0;
         for i in 0..2 {
             self.write(".align 8".to_string(), true);
@@ -169,55 +149,31 @@ impl<'a> CodeGenerator<'a> {
     }
 
     fn code_prototype(&mut self) {
+        let program = self.program;
         self.write("#   class prototype".to_string(), true);
 
-        for class_ in &self.ctx.class_table.classes.clone() {
-            if class_.0 == &PRIMSLOT.to_string() {
+        for class_ in &program.classes {
+            if class_.name == PRIMSLOT {
                 continue;
             }
-            let attr_len = self.ctx.class_table.get_attr_num_recursive(class_.0);
             self.write(".align 8".to_string(), true);
-            self.write(format!("{}_prototype:", class_.0), false);
-
-            let inheritance = self.ctx.class_table.get_inheritance();
+            self.write(format!("{}_prototype:", class_.name), false);
 
-            self.write(format!(".quad {}", (attr_len + 3) * 8), true);
+            self.write(format!(".quad {}", (class_.fields.len() + 3) * 8), true);
             // for null
             // modify dispatch table, all attr location, init
             self.write(format!(".quad {}", 0), true);
-            self.write(format!(".quad {}_dispatch_table", class_.0), true);
-            let mut index = 0;
-            for curr_class in inheritance.get(class_.0).unwrap() {
-                for attr_ in &curr_class.features {
-                    if let Feature::Attribute(attr) = attr_ {
-                        self.environment.field_map.insert(
-                            (curr_class.name.clone(), attr.name.clone()),
-                            FIELD_BASIC_OFFSET + index * 8,
-                        );
-                        index += 1;
-                        if attr.type_.clone().unwrap() == STRING {
-                            self.write(
-                                format!(
-                                    ".quad str_const_{}",
-                                    self.str_const_table.get("").unwrap()
-                                ),
-                                true,
-                            );
-                        } else if attr.type_.clone().unwrap() == BOOL {
-                            self.write(format!(".quad bool_const_0"), true);
-                        } else if attr.type_.clone().unwrap() == INT {
-                            self.write(
-                                format!(
-                                    // ".quad int_const_{}",
-                                    // self.int_const_table.get("0").unwrap()
-                                    ".quad 0",
-                                ),
-                                true,
-                            );
-                        } else if attr.type_.clone().unwrap() == INT.to_string() {
-                            self.write(format!(".quad 0"), true);
-                        }
-                    }
+            self.write(format!(".quad {}_dispatch_table", class_.name), true);
+            for field in &class_.fields {
+                if field.type_ == STRING {
+                    self.write(
+                        format!(".quad str_const_{}", self.str_const_table.get("").unwrap()),
+                        true,
+                    );
+                } else if field.type_ == BOOL {
+                    self.write(format!(".quad bool_const_0"), true);
+                } else if field.type_ == INT || field.type_ == RAW_BOOL {
+                    self.write(format!(".quad 0"), true);
                 }
             }
 
@@ -226,123 +182,58 @@ impl<'a> CodeGenerator<'a> {
     }
 
     fn code_dispatch_table(&mut self) {
+        let program = self.program;
         self.write("#   dispatch tables".to_string(), true);
-        for class_ in &self.ctx.class_table.classes.clone() {
+        for class_ in &program.classes {
             self.write(".align 8".to_string(), true);
-            self.write(format!("{}_dispatch_table:", class_.0), false);
-
-            let inheritance = self
-                .ctx
-                .class_table
-                .get_inheritance()
-                .get(class_.0)
-                .unwrap()
-                .clone();
-            let mut offset = 0;
-            let mut reverse_inheritance = inheritance.clone();
-            reverse_inheritance.reverse();
-            let mut method_map: HashMap<Type, Vec<String>> = HashMap::new();
-
-            for curr_class in &inheritance {
-                let mut v: Vec<String> = vec![];
-                for feature_ in &curr_class.features {
-                    if let Feature::Method(method_) = feature_ {
-                        v.insert(0, method_.name.clone());
-                    }
-                }
-                method_map.insert(curr_class.name.clone(), v);
+            // parent chain for is and as
+            match &class_.parent {
+                Some(parent) => self.write(format!(".quad {}_dispatch_table", parent), true),
+                None => self.write(format!(".quad 0"), true),
             }
-
-            for curr_class in &inheritance {
-                for feature_ in &curr_class.features {
-                    if let Feature::Method(method_) = feature_ {
-                        for c in &reverse_inheritance {
-                            // find first one override the method
-                            if method_map.get(&c.name).unwrap().contains(&method_.name) {
-                                // find
-                                self.write(format!(".quad {}.{}", c.name, method_.name), true);
-                                self.dispatch_table.insert(
-                                    (class_.0.to_string(), method_.name.to_string()),
-                                    offset * 8,
-                                );
-                                break;
-                            }
-                        }
-                        offset += 1;
-                    }
-                }
+            self.write(format!("{}_dispatch_table:", class_.name), false);
+            for label in &class_.vtable {
+                self.write(format!(".quad {}", label), true);
             }
-
-            self.write(format!(".quad {}.init", class_.0), true);
+            self.write(format!(".quad {}.init", class_.name), true);
             self.write(format!(""), true);
         }
     }
 
     fn code_method(&mut self) {
-        let inheritance = self.ctx.class_table.get_inheritance();
+        let program = self.program;
         self.write("#   init method".to_string(), true);
         self.write(".text".to_string(), true);
 
         // for all classes's init method
-        for class_ in &self.ctx.class_table.classes.clone() {
-            self.write(format!("{}.init:", class_.0), false);
+        for class_ in &program.classes {
+            self.write(format!("{}.init:", class_.name), false);
             self.method_start();
 
             // Object <- A <- Main
-            if class_.0 != OBJECT {
-                let parent = self.ctx.class_table.get_parent(class_.0);
+            if let Some(parent) = &class_.parent {
                 self.write(format!("call {}.init", parent), true);
             }
 
-            self.environment
-                .env
-                .insert(class_.0.to_string(), SymbolTable::new());
-
-            self.environment
-                .env
-                .get_mut(class_.0)
-                .unwrap()
-                .enter_scope();
-
-            let mut index = 0;
-            let parents = inheritance.get(class_.0).unwrap();
-
-            for curr_class in parents {
-                for feature in &curr_class.features {
-                    if let Feature::Attribute(attr) = feature {
-                        let offset_ = (index + 3) * 8;
-                        self.environment.env.get_mut(class_.0).unwrap().add(
-                            &attr.name,
-                            &Location {
-                                reg: "%rbx".to_string(),
-                                offset: offset_ as i32,
-                                // type_: attr.type_.clone().unwrap(),
-                            },
-                        );
-                        if let Some(expr_) = attr.init.deref() {
-                            expr_.code_generate(self);
-                            self.write(format!("movq %rax, {}(%rbx)", offset_), true);
-                        } else {
-                            let type_ = attr.type_.clone().unwrap();
-                            if type_ != INT.to_string() {
-                                self.write(
-                                    format!(
-                                        "movq ${}_prototype, {}(%rbx)",
-                                        attr.type_.clone().unwrap(),
-                                        offset_
-                                    ),
-                                    true,
-                                )
-                            }
-                        }
-                        index += 1;
-                    }
+            for field in &class_.fields {
+                if let Some(init) = &field.init {
+                    init.code_generate(self);
+                    self.write(format!("movq %rax, {}(%rbx)", field.offset), true);
+                } else if !is_primitive(&field.type_) {
+                    self.write(
+                        format!(
+                            "movq ${}_prototype, {}(%rbx)",
+                            non_null(&field.type_),
+                            field.offset
+                        ),
+                        true,
+                    )
                 }
             }
             self.write(
                 format!(
                     "movq ${}_dispatch_table,{}(%rbx)",
-                    class_.0, DISPATCH_TABLE_OFFSET
+                    class_.name, DISPATCH_TABLE_OFFSET
                 ),
                 true,
             );
@@ -351,178 +242,151 @@ impl<'a> CodeGenerator<'a> {
             self.method_end();
         }
 
-        let classes = &self.ctx.classes.clone();
-        for class_ in classes {
-            self.environment.curr_class = class_.name.clone();
-
-            for feature in &class_.features {
-                match feature {
-                    Feature::Attribute(_) => {}
-                    Feature::Constructor(decl) => {
-                        let decl_type = decl.param.deref();
-                        let iter: Vec<String> = decl_type.iter().map(|x| x.1.to_string()).collect();
-                        self.code_method_constructor(&class_.name, feature, Some(iter));
-                    }
-                    Feature::Method(_) => {
-                        self.code_method_constructor(&class_.name, feature, None);
-                    }
-                }
+        for class_ in &program.classes {
+            for method in &class_.methods {
+                self.code_method_constructor(method);
             }
         }
     }
 
-    fn code_method_constructor(
-        &mut self,
-        curr_class: &String,
-        feature: &Feature,
-        specific_construtor: Option<Vec<String>>,
-    ) {
-        self.environment
-            .env
-            .get_mut(curr_class)
-            .unwrap()
-            .enter_scope();
-
-        let mut offset = 0;
-        let len = feature.get_param_len();
-        for param in feature.clone().get_param().deref() {
-            self.environment.env.get_mut(curr_class).unwrap().add(
-                &param.0,
-                &Location {
-                    reg: "%rbp".to_string(),
-                    offset: 8 * (3 + len - 1 - offset),
-                    // type_: param.1.clone(),
-                },
-            );
-            offset += 1;
-        }
-
-        if let Some(expr_) = feature.get_body().deref() {
-            self.environment.var_offset = 1;
-            self.environment
-                .env
-                .get_mut(curr_class)
-                .unwrap()
-                .enter_scope();
-
-            // self.write(format!("{}.{}:", curr_class, method.name), false);
-            match feature {
-                Feature::Method(method) => {
-                    self.write(format!("{}.{}:", curr_class, method.name), false)
+    fn code_method_constructor(&mut self, method: &Method) {
+        self.write(format!("{}:", method.label), false);
+        self.method_start();
+        let body = match &method.body {
+            Some(body) => body,
+            None => {
+                match method.kind {
+                    MethodKind::Method => self.write(format!("movq $Object_prototype, %rax"), true),
+                    MethodKind::Constructor => self.write(format!("movq %rbx, %rax"), true),
                 }
-                Feature::Constructor(_) => self.write(
-                    format!(
-                        "{}.Constructor_{}:",
-                        curr_class,
-                        specific_construtor.unwrap().join("_")
-                    ),
-                    false,
-                ),
-                _ => {}
-            }
-            self.method_start();
-
-            // sub rsp to store local var
-            let mut var_vec = Vec::new();
-            for expr in expr_ {
-                var_vec.append(&mut expr.get_var_num());
+                self.method_end();
+                return;
             }
-            let align_stack;
+        };
 
-            // if attr's len is odd
-            if len % 2 == 0 {
-                align_stack = crate::utils::util::align_to_16_bit(var_vec.len() * 8) + 8;
-            } else {
-                align_stack = crate::utils::util::align_to_16_bit(var_vec.len() * 8);
-            }
-            self.environment.align_stack = align_stack;
-            self.write(format!("subq ${}, %rsp", align_stack), true);
+        // sub rsp to store local var
+        let align_stack;
+        // if attr's len is odd
+        if method.params.len() % 2 == 0 {
+            align_stack = crate::utils::util::align_to_16_bit(method.locals * 8) + 8;
+        } else {
+            align_stack = crate::utils::util::align_to_16_bit(method.locals * 8);
+        }
+        self.environment.align_stack = align_stack;
+        self.write(format!("subq ${}, %rsp", align_stack), true);
 
-            for expr in expr_ {
-                expr.code_generate(self);
-            }
+        for expr in body {
+            expr.code_generate(self);
+        }
 
-            self.environment
-                .env
-                .get_mut(curr_class)
-                .unwrap()
-                .exit_scope();
-            match feature {
-                Feature::Constructor(_) => {
-                    self.write(format!("movq %rbx, %rax"), true);
-                    self.write(format!("addq ${}, %rsp", align_stack), true);
-                    self.method_end();
-                }
-                _ => {}
+        match method.kind {
+            MethodKind::Constructor => {
+                self.write(format!("movq %rbx, %rax"), true);
+                self.write(format!("addq ${}, %rsp", align_stack), true);
+                self.method_end();
             }
-        } else {
-            match feature {
-                Feature::Method(method) => {
-                    self.write(format!("{}.{}:", curr_class, method.name), false);
-                    self.method_start();
-                    self.write(format!("movq $Object_prototype, %rax"), true);
-                    self.method_end();
-                }
-                Feature::Constructor(_) => {
-                    self.write(
-                        format!(
-                            "{}.Constructor_{}:",
-                            curr_class,
-                            specific_construtor.unwrap().join("_")
-                        ),
-                        false,
-                    );
-                    self.method_start();
-                    self.write(format!("movq %rbx, %rax"), true);
-                    self.method_end();
-                }
-                _ => {}
+            // * a Void method may run off its end
+            MethodKind::Method if !method.always_returns => {
+                self.write(format!("addq ${}, %rsp", align_stack), true);
+                self.method_end();
             }
+            //* a method that always returns has ended on every path
+            MethodKind::Method => {}
         }
-        self.environment
-            .env
-            .get_mut(curr_class)
-            .unwrap()
-            .exit_scope();
     }
 
     fn code_main(&mut self) {
+        //* the entry class is created like `new Main`, so its constructor without parameters runs
+        let (class, method) = (&self.program.entry_class, &self.program.entry_method);
+        let has_constructor = self.program.class(class).map_or(false, |main| {
+            main.methods
+                .iter()
+                .any(|m| m.kind == MethodKind::Constructor && m.params.is_empty())
+        });
+        let constructor = if has_constructor {
+            format!("\n    call {}.{}", class, CONSTRUCTOR)
+        } else {
+            String::new()
+        };
         self.write(
             format!(
                 ".globl main
 main:
-    pushq $Main_prototype
+    pushq ${0}_prototype
     call Object.malloc
     addq $8, %rsp
     movq %rax, %rbx
-    call Main.init
+    call {0}.init
     # 0x....d9b8
     movq %rbx, %rax
     subq $8, %rsp
-    # 0x....d9b0
-    call Main.main
+    # 0x....d9b0{2}
+    call {0}.{1}
     # movq 24(%rax), %rax
     addq $8, %rsp
-    ret "
+    ret ",
+                class, method, constructor
             ),
             true,
         );
     }
 
-    fn code_abort(&mut self) {
-        self.write(format!("abort:"), false);
+    /// Runtime errors throw an exception carrying `err_msg`.
+    fn code_abort(&mut self, label: &str, class_name: &str, err_msg: &str) {
+        self.write(format!("{}:", label), false);
+        // the frame is unwound by the throw, only malloc cares about the stack
+        self.write(format!("andq $-16, %rsp"), true);
+        self.write(format!("pushq ${}_prototype", class_name), true);
+        self.write(format!("call Object.malloc"), true);
+        self.write(format!("addq $8, %rsp"), true);
+        self.write(format!("call {}.init", class_name), true);
+        self.write(
+            format!(
+                "movq $str_const_{}, {}(%rax)",
+                self.str_const_table.get(err_msg).unwrap(),
+                FIELD_BASIC_OFFSET
+            ),
+            true,
+        );
+        self.write(format!("jmp exception_throw"), true);
+    }
+
+    /// Throws the exception in %rax to the innermost handler,
+    /// or prints its message and exits when there is none.
+    fn code_throw(&mut self) {
+        self.write(format!("exception_throw:"), false);
+        self.write(format!("movq exception_handler, %rdi"), true);
+        self.write(format!("cmpq $0, %rdi"), true);
+        self.write(format!("je exception_uncaught"), true);
+        self.write(format!("movq 8(%rdi), %rbp"), true);
+        self.write(format!("movq 16(%rdi), %rbx"), true);
+        self.write(format!("movq 24(%rdi), %rsp"), true);
+        self.write(format!("movq (%rdi), %rsi"), true);
+        self.write(format!("movq %rsi, exception_handler"), true);
+        self.write(format!("jmp *32(%rdi)"), true);
+
+        self.write(format!("exception_uncaught:"), false);
+        self.write(format!("pushq %rax"), true);
         self.write(format!("movq $1, %rax"), true);
         self.write(format!("movq $2, %rdi"), true);
         self.write(
             format!(
                 "movq $str_const_ascii_{}, %rsi",
-                self.str_const_table.get(RUNTIME_ERR).unwrap()
+                self.str_const_table.get(UNCAUGHT_ERR).unwrap()
             ),
             true,
         );
-        self.write(format!("movq ${}, %rdx", RUNTIME_ERR.len()), true);
+        self.write(format!("movq ${}, %rdx", UNCAUGHT_ERR.len()), true);
+        self.write(format!("syscall"), true);
+        self.write(format!("popq %rax"), true);
+        self.write(format!("movq {}(%rax), %rax", FIELD_BASIC_OFFSET), true);
+        self.write(format!("movq {}(%rax), %rdx", STRING_CONST_VAL_OFFSET + 8), true);
+        self.write(format!("movq {}(%rax), %rsi", STRING_CONST_VAL_OFFSET), true);
+        self.write(format!("movq $1, %rax"), true);
+        self.write(format!("movq $2, %rdi"), true);
         self.write(format!("syscall"), true);
 
+        self.write(format!("movq $1, %rdi"), true);
         self.write(format!("call exit"), true);
     }
 }
@@ -0,0 +1,681 @@
+use crate::{
+    ir::{Call, Cast, Cond, Expr, ExprKind, FieldGet, For, Match, New, Try, Var, Variant},
+    parser::ast::{
+        class::mangle_method,
+        expr::{ComputeOp, CondOp},
+        Type,
+    },
+    BOOL_CONST_VAL_OFFSET, DISPATCH_TABLE_OFFSET, INT, INTEGER, INT_CONST_VAL_OFFSET,
+    NULL_TAG_OFFSET, OBJECT, PARENT_TABLE_OFFSET, STRING, STRING_CONST_VAL_OFFSET,
+};
+
+use super::cgen::{CodeGenerator, TryFrame};
+
+pub trait CodeGenerate {
+    fn code_generate(&self, code_generator: &mut CodeGenerator);
+}
+
+/// Where a variable is, as an operand.
+fn location(var: &Var) -> String {
+    match var {
+        Var::Local(local) => return format!("{}(%rbp)", -8 * *local as i32),
+        //* the arguments are pushed in order, above the saved %rbp, %rbx and return address
+        Var::Param { index, count } => return format!("{}(%rbp)", 8 * (3 + count - 1 - index)),
+        Var::Field(offset) => return format!("{}(%rbx)", offset),
+    }
+}
+
+fn local(local: usize) -> String {
+    return location(&Var::Local(local));
+}
+
+impl CodeGenerate for Expr {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        match &self.kind {
+            ExprKind::Int(const_) => {
+                code_generator.write(format!("movq ${}, %rax", const_), true);
+            }
+            ExprKind::Str(const_) => {
+                let index = code_generator.str_const_table.get(const_.as_str()).unwrap();
+                code_generator.write(format!("movq $str_const_{}, %rax", index), true);
+            }
+
+            ExprKind::Bool(const_) => {
+                let index = if *const_ { 1 } else { 0 };
+                code_generator.write(format!("movq $bool_const_{}, %rax", index), true);
+            }
+
+            // * null is the prototype of Object, whose null tag is 0
+            ExprKind::Null => {
+                code_generator.write(format!("movq ${}_prototype, %rax", OBJECT), true);
+            }
+
+            ExprKind::Prototype(class_name) => {
+                code_generator.write(format!("movq ${}_prototype, %rax", class_name), true);
+            }
+
+            ExprKind::Asm(s) => {
+                let mut fix_asm_code: String;
+                fix_asm_code = s.replace(
+                    "%d",
+                    format!(
+                        "$str_const_ascii_{}",
+                        code_generator.str_const_table.get("%d").unwrap()
+                    )
+                    .as_str(),
+                );
+                fix_asm_code = fix_asm_code.replace(
+                    "INT_CONST_VAL_OFFSET",
+                    INT_CONST_VAL_OFFSET.to_string().as_str(),
+                );
+                fix_asm_code = fix_asm_code.replace(
+                    "STRING_CONST_VAL_OFFSET",
+                    STRING_CONST_VAL_OFFSET.to_string().as_str(),
+                );
+
+                code_generator.write(format!("{}", fix_asm_code), true);
+            }
+
+            ExprKind::Self_ => {
+                code_generator.write(format!("movq %rbx, %rax"), true);
+            }
+
+            ExprKind::Var(var) => {
+                code_generator.write(format!("movq {}, %rax", location(var)), true);
+            }
+
+            ExprKind::Assign(var, value) => {
+                value.code_generate(code_generator);
+                code_generator.write(format!("movq %rax, {}", location(var)), true);
+            }
+
+            ExprKind::Let(locals) => {
+                for (slot, init) in locals {
+                    init.code_generate(code_generator);
+                    code_generator.write(format!("movq %rax, {}", local(*slot)), true);
+                }
+            }
+
+            ExprKind::Call(e) => e.code_generate(code_generator),
+
+            ExprKind::Field(e) => e.code_generate(code_generator),
+
+            ExprKind::New(e) => e.code_generate(code_generator),
+
+            ExprKind::Variant(e) => e.code_generate(code_generator),
+
+            ExprKind::Compute(op, left, right) => {
+                code_operands(code_generator, left, right);
+                match op {
+                    ComputeOp::Add => {
+                        code_generator.write(format!("addq %r10, %r11"), true);
+                    }
+                    ComputeOp::Minus => {
+                        code_generator.write(format!("subq %r10, %r11"), true);
+                    }
+                    ComputeOp::Mul => {
+                        code_generator.write(format!("movq %r11, %rax"), true);
+                        code_generator.write(format!("mulq %r10"), true);
+                        code_generator.write(format!("movq %rax, %r11"), true);
+                    }
+                    ComputeOp::Divide => {
+                        code_generator.write(format!("cmpq $0, %r10"), true);
+                        code_generator.write(format!("je abort_div"), true);
+                        code_generator.write(format!("movq %r11, %rax"), true);
+                        // * sign extend %rax into %rdx:%rax for the signed division
+                        code_generator.write(format!("cqto"), true);
+                        code_generator.write(format!("idivq %r10"), true);
+                        code_generator.write(format!("movq %rax, %r11"), true);
+                    }
+                };
+                code_generator.write(format!("movq %r11, %rax"), true);
+            }
+
+            ExprKind::Compare(op, left, right) => {
+                code_operands(code_generator, left, right);
+                code_generator.write(format!("movq $bool_const_1, %rdi"), true);
+                code_generator.write(format!("movq $bool_const_0, %rax"), true);
+                code_generator.write(format!("subq %r10, %r11"), true);
+                match op {
+                    CondOp::More => code_generator.write(format!("cmovg %rdi, %rax"), true),
+                    CondOp::MoreE => code_generator.write(format!("cmovge %rdi, %rax"), true),
+                    CondOp::Less => code_generator.write(format!("cmovl %rdi, %rax"), true),
+                    CondOp::LessE => code_generator.write(format!("cmovle %rdi, %rax"), true),
+                    CondOp::Equal => code_generator.write(format!("cmove %rdi, %rax"), true),
+                }
+            }
+
+            ExprKind::Concat(left, right) => {
+                // left is in stack
+                // %rax is right
+                left.code_generate(code_generator);
+                code_generator.write(format!("pushq %rax"), true);
+                right.code_generate(code_generator);
+                code_generator.write(format!("movq (%rsp), %r10"), true);
+                code_generator.write(format!("movq %rax, %r11"), true);
+                code_generator.write(format!("pushq %r10"), true);
+                code_generator.write(format!("pushq %r11"), true);
+                let concat = mangle_method(&"concat".to_string(), &vec![STRING.to_string(); 2]);
+                code_generator.write(format!("call String.{}", concat), true);
+                code_generator.write(format!("addq $24, %rsp"), true);
+            }
+
+            ExprKind::Not(expr) => {
+                expr.code_generate(code_generator);
+                // if true retrun false
+                code_generator.write(format!("movq $bool_const_1, %r10"), true);
+                code_generator.write(format!("movq $bool_const_0, %r11"), true);
+                code_generator.write(format!("movq {}(%rax), %rax", BOOL_CONST_VAL_OFFSET), true);
+                code_generator.write(format!("cmpq $1, %rax"), true);
+                code_generator.write(format!("cmove %r11, %rax"), true);
+                code_generator.write(format!("cmovne %r10, %rax"), true);
+            }
+
+            ExprKind::Isnull(expr) => {
+                expr.code_generate(code_generator);
+                // if null return True
+                code_generator.write(format!("movq $bool_const_1, %r10"), true);
+                code_generator.write(format!("movq $bool_const_0, %r11"), true);
+                code_generator.write(format!("movq {}(%rax), %rax", NULL_TAG_OFFSET), true);
+                code_generator.write(format!("cmpq $1, %rax"), true);
+                code_generator.write(format!("cmove %r11, %rax"), true);
+                code_generator.write(format!("cmovne %r10, %rax"), true);
+            }
+
+            ExprKind::Box_(expr) => {
+                expr.code_generate(code_generator);
+                if self.type_ == INTEGER {
+                    code_generator.write(format!("pushq %rax"), true);
+                    code_generator.write(format!("pushq ${}_prototype", INTEGER), true);
+                    code_generator.write(format!("call Object.malloc"), true);
+                    code_generator.write(format!("addq $8, %rsp"), true);
+                    code_generator.write(format!("call {}.init", INTEGER), true);
+                    code_generator.write(format!("popq %rdi"), true);
+                    code_generator
+                        .write(format!("movq %rdi, {}(%rax)", INT_CONST_VAL_OFFSET), true);
+                } else {
+                    // * a bool is boxed into one of the two Bool constants
+                    code_generator.write(format!("movq %rax, %r10"), true);
+                    code_generator.write(format!("movq $bool_const_1, %rdi"), true);
+                    code_generator.write(format!("movq $bool_const_0, %rax"), true);
+                    code_generator.write(format!("cmpq $0, %r10"), true);
+                    code_generator.write(format!("cmovne %rdi, %rax"), true);
+                }
+            }
+
+            ExprKind::Unbox(expr) => {
+                expr.code_generate(code_generator);
+                let offset = if self.type_ == INT {
+                    INT_CONST_VAL_OFFSET
+                } else {
+                    BOOL_CONST_VAL_OFFSET
+                };
+                code_generator.write(format!("movq {}(%rax), %rax", offset), true);
+            }
+
+            ExprKind::Is(expr, class_name) => code_type_test(code_generator, expr, class_name),
+
+            ExprKind::Cast(e) => e.code_generate(code_generator),
+
+            ExprKind::Coalesce(left, right) => {
+                let label_done = code_generator.environment.label + 1;
+                code_generator.environment.label += 1;
+
+                left.code_generate(code_generator);
+                code_generator.write(format!("cmpq $0, {}(%rax)", NULL_TAG_OFFSET), true);
+                code_generator.write(format!("jne label_{}", label_done), true);
+                right.code_generate(code_generator);
+                code_generator.write(format!("label_{}:", label_done), false);
+            }
+
+            ExprKind::Cond(e) => e.code_generate(code_generator),
+
+            ExprKind::While(test, body) => code_while(code_generator, test, body),
+
+            ExprKind::For(e) => e.code_generate(code_generator),
+
+            ExprKind::Block(body) => {
+                for expr in body {
+                    expr.code_generate(code_generator);
+                }
+            }
+
+            ExprKind::Return(val) => {
+                if let Some(val) = val {
+                    val.code_generate(code_generator);
+                }
+                code_return(code_generator);
+            }
+
+            ExprKind::Throw(expr) => {
+                expr.code_generate(code_generator);
+                code_generator.write(format!("jmp exception_throw"), true);
+            }
+
+            ExprKind::Try(e) => e.code_generate(code_generator),
+
+            ExprKind::Match(e) => e.code_generate(code_generator),
+        }
+    }
+}
+
+/// The left operand in %r11, the right one in %r10.
+fn code_operands(code_generator: &mut CodeGenerator, left: &Expr, right: &Expr) {
+    // r10-r11 for temp register
+    left.code_generate(code_generator);
+    code_generator.write(format!("pushq %rax"), true);
+    right.code_generate(code_generator);
+    code_generator.write(format!("movq %rax, %r10"), true);
+    code_generator.write(format!("movq (%rsp), %r11"), true);
+    code_generator.write(format!("addq $8, %rsp"), true);
+}
+
+/// Returns %rax, through the finally of every enclosing try.
+fn code_return(code_generator: &mut CodeGenerator) {
+    match code_generator.environment.try_stack.last().cloned() {
+        Some(frame) => {
+            code_generator.write(format!("movq %rax, {}(%rbp)", frame.value), true);
+            code_generator.write(format!("movq $2, {}(%rbp)", frame.mode), true);
+            code_uninstall_handler(code_generator, frame.record);
+            code_generator.write(format!("jmp label_{}", frame.label_finally), true);
+        }
+        None => {
+            code_generator.write(
+                format!("addq ${}, %rsp", code_generator.environment.align_stack),
+                true,
+            );
+            code_generator.method_end();
+        }
+    }
+}
+
+impl CodeGenerate for Call {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        // * the target runs before the arguments and waits for them in its local
+        self.target.code_generate(code_generator);
+        code_generator.write(format!("movq %rax, {}", local(self.receiver)), true);
+        // * a safe dispatch skips the call and gives the null target back
+        let label_done = code_generator.environment.label + 1;
+        if self.safe {
+            code_generator.environment.label += 1;
+            code_generator.write(format!("cmpq $0, {}(%rax)", NULL_TAG_OFFSET), true);
+            code_generator.write(format!("je label_{}", label_done), true);
+        }
+
+        for arg in &self.args {
+            arg.code_generate(code_generator);
+            code_generator.write(format!("pushq %rax"), true);
+        }
+        code_generator.write(format!("movq {}, %rax", local(self.receiver)), true);
+
+        code_generator.write(format!("cmpq $0, {}(%rax)", NULL_TAG_OFFSET), true);
+        code_generator.write(format!("je abort"), true);
+        code_generator.write(format!("movq {}(%rax), %rdi", DISPATCH_TABLE_OFFSET), true);
+        code_generator.write(format!("call *{}(%rdi)", self.slot), true);
+        code_generator.write(format!("addq ${}, %rsp", self.args.len() * 8), true);
+
+        if self.safe {
+            code_generator.write(format!("label_{}:", label_done), false);
+        }
+    }
+}
+
+impl CodeGenerate for FieldGet {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        self.target.code_generate(code_generator);
+        if self.safe {
+            let label_done = code_generator.environment.label + 1;
+            code_generator.environment.label += 1;
+            code_generator.write(format!("cmpq $0, {}(%rax)", NULL_TAG_OFFSET), true);
+            code_generator.write(format!("je label_{}", label_done), true);
+            code_generator.write(format!("movq {}(%rax), %rax", self.offset), true);
+            code_generator.write(format!("label_{}:", label_done), false);
+        } else {
+            code_generator.write(format!("movq {}(%rax), %rax", self.offset), true);
+        }
+    }
+}
+
+impl CodeGenerate for New {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        for arg in &self.args {
+            arg.code_generate(code_generator);
+            code_generator.write(format!("pushq %rax"), true);
+        }
+        code_generator.write(format!("pushq ${}_prototype", self.class_name), true);
+        code_generator.write(format!("call Object.malloc"), true);
+        code_generator.write(format!("addq $8, %rsp"), true);
+        code_generator.write(format!("call {}.init", self.class_name), true);
+
+        if let Some(constructor) = &self.constructor {
+            code_generator.write(format!("call {}", constructor), true);
+        }
+        if !self.args.is_empty() {
+            code_generator.write(format!("addq ${}, %rsp", self.args.len() * 8), true);
+        }
+    }
+}
+
+impl CodeGenerate for Cond {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        let label_then = code_generator.environment.label + 1;
+        let label_done = code_generator.environment.label + 2;
+        code_generator.environment.label += 2;
+
+        // if jump to then
+        // eval test
+        // jmp -> label_0
+        self.test.code_generate(code_generator);
+        // is bool type
+        code_generator.write(format!("movq {}(%rax), %rax", BOOL_CONST_VAL_OFFSET), true);
+        code_generator.write(format!("cmpq $1, %rax"), true);
+        code_generator.write(format!("je label_{}", label_then), true);
+        // else body
+        for else_ in &self.else_body {
+            else_.code_generate(code_generator);
+        }
+        // jmp  label_1
+        code_generator.write(format!("jmp label_{}", label_done), true);
+
+        // label_0: then body
+        code_generator.write(format!("label_{}:", label_then), false);
+        for then in &self.then_body {
+            then.code_generate(code_generator);
+        }
+
+        //  done:
+        code_generator.write(format!("label_{}:", label_done), false);
+    }
+}
+
+fn code_while(code_generator: &mut CodeGenerator, test: &Expr, body: &Vec<Expr>) {
+    // jmp test ->label_loop + 1
+    // loop:    label_loop
+    //      body
+    // test:
+    //      test.code
+    //      goto loop
+
+    let label_loop = code_generator.environment.label + 1;
+    let lable_done = label_loop + 1;
+    code_generator.environment.label += 2;
+
+    code_generator.write(format!("jmp label_{}", lable_done), true);
+
+    code_generator.write(format!("label_{}:", label_loop), false);
+    for body_ in body {
+        body_.code_generate(code_generator);
+    }
+
+    code_generator.write(format!("label_{}:", lable_done), false);
+
+    test.code_generate(code_generator);
+    // is bool type
+    code_generator.write(format!("movq {}(%rax), %rax", BOOL_CONST_VAL_OFFSET), true);
+    code_generator.write(format!("cmpq $1, %rax"), true);
+    code_generator.write(format!("je label_{}", label_loop), true);
+}
+
+impl CodeGenerate for For {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        let label_loop = code_generator.environment.label + 1;
+        let lable_done = label_loop + 1;
+        code_generator.environment.label += 2;
+
+        for init_ in &self.init {
+            init_.code_generate(code_generator);
+        }
+
+        code_generator.write(format!("jmp label_{}", lable_done), true);
+
+        code_generator.write(format!("label_{}:", label_loop), false);
+
+        for body_ in &self.body {
+            body_.code_generate(code_generator);
+        }
+
+        for iter_ in &self.iter {
+            iter_.code_generate(code_generator);
+        }
+
+        code_generator.write(format!("label_{}:", lable_done), false);
+        for test_ in &self.test {
+            test_.code_generate(code_generator);
+            // is bool type
+            code_generator.write(format!("movq {}(%rax), %rax", BOOL_CONST_VAL_OFFSET), true);
+        }
+
+        code_generator.write(format!("cmpq $1, %rax"), true);
+        code_generator.write(format!("je label_{}", label_loop), true);
+    }
+}
+
+impl CodeGenerate for Variant {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        let len = self.fields.len();
+
+        for (_, expr) in &self.fields {
+            expr.code_generate(code_generator);
+            code_generator.write(format!("pushq %rax"), true);
+        }
+        code_generator.write(format!("pushq ${}_prototype", self.enum_name), true);
+        code_generator.write(format!("call Object.malloc"), true);
+        code_generator.write(format!("addq $8, %rsp"), true);
+        code_generator.write(format!("call {}.init", self.enum_name), true);
+
+        code_generator.write(format!("movq ${}, {}(%rax)", self.tag, self.tag_offset), true);
+        for (index, (slot_offset, _)) in self.fields.iter().enumerate() {
+            code_generator.write(
+                format!("movq {}(%rsp), %r10", (len - 1 - index) * 8),
+                true,
+            );
+            code_generator.write(format!("movq %r10, {}(%rax)", slot_offset), true);
+        }
+        code_generator.write(format!("addq ${}, %rsp", len * 8), true);
+    }
+}
+
+impl CodeGenerate for Match {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        // eval expr and keep it in a local slot
+        // for every arm:
+        //      cmp tag, jne next arm
+        //      bind fields to local slots
+        //      eval guard, jne next arm
+        //      eval body, jmp done
+        let label_done = code_generator.environment.label + 1;
+        code_generator.environment.label += 1;
+
+        self.expr.code_generate(code_generator);
+        let value = local(self.local);
+        code_generator.write(format!("movq %rax, {}", value), true);
+
+        for arm in &self.arms {
+            let label_next = code_generator.environment.label + 1;
+            code_generator.environment.label += 1;
+
+            if let Some(tag) = arm.tag {
+                code_generator.write(format!("movq {}, %rax", value), true);
+                code_generator.write(format!("cmpq ${}, {}(%rax)", tag, self.tag_offset), true);
+                code_generator.write(format!("jne label_{}", label_next), true);
+
+                for (slot_offset, binding) in &arm.bindings {
+                    code_generator.write(format!("movq {}, %rax", value), true);
+                    code_generator.write(format!("movq {}(%rax), %rax", slot_offset), true);
+                    code_generator.write(format!("movq %rax, {}", local(*binding)), true);
+                }
+            }
+
+            if let Some(guard) = &arm.guard {
+                guard.code_generate(code_generator);
+                code_generator.write(format!("movq {}(%rax), %rax", BOOL_CONST_VAL_OFFSET), true);
+                code_generator.write(format!("cmpq $1, %rax"), true);
+                code_generator.write(format!("jne label_{}", label_next), true);
+            }
+
+            arm.body.code_generate(code_generator);
+            code_generator.write(format!("jmp label_{}", label_done), true);
+            code_generator.write(format!("label_{}:", label_next), false);
+        }
+
+        // * exhaustiveness is checked, falling through is a runtime error
+        code_generator.write(format!("jmp abort_match"), true);
+        code_generator.write(format!("label_{}:", label_done), false);
+    }
+}
+
+/// Walk the parent chain of the dispatch table of the object in %rax,
+/// jump to `label_found` if it reaches `class_name`, fall through otherwise.
+fn code_class_chain(code_generator: &mut CodeGenerator, class_name: &Type, label_found: usize) {
+    let label_loop = code_generator.environment.label + 1;
+    code_generator.environment.label += 1;
+
+    code_generator.write(format!("movq {}(%rax), %rdi", DISPATCH_TABLE_OFFSET), true);
+    code_generator.write(format!("label_{}:", label_loop), false);
+    code_generator.write(format!("cmpq ${}_dispatch_table, %rdi", class_name), true);
+    code_generator.write(format!("je label_{}", label_found), true);
+    code_generator.write(format!("movq -{}(%rdi), %rdi", PARENT_TABLE_OFFSET), true);
+    code_generator.write(format!("cmpq $0, %rdi"), true);
+    code_generator.write(format!("jne label_{}", label_loop), true);
+}
+
+fn code_type_test(code_generator: &mut CodeGenerator, expr: &Expr, class_name: &Type) {
+    // null is not an instance of anything
+    let label_false = code_generator.environment.label + 1;
+    let label_true = code_generator.environment.label + 2;
+    let label_done = code_generator.environment.label + 3;
+    code_generator.environment.label += 3;
+
+    expr.code_generate(code_generator);
+    code_generator.write(format!("cmpq $0, {}(%rax)", NULL_TAG_OFFSET), true);
+    code_generator.write(format!("je label_{}", label_false), true);
+    code_class_chain(code_generator, class_name, label_true);
+
+    code_generator.write(format!("label_{}:", label_false), false);
+    code_generator.write(format!("movq $bool_const_0, %rax"), true);
+    code_generator.write(format!("jmp label_{}", label_done), true);
+    code_generator.write(format!("label_{}:", label_true), false);
+    code_generator.write(format!("movq $bool_const_1, %rax"), true);
+    code_generator.write(format!("label_{}:", label_done), false);
+}
+
+impl CodeGenerate for Cast {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        self.expr.code_generate(code_generator);
+
+        // upcasts always succeed, null can be cast to anything
+        if !self.checked {
+            return;
+        }
+        let label_done = code_generator.environment.label + 1;
+        code_generator.environment.label += 1;
+
+        code_generator.write(format!("cmpq $0, {}(%rax)", NULL_TAG_OFFSET), true);
+        code_generator.write(format!("je label_{}", label_done), true);
+        code_class_chain(code_generator, &self.class_name, label_done);
+        code_generator.write(format!("jmp abort_cast"), true);
+        code_generator.write(format!("label_{}:", label_done), false);
+    }
+}
+
+/// The handler record lives in the frame and is linked into `exception_handler`
+/// while the body and the catches run, a throw restores the registers it saved
+/// and jumps to its catch label. Returns and uncaught exceptions go through
+/// the finally, which picks up what to do from the mode slot.
+impl CodeGenerate for Try {
+    fn code_generate(&self, code_generator: &mut CodeGenerator) {
+        let base = self.base as i32;
+        let record = -8 * (base + 4);
+        let thrown = -8 * (base + 5);
+        let mode = -8 * (base + 6);
+        let value = -8 * (base + 7);
+
+        let label_catch = code_generator.environment.label + 1;
+        let label_catch_fail = code_generator.environment.label + 2;
+        let label_finally = code_generator.environment.label + 3;
+        let label_end = code_generator.environment.label + 4;
+        code_generator.environment.label += 4;
+
+        code_generator.write(format!("movq $0, {}(%rbp)", mode), true);
+        code_generator.write(format!("movq exception_handler, %rax"), true);
+        code_generator.write(format!("movq %rax, {}(%rbp)", record), true);
+        code_generator.write(format!("movq %rbp, {}(%rbp)", record + 8), true);
+        code_generator.write(format!("movq %rbx, {}(%rbp)", record + 16), true);
+        code_generator.write(format!("movq %rsp, {}(%rbp)", record + 24), true);
+        code_install_handler(code_generator, record, label_catch);
+
+        code_generator.environment.try_stack.push(TryFrame {
+            record,
+            mode,
+            value,
+            label_finally,
+        });
+
+        for expr_ in &self.body {
+            expr_.code_generate(code_generator);
+        }
+        code_uninstall_handler(code_generator, record);
+        code_generator.write(format!("jmp label_{}", label_finally), true);
+
+        // * a throw in the body lands here with the handler already unlinked
+        code_generator.write(format!("label_{}:", label_catch), false);
+        code_generator.write(format!("movq %rax, {}(%rbp)", thrown), true);
+        code_install_handler(code_generator, record, label_catch_fail);
+        for catch in &self.catches {
+            let label_found = code_generator.environment.label + 1;
+            let label_next = code_generator.environment.label + 2;
+            code_generator.environment.label += 2;
+
+            code_generator.write(format!("movq {}(%rbp), %rax", thrown), true);
+            code_class_chain(code_generator, &catch.class_name, label_found);
+            code_generator.write(format!("jmp label_{}", label_next), true);
+
+            code_generator.write(format!("label_{}:", label_found), false);
+            code_generator.write(format!("movq %rax, {}", local(catch.local)), true);
+            for expr_ in &catch.body {
+                expr_.code_generate(code_generator);
+            }
+            code_uninstall_handler(code_generator, record);
+            code_generator.write(format!("jmp label_{}", label_finally), true);
+            code_generator.write(format!("label_{}:", label_next), false);
+        }
+        // * nothing caught it, rethrow after the finally
+        code_uninstall_handler(code_generator, record);
+        code_generator.write(format!("movq $1, {}(%rbp)", mode), true);
+        code_generator.write(format!("jmp label_{}", label_finally), true);
+
+        // * a throw in a catch replaces the exception
+        code_generator.write(format!("label_{}:", label_catch_fail), false);
+        code_generator.write(format!("movq %rax, {}(%rbp)", thrown), true);
+        code_generator.write(format!("movq $1, {}(%rbp)", mode), true);
+
+        code_generator.write(format!("label_{}:", label_finally), false);
+        code_generator.environment.try_stack.pop();
+        for expr_ in self.finally.iter().flatten() {
+            expr_.code_generate(code_generator);
+        }
+        code_generator.write(format!("movq {}(%rbp), %rax", mode), true);
+        code_generator.write(format!("cmpq $0, %rax"), true);
+        code_generator.write(format!("je label_{}", label_end), true);
+        code_generator.write(format!("cmpq $1, %rax"), true);
+        code_generator.write(format!("movq {}(%rbp), %rax", thrown), true);
+        code_generator.write(format!("je exception_throw"), true);
+        code_generator.write(format!("movq {}(%rbp), %rax", value), true);
+        code_return(code_generator);
+        code_generator.write(format!("label_{}:", label_end), false);
+    }
+}
+
+fn code_install_handler(code_generator: &mut CodeGenerator, record: i32, label_catch: usize) {
+    code_generator.write(
+        format!("movq $label_{}, {}(%rbp)", label_catch, record + 32),
+        true,
+    );
+    code_generator.write(format!("leaq {}(%rbp), %rax", record), true);
+    code_generator.write(format!("movq %rax, exception_handler"), true);
+}
+
+fn code_uninstall_handler(code_generator: &mut CodeGenerator, record: i32) {
+    code_generator.write(format!("movq {}(%rbp), %rdi", record), true);
+    code_generator.write(format!("movq %rdi, exception_handler"), true);
+}
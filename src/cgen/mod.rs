@@ -1,24 +1,12 @@
-use std::{cell::RefCell, process::Command};
+use crate::{cgen::cgen::CodeGenerator, ir::Program};
 
-use owo_colors::OwoColorize;
-
-use crate::{cgen::cgen::CodeGenerator, ctx::CompileContext};
-
-mod ast;
 pub mod cgen;
+mod expr;
 
-pub fn code_gen(ctx: CompileContext) {
-    let mut asm_file = std::fs::File::create("./build/a.s").expect("create failed");
-    let mut cgen = CodeGenerator::new(ctx, &mut asm_file);
+/// The assembly of the whole program.
+pub fn asm(program: &Program) -> String {
+    let mut asm = vec![];
+    let mut cgen = CodeGenerator::new(program, &mut asm);
     cgen.code_generate();
-    Command::new("gcc")
-        .arg("-no-pie")
-        .arg("-static")
-        .arg("-m64")
-        .arg("./build/a.s")
-        .arg("-o")
-        .arg("./build/a.out")
-        .spawn()
-        .expect("gcc command failed to start");
-    println!("{}", "🔑 Congratulations you successfully generated assembly code, please execute ./build/a.out in your shell!".green());
+    return String::from_utf8(asm).expect("the assembly is not UTF-8");
 }
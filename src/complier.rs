@@ -1,84 +1,307 @@
-use std::borrow::{Borrow, BorrowMut};
-use std::cell::RefCell;
-use std::fs::{self, metadata, File};
-use std::io::Read;
+use std::fs::{self, metadata};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
 use std::process::Command;
 
-use clap::parser;
 use owo_colors::OwoColorize;
-use simple_home_dir::home_dir;
-
-use crate::cgen::cgen;
-use crate::ctx::CompileContext;
-use crate::lexer::lexer::Lexer;
-use crate::lexer::{self, Position};
-use crate::parser::ast::class::Class;
-use crate::semantic::semantic::{SemanticChecker, SemanticError};
-use crate::utils::table::ClassTable;
-use crate::{semantic, strawberry};
-
-pub fn build() {
-    let mut curr_path = "./src".to_string();
-    let mut path_flag = true;
-    let home_dir = home_dir().unwrap().into_os_string().into_string().unwrap();
-    let mut files: Vec<String> = vec![
-        format!("{}/.strawberry/std/Object.st", home_dir),
-        format!("{}/.strawberry/std/Integer.st", home_dir),
-        format!("{}/.strawberry/std/String.st", home_dir),
-        format!("{}/.strawberry/std/Bool.st", home_dir),
-        format!("{}/.strawberry/std/Void.st", home_dir),
-    ];
-
-    while path_flag {
-        path_flag = false;
-        if let Ok(paths) = fs::read_dir(&curr_path) {
-            for path in paths {
-                if let Ok(d) = &path {
-                    let path_name = d.path().to_str().unwrap().to_string();
-                    if metadata(&path_name).unwrap().is_dir() {
-                        path_flag = true;
-                        curr_path = path_name.to_string();
-                    } else {
-                        files.insert(0, path_name);
-                    }
-                }
-            }
-        } else {
-            let err = format!("❌ Failed to build because the current directory is not a strawberry project, try \"strawberry new example\"");
-            println!("{}", err.red());
+use strawberry::{Diagnostics, Level, MessageFormat, Source};
+
+use crate::{driver::Toolchain, manifest::Manifest};
+
+const MAIN_FILE: &str = "./src/main.st";
+const BUILD_DIR: &str = "./build";
+
+/// What `strawberry build` stops at and writes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emit {
+    Tokens,
+    Ast,
+    TypedAst,
+    Ir,
+    Asm,
+    Obj,
+    Exe,
+}
+
+impl Emit {
+    pub const NAMES: [&'static str; 7] = ["tokens", "ast", "typed-ast", "ir", "asm", "obj", "exe"];
+
+    /// One of `NAMES`, None for any other name.
+    pub fn from_name(name: &str) -> Option<Emit> {
+        match name {
+            "tokens" => return Some(Emit::Tokens),
+            "ast" => return Some(Emit::Ast),
+            "typed-ast" => return Some(Emit::TypedAst),
+            "ir" => return Some(Emit::Ir),
+            "asm" => return Some(Emit::Asm),
+            "obj" => return Some(Emit::Obj),
+            "exe" => return Some(Emit::Exe),
+            _ => return None,
+        }
+    }
+
+    /// The dumps, which go to the standard output unless `-o` is given.
+    fn is_text(&self) -> bool {
+        return matches!(self, Emit::Tokens | Emit::Ast | Emit::TypedAst | Emit::Ir);
+    }
+}
+
+/// How a command that compiles was invoked.
+pub struct Options {
+    pub format: MessageFormat,
+    /// The lint levels from the command line, a later one wins.
+    pub lints: Vec<(String, Level)>,
+    /// A file compiled on its own instead of the project in the current directory.
+    pub file: Option<String>,
+    pub emit: Emit,
+    /// Where the emitted output is written instead of its default place.
+    pub output: Option<String>,
+    /// The C compiler and the linker from `--cc` and `--linker`.
+    pub cc: Option<String>,
+    pub linker: Option<String>,
+}
+
+/// The files of what is compiled, and where its assembly, object and executable go. A
+/// project builds into `./build`, a single file `foo.st` into `foo.s`, `foo.o` and `foo` next
+/// to where strawberry runs.
+struct Target {
+    manifest: Manifest,
+    files: Vec<String>,
+    asm: String,
+    obj: String,
+    exe: String,
+}
+
+/// `manifest` with `overrides` from the command line applied to its lint levels in order.
+fn with_overrides(manifest: Manifest, overrides: &Vec<(String, Level)>) -> Result<Manifest, String> {
+    let mut manifest = manifest;
+    for (name, level) in overrides {
+        manifest.lints.set(name, *level)?;
+    }
+    return Ok(manifest);
+}
+
+/// The build directory isn't created here, a check writes nothing.
+fn target(options: &Options, check_only: bool) -> Result<Target, String> {
+    if let Some(file) = &options.file {
+        let stem = Path::new(file)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "a".to_string());
+        let mut manifest = with_overrides(Manifest::new(&stem), &options.lints)?;
+        manifest.output = stem.clone();
+        return Ok(Target {
+            manifest,
+            files: vec![file.clone()],
+            asm: format!("./{}.s", stem),
+            obj: format!("./{}.o", stem),
+            exe: format!("./{}", stem),
+        });
+    }
+    let manifest = with_overrides(Manifest::load(".")?, &options.lints)?;
+    let mut files: Vec<String> = vec![];
+    if let Err(_) = sources("./src", &mut files) {
+        return Err(format!(
+            "Failed to build because the current directory is not a strawberry project, try \"strawberry new example\" or give a file"
+        ));
+    }
+    if !files.iter().any(|f| f == MAIN_FILE) {
+        //* an executable needs it, the other outputs can still be looked at without it
+        if !check_only && options.emit == Emit::Exe {
+            return Err(format!("There is no main.st in your src directory!"));
+        }
+        eprintln!("{}", format!("⚠️  There is no main.st in your src directory!").yellow());
+    }
+    return Ok(Target {
+        exe: format!("{}/{}", BUILD_DIR, manifest.output),
+        manifest,
+        files,
+        asm: format!("{}/a.s", BUILD_DIR),
+        obj: format!("{}/a.o", BUILD_DIR),
+    });
+}
+
+/// `strawberry build`, the path of what it emitted when it succeeds.
+pub fn build(options: &Options) -> Option<String> {
+    return compile(options, false);
+}
+
+/// `strawberry check`, the syntax analysis and the semantic check without generating code.
+pub fn check(options: &Options) -> bool {
+    return compile(options, true).is_some();
+}
+
+/// `strawberry run`, builds the executable and runs it with `args`, its exit code is the
+/// exit code of strawberry.
+pub fn run(options: &Options, args: Vec<String>) -> i32 {
+    let exe = match build(options) {
+        Some(exe) => exe,
+        None => return 1,
+    };
+    match Command::new(&exe).args(args).status() {
+        Ok(status) => return status.code().unwrap_or(1),
+        Err(e) => {
+            eprintln!("{}", format!("❌ Failed to run {}: {}", exe, e).red());
+            return 1;
         }
     }
-    compile(files);
 }
 
-fn compile<'a>(files: Vec<String>) {
-    let mut ctx = CompileContext::new();
-    let ctx_ref = RefCell::new(ctx);
-    crate::parser::parse_file(files, &ctx_ref);
-    let result = semantic::semantic_check(ctx_ref);
-    if let Ok(ctx) = result {
-        crate::cgen::code_gen(ctx);
+/// `strawberry clean`, removes the build directory of the project.
+pub fn clean() -> bool {
+    match fs::remove_dir_all(BUILD_DIR) {
+        Ok(()) => return true,
+        Err(e) if e.kind() == ErrorKind::NotFound => return true,
+        Err(e) => {
+            eprintln!("{}", format!("❌ Failed to remove {}: {}", BUILD_DIR, e).red());
+            return false;
+        }
+    }
+}
+
+/// Every `.st` file under `dir` and its subdirectories, in the order of their paths so every
+/// build sees the files in the same order.
+fn sources(dir: &str, files: &mut Vec<String>) -> std::io::Result<()> {
+    let mut paths: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().to_string_lossy().to_string())
+        .collect();
+    paths.sort();
+    for path in paths {
+        if metadata(&path)?.is_dir() {
+            sources(&path, files)?;
+        } else if path.ends_with(".st") {
+            files.push(path);
+        }
+    }
+    return Ok(());
+}
+
+fn compile(options: &Options, check_only: bool) -> Option<String> {
+    let target = match target(options, check_only) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("{}", format!("❌ {}", e).red());
+            return None;
+        }
+    };
+    let mut sources = vec![];
+    for file_name in &target.files {
+        match fs::read_to_string(file_name) {
+            Ok(text) => sources.push(Source::new(file_name, &text)),
+            Err(e) => {
+                eprintln!("{}", format!("❌ Failed to read {}: {}", file_name, e).red());
+                return None;
+            }
+        }
+    }
+    let emit = options.emit;
+    //* the dumps are left alone on the standard output
+    let human = options.format == MessageFormat::Human && !(emit.is_text() && options.output.is_none());
+    let format = options.format;
+    let emit_diagnostics = |mut diagnostics: Diagnostics| {
+        diagnostics.format = format;
+        diagnostics.emit();
+    };
+
+    if emit == Emit::Tokens && !check_only {
+        return write_text(options, strawberry::tokens(&sources));
+    }
+    let program = match strawberry::parse_sources(sources) {
+        Ok(program) => program,
+        Err(diagnostics) => {
+            emit_diagnostics(diagnostics);
+            return None;
+        }
+    };
+    if human {
+        eprintln!("{}", "🎉 Congratulations you passped the syntax analysis!".green());
     }
+    if emit == Emit::Ast && !check_only {
+        return write_text(options, program.ast());
+    }
+    let manifest = target.manifest;
+    let program = program
+        .with_lints(manifest.lints)
+        .with_entry(&manifest.entry_class, &manifest.entry_method);
+    let program = match strawberry::check(program) {
+        Ok(program) => program,
+        Err(diagnostics) => {
+            emit_diagnostics(diagnostics);
+            return None;
+        }
+    };
+    if human {
+        eprintln!("{}", "🎺 Congratulations you passped the semantic check!".green());
+    }
+    emit_diagnostics(program.warnings.clone());
+    if check_only {
+        return Some(String::new());
+    }
+    match emit {
+        Emit::TypedAst => return write_text(options, program.typed_ast()),
+        Emit::Ir => return write_text(options, program.ir()),
+        _ => {}
+    }
+
+    let asm = match (emit, &options.output) {
+        (Emit::Asm, Some(output)) => output.clone(),
+        _ => target.asm,
+    };
+    if let Some(dir) = Path::new(&asm).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            eprintln!("{}", format!("❌ Failed to create {}: {}", dir.display(), e).red());
+            return None;
+        }
+    }
+    if let Err(e) = fs::write(&asm, strawberry::emit_asm(&program)) {
+        eprintln!("{}", format!("❌ Failed to write {}: {}", asm, e).red());
+        return None;
+    }
+    if emit == Emit::Asm {
+        return Some(asm);
+    }
+    let output = match &options.output {
+        Some(output) => output.clone(),
+        None if emit == Emit::Obj => target.obj.clone(),
+        None => target.exe,
+    };
+    let toolchain = Toolchain::new(options.cc.clone(), options.linker.clone());
+    let obj = if emit == Emit::Obj { output.clone() } else { target.obj };
+    let built = toolchain
+        .assemble(&asm, &obj)
+        .and_then(|()| match emit {
+            Emit::Obj => Ok(()),
+            _ => toolchain.link(&obj, &output, &manifest.linker_args),
+        });
+    if let Err(e) = built {
+        eprintln!("{}", format!("❌ {}", e).red());
+        return None;
+    }
+    if human && emit == Emit::Exe {
+        let msg = format!(
+            "🔑 Congratulations you successfully built {} {}, please execute {} in your shell!",
+            manifest.name, manifest.version, output
+        );
+        eprintln!("{}", msg.green());
+    }
+    return Some(output);
 }
 
-pub fn print_err_msg(pos: Position, file_name: &String, err_msg_: &String) {
-    let line = pos.row;
-    let off = pos.column;
-    let mut err_file = File::open(file_name).unwrap();
-    let mut err_content = String::new();
-    let mut lines;
-    let err_msg = format!("--> {}:{}:{}", file_name, line, off);
-    err_file.read_to_string(&mut err_content).expect("error");
-    lines = err_content.lines();
-    println!("{}", err_msg.blue());
-    println!("{0:<4}{1:<4}", "".to_string(), format!("|").blue());
-    print!("{0:<4}{1:<4}", line.blue(), format!("|").blue());
-    println!("{}", lines.nth(line - 1).unwrap().blue());
-    print!(
-        "{0:<4}{1:<4}{2:<off$}",
-        "".to_string(),
-        format!("|").blue(),
-        "".to_string()
-    );
-    println!("{}{}", format!("^ ").red(), err_msg_.red());
+/// Prints a dump, or writes it to the `-o` file.
+fn write_text(options: &Options, text: String) -> Option<String> {
+    match &options.output {
+        Some(output) => match fs::write(output, text) {
+            Ok(()) => return Some(output.clone()),
+            Err(e) => {
+                eprintln!("{}", format!("❌ Failed to write {}: {}", output, e).red());
+                return None;
+            }
+        },
+        None => {
+            //* a closed pipe, like `| head`, is no error
+            let _ = std::io::stdout().write_all(text.as_bytes());
+            return Some(String::new());
+        }
+    }
 }
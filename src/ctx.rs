@@ -1,7 +1,9 @@
 use crate::{
+    diagnostic::Diagnostics,
     parser::ast::class::Class,
+    semantic::{lint::LintLevels, packages::FileScope},
     utils::table::{self, ClassTable, Tables},
-    RUNTIME_ERR,
+    CAST_ERR, DIV_ERR, MATCH_ERR, RUNTIME_ERR, UNCAUGHT_ERR,
 };
 
 #[derive(Debug, Clone)]
@@ -11,6 +13,13 @@ pub struct CompileContext {
     pub file_name: String,
     pub tables: Tables,
     pub class_table: ClassTable,
+    pub diagnostics: Diagnostics,
+    pub lints: LintLevels,
+    /// The package and imports of every file that parsed.
+    pub scopes: Vec<FileScope>,
+    /// The class the program starts by creating, and the method it then calls.
+    pub entry_class: String,
+    pub entry_method: String,
 }
 
 impl CompileContext {
@@ -21,27 +30,23 @@ impl CompileContext {
         tables.string_table.push("%s".to_string());
         tables.string_table.push("%d".to_string());
         tables.string_table.push(RUNTIME_ERR.to_string());
+        tables.string_table.push(CAST_ERR.to_string());
+        tables.string_table.push(DIV_ERR.to_string());
+        tables.string_table.push(MATCH_ERR.to_string());
+        tables.string_table.push(UNCAUGHT_ERR.to_string());
         tables.int_table.insert("0".to_string());
-        let mut class_table = ClassTable::new();
+        let class_table = ClassTable::new();
         CompileContext {
             classes: vec![],
             content: "".to_string(),
             file_name: "".to_string(),
             tables,
             class_table,
+            diagnostics: Diagnostics::new(),
+            lints: LintLevels::new(),
+            scopes: vec![],
+            entry_class: "Main".to_string(),
+            entry_method: "main".to_string(),
         }
     }
-
-    pub fn preprocess(&mut self, raw: String) {
-        let ref this = raw;
-        let mut result = String::new();
-        let mut last_end = 0;
-        for (start, part) in this.match_indices("\t") {
-            result.push_str(unsafe { this.get_unchecked(last_end..start) });
-            result.push_str("    ");
-            last_end = start + part.len();
-        }
-        result.push_str(unsafe { this.get_unchecked(last_end..this.len()) });
-        self.content = result;
-    }
 }
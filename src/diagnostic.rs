@@ -0,0 +1,439 @@
+use std::fmt::Display;
+
+use owo_colors::OwoColorize;
+
+use crate::{lexer::Position, source_map::SourceMap};
+
+/// The stable codes of every diagnostic, tools may match on them but never on the messages.
+pub mod codes {
+    /// The lexer met a character it doesn't know.
+    pub const E0001: &str = "E0001";
+    /// The parser met a token it can't use here.
+    pub const E0002: &str = "E0002";
+    /// There are tokens left after the program ended.
+    pub const E0003: &str = "E0003";
+    /// The file ended in the middle of a declaration.
+    pub const E0004: &str = "E0004";
+    /// Any other syntax error.
+    pub const E0005: &str = "E0005";
+
+    /// There is no Main class.
+    pub const E0100: &str = "E0100";
+    /// The Main class has no main method.
+    pub const E0101: &str = "E0101";
+    /// Two classes have the same name.
+    pub const E0102: &str = "E0102";
+    /// A class inherits a class that doesn't exist.
+    pub const E0103: &str = "E0103";
+    /// A class inherits an enum.
+    pub const E0104: &str = "E0104";
+    /// Classes inherit each other.
+    pub const E0105: &str = "E0105";
+    /// A subclass declares a field of its superclass again.
+    pub const E0106: &str = "E0106";
+    /// Several methods override one inherited method and none of them is more specific.
+    pub const E0107: &str = "E0107";
+    /// An override returns a type that doesn't fit the return type of the method it overrides.
+    pub const E0108: &str = "E0108";
+    /// An override is public where the method it overrides is private, or the other way around.
+    pub const E0109: &str = "E0109";
+    /// Two constructors take the same parameters.
+    pub const E0110: &str = "E0110";
+    /// Two variants of an enum have the same name.
+    pub const E0111: &str = "E0111";
+    /// Two fields of a variant have the same name.
+    pub const E0112: &str = "E0112";
+    /// A comparison operator method doesn't return Bool.
+    pub const E0113: &str = "E0113";
+    /// A non-nullable field has no initializer and its class no constructor.
+    pub const E0114: &str = "E0114";
+    /// A constructor may leave a non-nullable field unassigned.
+    pub const E0115: &str = "E0115";
+    /// A constructor contains a return.
+    pub const E0116: &str = "E0116";
+    /// A method may run off its end without returning a value.
+    pub const E0117: &str = "E0117";
+    /// A class declares two methods with the same name and parameter types.
+    pub const E0118: &str = "E0118";
+    /// A class declares two fields with the same name.
+    pub const E0119: &str = "E0119";
+    /// A method or constructor has two parameters with the same name.
+    pub const E0120: &str = "E0120";
+    /// Main has constructors, but none without parameters to start the program with.
+    pub const E0121: &str = "E0121";
+    /// An import names a class that isn't in its package.
+    pub const E0122: &str = "E0122";
+    /// A file imports two classes with the same name.
+    pub const E0123: &str = "E0123";
+
+    /// A name isn't declared or is out of scope.
+    pub const E0200: &str = "E0200";
+    /// A type names a class that doesn't exist.
+    pub const E0201: &str = "E0201";
+    /// An enum or one of its variants doesn't exist.
+    pub const E0202: &str = "E0202";
+    /// A class has no such method or field.
+    pub const E0203: &str = "E0203";
+    /// A private field is read from outside its class.
+    pub const E0204: &str = "E0204";
+    /// A call passes the wrong number of arguments.
+    pub const E0205: &str = "E0205";
+    /// A call passes an argument of the wrong type.
+    pub const E0206: &str = "E0206";
+    /// No overload of a method takes the arguments.
+    pub const E0207: &str = "E0207";
+    /// More than one overload of a method or constructor fits the arguments equally well.
+    pub const E0208: &str = "E0208";
+    /// No constructor of a class takes the arguments.
+    pub const E0209: &str = "E0209";
+    /// A class with fields to assign is created without a constructor.
+    pub const E0210: &str = "E0210";
+    /// A value doesn't fit the declared type of a local, field or parameter.
+    pub const E0211: &str = "E0211";
+    /// A test of an if, loop, not or guard isn't a Bool.
+    pub const E0212: &str = "E0212";
+    /// An operator is used on types it doesn't take.
+    pub const E0213: &str = "E0213";
+    /// A raw type is used where only objects may be.
+    pub const E0214: &str = "E0214";
+    /// A method or field of a nullable value is used without <?.>.
+    pub const E0215: &str = "E0215";
+    /// A local is declared from null without a type.
+    pub const E0216: &str = "E0216";
+    /// A cast between classes where neither inherits the other.
+    pub const E0217: &str = "E0217";
+    /// Something other than an exception is thrown or caught.
+    pub const E0218: &str = "E0218";
+    /// Something other than an enum is matched.
+    pub const E0219: &str = "E0219";
+    /// A variant is given or bound with the wrong number of fields.
+    pub const E0220: &str = "E0220";
+    /// A match doesn't cover every variant.
+    pub const E0221: &str = "E0221";
+    /// A returned value doesn't fit the return type.
+    pub const E0222: &str = "E0222";
+    /// A local or field may be read before it is assigned.
+    pub const E0223: &str = "E0223";
+    /// A for loop has more than one initializer or test.
+    pub const E0224: &str = "E0224";
+    /// A scope declares two locals with the same name.
+    pub const E0225: &str = "E0225";
+    /// A catch can never run, an earlier catch of its try takes all its exceptions.
+    pub const E0226: &str = "E0226";
+
+    /// Code after a return or throw.
+    pub const W0001: &str = "W0001";
+    /// A local or parameter is never read.
+    pub const W0002: &str = "W0002";
+    /// A private method is never called or a private field never read.
+    pub const W0003: &str = "W0003";
+    /// A local has the name of a field.
+    pub const W0004: &str = "W0004";
+    /// A condition made of literals is always true or always false.
+    pub const W0005: &str = "W0005";
+    /// An isnull on a new object, which is never null.
+    pub const W0006: &str = "W0006";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// The stage that found the problem.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Lexical,
+    Syntax,
+    Semantic,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::Lexical => write!(f, "lexical"),
+            Stage::Syntax => write!(f, "syntax"),
+            Stage::Semantic => write!(f, "semantic"),
+        }
+    }
+}
+
+/// How the diagnostics are printed, `--message-format`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    /// One JSON object per line, for editors and CI.
+    Json,
+}
+
+/// Another place that explains a diagnostic, like the method an override breaks.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub msg: String,
+    pub file_name: String,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub stage: Stage,
+    pub code: &'static str,
+    pub msg: String,
+    pub file_name: String,
+    pub position: Option<Position>,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    fn to_json(&self, source_map: &SourceMap) -> String {
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|l| {
+                format!(
+                    "{{\"message\":{},\"file\":{},{}}}",
+                    json_str(&l.msg),
+                    json_str(&l.file_name),
+                    json_span(source_map, &l.file_name, Some(l.position))
+                )
+            })
+            .collect();
+        let help = match &self.help {
+            Some(help) => json_str(help),
+            None => "null".to_string(),
+        };
+        return format!(
+            "{{\"severity\":\"{}\",\"stage\":\"{}\",\"code\":\"{}\",\"message\":{},\"file\":{},{},\"labels\":[{}],\"help\":{}}}",
+            self.severity,
+            self.stage,
+            self.code,
+            json_str(&self.msg),
+            json_str(&self.file_name),
+            json_span(source_map, &self.file_name, self.position),
+            labels.join(","),
+            help
+        );
+    }
+}
+
+/// The byte range and the lines and columns it starts and ends at, all null without a position.
+fn json_span(source_map: &SourceMap, file_name: &String, position: Option<Position>) -> String {
+    let fields = ["start", "end", "line", "column", "end_line", "end_column"];
+    let values: Vec<String> = match position {
+        Some(pos) => {
+            let (line, column) = source_map.line_column(file_name, pos.start);
+            let (end_line, end_column) = source_map.line_column(file_name, pos.end);
+            [pos.start, pos.end, line, column, end_line, end_column]
+                .iter()
+                .map(|v| v.to_string())
+                .collect()
+        }
+        None => fields.iter().map(|_| "null".to_string()).collect(),
+    };
+    let pairs: Vec<String> = fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| format!("\"{}\":{}", field, value))
+        .collect();
+    return pairs.join(",");
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+/// Every problem found in one compile, so they can all be shown at once, and the sources
+/// they point into.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    pub items: Vec<Diagnostic>,
+    pub format: MessageFormat,
+    pub source_map: SourceMap,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self {
+            items: vec![],
+            format: MessageFormat::Human,
+            source_map: SourceMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn error(
+        &mut self,
+        stage: Stage,
+        code: &'static str,
+        msg: String,
+        file_name: &String,
+        position: Option<Position>,
+    ) {
+        self.items.push(Diagnostic {
+            severity: Severity::Error,
+            stage,
+            code,
+            msg,
+            file_name: file_name.clone(),
+            position,
+            labels: vec![],
+            help: None,
+        });
+    }
+
+    /// Whether progress messages may be printed next to the diagnostics.
+    pub fn is_human(&self) -> bool {
+        return self.format == MessageFormat::Human;
+    }
+
+    pub fn error_count(&self) -> usize {
+        return self.count(Severity::Error);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        return self.error_count() > 0;
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        return self.items.iter().filter(|d| d.severity == severity).count();
+    }
+
+    /// The diagnostics by file and position.
+    fn sorted(&self) -> Vec<&Diagnostic> {
+        let mut items: Vec<&Diagnostic> = self.items.iter().collect();
+        //* the ones without a position go after the rest of their file
+        items.sort_by_key(|d| {
+            let start = d.position.map_or(usize::MAX, |pos| pos.start);
+            (d.file_name.clone(), start)
+        });
+        return items;
+    }
+
+    /// Every diagnostic as a line of JSON, what `--message-format=json` prints.
+    pub fn to_json_lines(&self) -> Vec<String> {
+        return self.sorted().iter().map(|d| d.to_json(&self.source_map)).collect();
+    }
+
+    /// Prints every diagnostic by file and position, then how many there were, on stderr so
+    /// the output of a program or a dump stays alone on stdout. The JSON lines are for tools
+    /// and go to stdout.
+    pub fn emit(&self) {
+        if self.format == MessageFormat::Json {
+            for line in self.to_json_lines() {
+                println!("{}", line);
+            }
+            return;
+        }
+
+        let source_map = &self.source_map;
+        let items = self.sorted();
+
+        for d in items {
+            let title = format!("[{}] {} {}", d.code, d.stage, d.severity);
+            match d.severity {
+                Severity::Error => eprintln!("{}", format!("❌ Oops, {} has occurred!", title).red()),
+                Severity::Warning => eprintln!("{}", format!("⚠️  {}", title).yellow()),
+            }
+            match (d.position, d.severity) {
+                (Some(pos), Severity::Error) => print_err_msg(source_map, pos, &d.file_name, &d.msg),
+                (Some(pos), Severity::Warning) => {
+                    print_warn_msg(source_map, pos, &d.file_name, &d.msg)
+                }
+                (None, _) => {
+                    eprintln!("{}{}", format!("--> ").blue(), d.file_name.blue());
+                    eprintln!("\t{}", d.msg.blue());
+                }
+            }
+            for label in &d.labels {
+                print_label_msg(source_map, label.position, &label.file_name, &label.msg);
+            }
+            if let Some(help) = &d.help {
+                eprintln!("{}{}", format!("    = help: ").green(), help.green());
+            }
+        }
+
+        let (errors, warnings) = (self.count(Severity::Error), self.count(Severity::Warning));
+        if errors > 0 {
+            let summary = format!(
+                "❌ {} {} and {} {} found.",
+                errors,
+                plural(errors, "error"),
+                warnings,
+                plural(warnings, "warning")
+            );
+            eprintln!("{}", summary.red());
+        } else if warnings > 0 {
+            let summary = format!("⚠️  {} {} found.", warnings, plural(warnings, "warning"));
+            eprintln!("{}", summary.yellow());
+        }
+    }
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        return word.to_string();
+    }
+    return format!("{}s", word);
+}
+
+fn print_err_msg(source_map: &SourceMap, pos: Position, file_name: &String, err_msg_: &String) {
+    let mark = print_source_line(source_map, pos, file_name, "^");
+    eprintln!("{}{}", mark.red(), err_msg_.red());
+}
+
+fn print_warn_msg(source_map: &SourceMap, pos: Position, file_name: &String, warn_msg: &String) {
+    let mark = print_source_line(source_map, pos, file_name, "^");
+    eprintln!("{}{}", mark.yellow(), warn_msg.yellow());
+}
+
+/// Points at a place that explains the diagnostic above it.
+fn print_label_msg(source_map: &SourceMap, pos: Position, file_name: &String, label_msg: &String) {
+    let mark = print_source_line(source_map, pos, file_name, "-");
+    eprintln!("{}{}", mark.blue(), label_msg.blue());
+}
+
+/// Prints the line at `pos` and leaves the cursor under its start, gives the underline of `pos`.
+fn print_source_line(source_map: &SourceMap, pos: Position, file_name: &String, mark: &str) -> String {
+    let (line, column) = source_map.line_column(file_name, pos.start);
+    let (_, text, indent, len) = source_map.snippet(file_name, pos);
+    let err_msg = format!("--> {}:{}:{}", file_name, line, column);
+    eprintln!("{}", err_msg.blue());
+    eprintln!("{0:<4}{1:<4}", "".to_string(), format!("|").blue());
+    eprint!("{0:<4}{1:<4}", line.blue(), format!("|").blue());
+    eprintln!("{}", text.blue());
+    eprint!(
+        "{0:<4}{1:<4}{2:<indent$}",
+        "".to_string(),
+        format!("|").blue(),
+        "".to_string()
+    );
+    return format!("{} ", mark.repeat(len));
+}
@@ -0,0 +1,120 @@
+//! The toolchain that turns the generated assembly into a program. The C compiler assembles it
+//! into an object, then the linker, the C compiler unless `--linker` names another, links the
+//! object into the executable. Each tool is waited for and what it prints is kept, so a build
+//! only succeeds when they do and their errors can be told apart.
+
+use std::process::Command;
+
+/// The C compiler when neither `--cc` nor `CC` names one.
+const DEFAULT_CC: &str = "gcc";
+
+pub struct Toolchain {
+    pub cc: String,
+    /// Called like a C compiler, so it may be `clang` but not `ld`.
+    pub linker: String,
+}
+
+impl Toolchain {
+    /// `--cc` wins over the `CC` environment variable, and the linker is the C compiler
+    /// unless `--linker` is given.
+    pub fn new(cc: Option<String>, linker: Option<String>) -> Toolchain {
+        let cc = cc
+            .or_else(|| std::env::var("CC").ok().filter(|cc| !cc.is_empty()))
+            .unwrap_or_else(|| DEFAULT_CC.to_string());
+        let linker = linker.unwrap_or_else(|| cc.clone());
+        return Toolchain { cc, linker };
+    }
+
+    /// Assembles `asm` into the object `obj`. The errors name the method whose assembly they
+    /// are in, which is most likely an `asm` block of it.
+    pub fn assemble(&self, asm: &str, obj: &str) -> Result<(), String> {
+        let mut cc = Command::new(&self.cc);
+        cc.arg("-m64")
+            .arg("-c")
+            .arg(asm)
+            .arg("-o")
+            .arg(obj);
+        return match invoke(&mut cc, &self.cc, "--cc or the CC environment variable") {
+            Ok(()) => Ok(()),
+            Err(stderr) => Err(format!(
+                "{} failed to assemble {}:\n{}",
+                self.cc,
+                asm,
+                attribute(asm, &stderr)
+            )),
+        };
+    }
+
+    /// Links the object `obj` into the executable `exe`, `args` go last.
+    pub fn link(&self, obj: &str, exe: &str, args: &Vec<String>) -> Result<(), String> {
+        let mut linker = Command::new(&self.linker);
+        linker
+            .arg("-no-pie")
+            .arg("-static")
+            .arg("-m64")
+            .arg(obj)
+            .arg("-o")
+            .arg(exe)
+            .args(args);
+        return match invoke(&mut linker, &self.linker, "--linker") {
+            Ok(()) => Ok(()),
+            Err(stderr) => Err(format!("{} failed to link {}:\n{}", self.linker, exe, stderr.trim_end())),
+        };
+    }
+}
+
+/// Runs `command` to its end, what it printed to stderr when it fails. A tool that succeeds
+/// still has its warnings printed, `option` is how another tool is chosen.
+fn invoke(command: &mut Command, name: &str, option: &str) -> Result<(), String> {
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => {
+            return Err(format!(
+                "{} could not be started: {}, another can be chosen with {}",
+                name, e, option
+            ))
+        }
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if output.status.success() {
+        eprint!("{}", stderr);
+        return Ok(());
+    }
+    if stderr.trim().is_empty() {
+        return Err(format!("{} exited with {}", name, output.status));
+    }
+    return Err(stderr);
+}
+
+/// The assembler's messages, each `file:line: Error: ...` followed by the method that line is
+/// in.
+fn attribute(asm: &str, stderr: &str) -> String {
+    let text = std::fs::read_to_string(asm).unwrap_or_default();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = vec![];
+    for message in stderr.lines() {
+        out.push(message.to_string());
+        let line = message
+            .strip_prefix(asm)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .and_then(|rest| rest.split(':').next())
+            .and_then(|line| line.parse::<usize>().ok());
+        if let Some(method) = line.and_then(|line| method_at(&lines, line)) {
+            out.push(format!("    in the assembly of {}", method));
+        }
+    }
+    return out.join("\n");
+}
+
+/// The method whose label is the last one at or before `line`, counted from 1. Methods are
+/// labelled like `Main.main:` at the start of a line, the labels inside them have no dot.
+fn method_at<'a>(lines: &Vec<&'a str>, line: usize) -> Option<&'a str> {
+    return lines
+        .iter()
+        .take(line)
+        .rev()
+        .filter_map(|l| l.strip_suffix(':'))
+        .find(|l| {
+            !l.starts_with(|c: char| c.is_whitespace() || c == '.' || c == '#') && l.contains('.')
+        });
+}
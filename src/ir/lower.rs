@@ -0,0 +1,476 @@
+use std::{collections::HashMap, ops::Deref};
+
+use crate::{
+    ctx::CompileContext,
+    parser::ast::{
+        class::{self as ast_class, enum_slot, mangle_method, Feature, CONSTRUCTOR, ENUM_TAG},
+        expr::{self as ast, DispatchExpr, MathOp, Pattern},
+        is_primitive, non_null, Identifier, ParamDecl, Type,
+    },
+    semantic::flow::always_returns,
+    ty::Symbol,
+    utils::table::ClassTable,
+    BOOL, FIELD_BASIC_OFFSET, INT, NULL_TYPE, OBJECT, STRING, VOID,
+};
+
+use super::{
+    Call, Cast, Catch, Class, Cond, Expr, ExprKind, Field, FieldGet, For, Match, MatchArm,
+    Method, MethodKind, New, Program, Try, Var, Variant,
+};
+
+/// Lowers the classes of `ctx` once they pass the semantic check.
+pub fn lower(ctx: &CompileContext) -> Program {
+    let mut lowerer = Lowerer::new(&ctx.class_table, &ctx.classes);
+    let classes = ctx.classes.iter().map(|c| lowerer.class(c)).collect();
+    return Program {
+        classes,
+        strings: ctx.tables.string_table.clone(),
+        entry_class: ctx.entry_class.clone(),
+        entry_method: ctx.entry_method.clone(),
+    };
+}
+
+/// Where the fields and methods of a class are in its objects and its dispatch table.
+struct Layout {
+    /// Every field with the class declaring it, in slot order.
+    fields: Vec<(ast_class::VarDecl, Type, usize)>,
+    vtable: Vec<String>,
+    /// The byte offset of the slot of every method, by mangled name.
+    slots: HashMap<String, usize>,
+}
+
+impl Layout {
+    fn new(class_table: &ClassTable, class_name: &Type) -> Layout {
+        let inheritance = class_table.inheritance(Symbol::intern(class_name)).unwrap();
+        let mut fields = vec![];
+        let mut vtable = vec![];
+        let mut slots = HashMap::new();
+        for curr_class in inheritance.clone() {
+            for feature in &curr_class.features {
+                match feature {
+                    Feature::Attribute(attr) => {
+                        let offset = FIELD_BASIC_OFFSET + fields.len() * 8;
+                        fields.push((attr.clone(), curr_class.name.clone(), offset));
+                    }
+                    //* a slot keeps the signature of the method declaring it, and holds the
+                    //* override of the class closest to this one
+                    Feature::Method(method) => {
+                        let overrider = inheritance
+                            .clone()
+                            .rev()
+                            .find_map(|c| Some((c, class_table.overrider(c, method)?)));
+                        if let Some((c, overrider)) = overrider {
+                            slots.insert(method.mangled_name(), vtable.len() * 8);
+                            vtable.push(format!("{}.{}", c.name, overrider.mangled_name()));
+                        }
+                    }
+                    Feature::Constructor(_) => {}
+                }
+            }
+        }
+        return Layout {
+            fields,
+            vtable,
+            slots,
+        };
+    }
+
+    fn field_offset(&self, name: &Identifier) -> usize {
+        return self.fields.iter().find(|f| &f.0.name == name).unwrap().2;
+    }
+}
+
+struct Lowerer<'a> {
+    /// The checked classes, their bodies have types and boxing filled in.
+    classes: HashMap<Type, &'a ast_class::Class>,
+    layouts: HashMap<Type, Layout>,
+    /// The class being lowered.
+    class_name: Type,
+    scopes: Vec<HashMap<Identifier, Var>>,
+    /// The locals the method being lowered takes so far.
+    locals: usize,
+}
+
+impl<'a> Lowerer<'a> {
+    fn new(class_table: &'a ClassTable, classes: &'a Vec<ast_class::Class>) -> Lowerer<'a> {
+        let layouts = class_table
+            .classes()
+            .map(|c| (c.name.clone(), Layout::new(class_table, &c.name)))
+            .collect();
+        return Lowerer {
+            classes: classes.iter().map(|c| (c.name.clone(), c)).collect(),
+            layouts,
+            class_name: OBJECT.to_string(),
+            scopes: vec![],
+            locals: 0,
+        };
+    }
+
+    fn class(&mut self, class_: &ast_class::Class) -> Class {
+        self.class_name = class_.name.clone();
+        //* the parent is resolved by the check, a class without one inherits Object
+        let parent = match (class_.name.as_str(), &class_.parent) {
+            (OBJECT, _) => None,
+            (_, Some(parent)) => Some(parent.clone()),
+            (_, None) => Some(OBJECT.to_string()),
+        };
+
+        //* the initializers run on the new object, the checked ones are the declaring class's
+        self.enter_method(&vec![]);
+        let mut fields = vec![];
+        for (attr, owner, offset) in &self.layouts[&class_.name].fields.clone() {
+            let checked = self.classes[owner].features.iter().find_map(|f| match f {
+                Feature::Attribute(a) if a.name == attr.name => Some(a),
+                _ => None,
+            });
+            let init = checked.and_then(|a| a.init.deref().as_ref()).map(|e| self.expr(e));
+            fields.push(Field {
+                name: attr.name.clone(),
+                type_: attr.type_.clone().unwrap(),
+                offset: *offset,
+                init,
+            });
+        }
+
+        let mut methods = vec![];
+        for feature in &class_.features {
+            match feature {
+                Feature::Method(method) => methods.push(self.method(
+                    format!("{}.{}", class_.name, method.mangled_name()),
+                    MethodKind::Method,
+                    &method.param,
+                    &method.return_type,
+                    &method.body,
+                )),
+                Feature::Constructor(constructor) => methods.push(self.method(
+                    format!("{}.{}", class_.name, constructor.mangled_name()),
+                    MethodKind::Constructor,
+                    &constructor.param,
+                    &class_.name,
+                    &constructor.body,
+                )),
+                Feature::Attribute(_) => {}
+            }
+        }
+
+        return Class {
+            name: class_.name.clone(),
+            parent,
+            fields,
+            vtable: self.layouts[&class_.name].vtable.clone(),
+            methods,
+        };
+    }
+
+    fn method(
+        &mut self,
+        label: String,
+        kind: MethodKind,
+        params: &Vec<ParamDecl>,
+        return_type: &Type,
+        body: &Option<Vec<ast::Expr>>,
+    ) -> Method {
+        self.enter_method(params);
+        let lowered = body.as_ref().map(|body| self.body(body));
+        return Method {
+            label,
+            kind,
+            params: params.iter().map(|p| (p.0.clone(), p.1.clone())).collect(),
+            return_type: return_type.clone(),
+            body: lowered,
+            locals: self.locals,
+            always_returns: body.as_ref().map_or(false, |body| always_returns(body)),
+        };
+    }
+
+    /// The fields of the class and the parameters are in scope, no locals are taken yet.
+    fn enter_method(&mut self, params: &Vec<ParamDecl>) {
+        let fields = self.layouts[&self.class_name]
+            .fields
+            .iter()
+            .map(|(attr, _, offset)| (attr.name.clone(), Var::Field(*offset)))
+            .collect();
+        let count = params.len();
+        let params = params
+            .iter()
+            .enumerate()
+            .map(|(index, p)| (p.0.clone(), Var::Param { index, count }))
+            .collect();
+        self.scopes = vec![fields, params];
+        self.locals = 0;
+    }
+
+    fn new_local(&mut self) -> usize {
+        self.locals += 1;
+        return self.locals;
+    }
+
+    fn bind(&mut self, name: &Identifier, var: Var) {
+        self.scopes.last_mut().unwrap().insert(name.clone(), var);
+    }
+
+    fn resolve(&self, name: &Identifier) -> Var {
+        return *self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .expect("a checked name is in scope");
+    }
+
+    /// A body of its own scope.
+    fn body(&mut self, body: &Vec<ast::Expr>) -> Vec<Expr> {
+        self.scopes.push(HashMap::new());
+        let body = body.iter().map(|e| self.expr(e)).collect();
+        self.scopes.pop();
+        return body;
+    }
+
+    fn exprs(&mut self, exprs: &Vec<ast::Expr>) -> Vec<Expr> {
+        return exprs.iter().map(|e| self.expr(e)).collect();
+    }
+
+    fn boxed(&mut self, expr: &ast::Expr) -> Box<Expr> {
+        return Box::new(self.expr(expr));
+    }
+
+    fn expr(&mut self, expr: &ast::Expr) -> Expr {
+        let void = VOID.to_string();
+        match expr {
+            ast::Expr::Int(i, _) => return Expr::new(ExprKind::Int(*i), INT.to_string()),
+            ast::Expr::Bool(b, _) => return Expr::new(ExprKind::Bool(*b), BOOL.to_string()),
+            ast::Expr::Str(s, _) => return Expr::new(ExprKind::Str(s.clone()), STRING.to_string()),
+            ast::Expr::Null(_) => return Expr::new(ExprKind::Null, NULL_TYPE.to_string()),
+            ast::Expr::ASM(s) => return Expr::new(ExprKind::Asm(s.clone()), OBJECT.to_string()),
+            ast::Expr::Self_(_) => return Expr::new(ExprKind::Self_, self.class_name.clone()),
+            //* a name has the type the checker narrowed it to
+            ast::Expr::Identifier(e) => {
+                return Expr::new(ExprKind::Var(self.resolve(&e.name)), e.type_.clone())
+            }
+            ast::Expr::Assignment(e) => {
+                let var = self.resolve(&e.id);
+                return Expr::new(ExprKind::Assign(var, self.boxed(&e.compute)), void);
+            }
+            ast::Expr::Let(e) => {
+                let mut locals = vec![];
+                for decl in e.var_decls.iter() {
+                    let type_ = decl.type_.clone().unwrap();
+                    let local = self.new_local();
+                    let init = match decl.init.deref() {
+                        Some(init) => self.expr(init),
+                        None if is_primitive(&type_) => Expr::new(ExprKind::Int(0), type_.clone()),
+                        None => Expr::new(ExprKind::Prototype(non_null(&type_)), type_.clone()),
+                    };
+                    self.bind(&decl.name, Var::Local(local));
+                    locals.push((local, init));
+                }
+                return Expr::new(ExprKind::Let(locals), void);
+            }
+            ast::Expr::Dispatch(e) => return self.dispatch(e),
+            ast::Expr::New(e) => {
+                let args = match &e.param {
+                    Some(param) => self.exprs(param),
+                    None => vec![],
+                };
+                let constructor = e.param_types.as_ref().map(|types| {
+                    format!("{}.{}", e.class_name, mangle_method(&CONSTRUCTOR.to_string(), types))
+                });
+                let new = New {
+                    class_name: e.class_name.clone(),
+                    args,
+                    constructor,
+                };
+                return Expr::new(ExprKind::New(new), e.class_name.clone());
+            }
+            ast::Expr::Variant(e) => {
+                let layout = &self.layouts[&e.enum_name];
+                let tag_offset = layout.field_offset(&ENUM_TAG.to_string());
+                let offsets: Vec<usize> = (0..e.actual.len())
+                    .map(|index| layout.field_offset(&enum_slot(index)))
+                    .collect();
+                let (tag, _) = self.classes[&e.enum_name].get_variant(&e.variant).unwrap();
+                let fields = offsets.into_iter().zip(self.exprs(&e.actual)).collect();
+                let variant = Variant {
+                    enum_name: e.enum_name.clone(),
+                    tag,
+                    tag_offset,
+                    fields,
+                };
+                return Expr::new(ExprKind::Variant(variant), e.enum_name.clone());
+            }
+            ast::Expr::Math(e) => {
+                //* `a + b` on a class is a call of its add
+                if let Some(call) = &e.overload {
+                    return self.expr(call);
+                }
+                let (left, right) = (self.boxed(&e.left), self.boxed(&e.right));
+                let kind = match e.op.deref() {
+                    _ if left.type_ == STRING && right.type_ == STRING => {
+                        ExprKind::Concat(left, right)
+                    }
+                    MathOp::ComputeOp(op) => ExprKind::Compute(op.clone(), left, right),
+                    MathOp::CondOp(op) => ExprKind::Compare(op.clone(), left, right),
+                };
+                return Expr::new(kind, e.type_.clone());
+            }
+            ast::Expr::Not(e) => return Expr::new(ExprKind::Not(self.boxed(&e.expr)), BOOL.to_string()),
+            ast::Expr::Isnull(e) => {
+                return Expr::new(ExprKind::Isnull(self.boxed(&e.expr)), BOOL.to_string())
+            }
+            ast::Expr::Boxing(e) => {
+                return Expr::new(ExprKind::Box_(self.boxed(&e.expr)), e.class_name.clone())
+            }
+            ast::Expr::Unboxing(e) => {
+                return Expr::new(ExprKind::Unbox(self.boxed(&e.expr)), e.type_.clone())
+            }
+            ast::Expr::Is(e) => {
+                let kind = ExprKind::Is(self.boxed(&e.expr), e.class_name.clone());
+                return Expr::new(kind, BOOL.to_string());
+            }
+            ast::Expr::As(e) => {
+                let cast = Cast {
+                    expr: self.boxed(&e.expr),
+                    class_name: e.class_name.clone(),
+                    checked: !e.upcast,
+                };
+                return Expr::new(ExprKind::Cast(cast), e.class_name.clone());
+            }
+            ast::Expr::Coalesce(e) => {
+                let kind = ExprKind::Coalesce(self.boxed(&e.left), self.boxed(&e.right));
+                return Expr::new(kind, e.type_.clone());
+            }
+            ast::Expr::Cond(e) => {
+                let cond = Cond {
+                    test: self.boxed(&e.test),
+                    then_body: self.body(&e.then_body),
+                    else_body: self.body(&e.else_body),
+                };
+                return Expr::new(ExprKind::Cond(cond), void);
+            }
+            ast::Expr::While(e) => {
+                let test = self.boxed(&e.test);
+                return Expr::new(ExprKind::While(test, self.body(&e.body)), void);
+            }
+            ast::Expr::For(e) => {
+                self.scopes.push(HashMap::new());
+                let init = self.exprs(&e.init);
+                let test = self.exprs(&e.test);
+                let iter = self.exprs(&e.iter);
+                let body = self.body(&e.body);
+                self.scopes.pop();
+                let for_ = For {
+                    init,
+                    test,
+                    iter,
+                    body,
+                };
+                return Expr::new(ExprKind::For(for_), void);
+            }
+            ast::Expr::Block(body) => return Expr::new(ExprKind::Block(self.body(body)), void),
+            ast::Expr::Return(e) => {
+                let val = e.val.as_ref().map(|val| self.boxed(val));
+                return Expr::new(ExprKind::Return(val), void);
+            }
+            ast::Expr::Throw(e) => return Expr::new(ExprKind::Throw(self.boxed(&e.expr)), void),
+            ast::Expr::Try(e) => {
+                let base = self.locals + 1;
+                self.locals += 8;
+                let body = self.body(&e.body);
+                let mut catches = vec![];
+                for catch in &e.catches {
+                    self.scopes.push(HashMap::new());
+                    let local = self.new_local();
+                    self.bind(&catch.name, Var::Local(local));
+                    catches.push(Catch {
+                        class_name: catch.class_name.clone(),
+                        local,
+                        body: self.exprs(&catch.body),
+                    });
+                    self.scopes.pop();
+                }
+                let finally = e.finally.as_ref().map(|finally| self.body(finally));
+                let try_ = Try {
+                    base,
+                    body,
+                    catches,
+                    finally,
+                };
+                return Expr::new(ExprKind::Try(try_), void);
+            }
+            ast::Expr::Match(e) => return self.match_(e),
+        }
+    }
+
+    fn dispatch(&mut self, e: &ast::Dispatch) -> Expr {
+        let target = self.boxed(&e.target);
+        let layout = &self.layouts[&non_null(&target.type_)];
+        match &e.expr {
+            DispatchExpr::Method(method_call) => {
+                let slot = layout.slots[&mangle_method(&method_call.fun_name, &method_call.param_types)];
+                let method = layout.vtable[slot / 8].clone();
+                let receiver = self.new_local();
+                let call = Call {
+                    target,
+                    args: self.exprs(&method_call.actual),
+                    method,
+                    slot,
+                    receiver,
+                    safe: e.safe,
+                };
+                return Expr::new(ExprKind::Call(call), e.type_.clone());
+            }
+            DispatchExpr::Field(field) => {
+                let field_get = FieldGet {
+                    offset: layout.field_offset(field),
+                    target,
+                    safe: e.safe,
+                };
+                return Expr::new(ExprKind::Field(field_get), e.type_.clone());
+            }
+        }
+    }
+
+    fn match_(&mut self, e: &ast::Match) -> Expr {
+        let expr = self.boxed(&e.expr);
+        let enum_name = non_null(&expr.type_);
+        let local = self.new_local();
+        let tag_offset = self.layouts[&enum_name].field_offset(&ENUM_TAG.to_string());
+        let variants = self.classes[&enum_name].variants.clone().unwrap();
+
+        let mut arms = vec![];
+        for arm in &e.arms {
+            self.scopes.push(HashMap::new());
+            let (tag, bindings) = match &arm.pattern {
+                Pattern::Wildcard => (None, vec![]),
+                Pattern::Variant(pattern) => {
+                    let tag = variants.iter().position(|v| v.name == pattern.name).unwrap();
+                    let mut bindings = vec![];
+                    for (index, binding) in pattern.bindings.iter().enumerate() {
+                        if let Some(name) = binding {
+                            let offset = self.layouts[&enum_name].field_offset(&enum_slot(index));
+                            let binding_local = self.new_local();
+                            self.bind(name, Var::Local(binding_local));
+                            bindings.push((offset, binding_local));
+                        }
+                    }
+                    (Some(tag), bindings)
+                }
+            };
+            let guard = arm.guard.as_ref().map(|guard| self.boxed(guard));
+            arms.push(MatchArm {
+                tag,
+                bindings,
+                guard,
+                body: self.boxed(&arm.body),
+            });
+            self.scopes.pop();
+        }
+        let match_ = Match {
+            expr,
+            local,
+            tag_offset,
+            arms,
+        };
+        return Expr::new(ExprKind::Match(match_), e.type_.clone());
+    }
+}
@@ -0,0 +1,238 @@
+//! The typed IR the semantic check hands to the backends. Every expression carries its
+//! static type, names are resolved to where their values live, and calls to the dispatch
+//! table slot they go through, so a backend never looks at the AST or the class table.
+
+use crate::parser::ast::{
+    expr::{ComputeOp, CondOp},
+    Identifier, Int, Type,
+};
+
+pub mod lower;
+
+/// A checked program, everything a backend needs to emit it.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub classes: Vec<Class>,
+    /// The string constants, a `Str` is one of them.
+    pub strings: Vec<String>,
+    /// The class the program starts by creating, and the method without parameters it calls.
+    pub entry_class: Type,
+    pub entry_method: Identifier,
+}
+
+impl Program {
+    pub fn class(&self, name: &str) -> Option<&Class> {
+        return self.classes.iter().find(|c| c.name == name);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: Type,
+    /// None only for Object.
+    pub parent: Option<Type>,
+    /// The fields of the class and its superclasses, in the order of their slots.
+    pub fields: Vec<Field>,
+    /// The label of the method in every dispatch table slot.
+    pub vtable: Vec<String>,
+    /// The methods and constructors the class declares.
+    pub methods: Vec<Method>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    /// Only the `ir` dump reads it, the backend knows a field by its offset.
+    #[allow(dead_code)]
+    pub name: Identifier,
+    pub type_: Type,
+    /// Where the field is in an object, in bytes.
+    pub offset: usize,
+    pub init: Option<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MethodKind {
+    Method,
+    Constructor,
+}
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    /// The label the method is emitted under, like `Square.set$3int`.
+    pub label: String,
+    pub kind: MethodKind,
+    pub params: Vec<(Identifier, Type)>,
+    /// Only the `ir` dump reads it, the backend returns whatever is in %rax.
+    #[allow(dead_code)]
+    pub return_type: Type,
+    /// None for a method declared without a body.
+    pub body: Option<Vec<Expr>>,
+    /// How many local slots the body takes.
+    pub locals: usize,
+    /// Every path through the body returns or throws.
+    pub always_returns: bool,
+}
+
+/// Where a variable lives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Var {
+    /// A slot of the frame, counted from 1.
+    Local(usize),
+    /// The `index`th of `count` parameters.
+    Param { index: usize, count: usize },
+    /// A field of self, at this offset.
+    Field(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub type_: Type,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    Int(Int),
+    Bool(bool),
+    Str(String),
+    Null,
+    /// The prototype object of a class, what an object local holds before it is assigned.
+    Prototype(Type),
+    Asm(String),
+    Self_,
+    Var(Var),
+    Assign(Var, Box<Expr>),
+    /// New locals with their first values, each one in scope for the next.
+    Let(Vec<(usize, Expr)>),
+    Call(Call),
+    Field(FieldGet),
+    New(New),
+    Variant(Variant),
+    Compute(ComputeOp, Box<Expr>, Box<Expr>),
+    Compare(CondOp, Box<Expr>, Box<Expr>),
+    /// `+` on two strings.
+    Concat(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Isnull(Box<Expr>),
+    /// A raw value boxed into its type.
+    Box_(Box<Expr>),
+    /// The raw value of an `Integer` or `Bool`, of its type.
+    Unbox(Box<Expr>),
+    Is(Box<Expr>, Type),
+    Cast(Cast),
+    Coalesce(Box<Expr>, Box<Expr>),
+    Cond(Cond),
+    While(Box<Expr>, Vec<Expr>),
+    For(For),
+    Block(Vec<Expr>),
+    Return(Option<Box<Expr>>),
+    Throw(Box<Expr>),
+    Try(Try),
+    Match(Match),
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, type_: Type) -> Expr {
+        return Expr { kind, type_ };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub target: Box<Expr>,
+    pub args: Vec<Expr>,
+    /// The method the static type of the target dispatches to, like `Shape.get_area`. Only the
+    /// `ir` dump reads it, the backend calls through `slot`.
+    #[allow(dead_code)]
+    pub method: String,
+    /// Where the method is in the dispatch table, in bytes.
+    pub slot: usize,
+    /// The local the target is kept in while the arguments run, the target runs first.
+    pub receiver: usize,
+    /// `target?.f()` gives the null target back instead of calling.
+    pub safe: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldGet {
+    pub target: Box<Expr>,
+    pub offset: usize,
+    /// `target?.field` gives null for a null target.
+    pub safe: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct New {
+    pub class_name: Type,
+    pub args: Vec<Expr>,
+    /// The label of the constructor to run, none when the class has no constructors.
+    pub constructor: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub enum_name: Type,
+    pub tag: usize,
+    pub tag_offset: usize,
+    /// The payload with the offset of the slot each value goes to.
+    pub fields: Vec<(usize, Expr)>,
+}
+
+/// `expr as Class`, only a downcast is checked at runtime.
+#[derive(Debug, Clone)]
+pub struct Cast {
+    pub expr: Box<Expr>,
+    pub class_name: Type,
+    pub checked: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cond {
+    pub test: Box<Expr>,
+    pub then_body: Vec<Expr>,
+    pub else_body: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct For {
+    pub init: Vec<Expr>,
+    pub test: Vec<Expr>,
+    pub iter: Vec<Expr>,
+    pub body: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Try {
+    /// The first of the eight locals keeping the handler record and what the finally does.
+    pub base: usize,
+    pub body: Vec<Expr>,
+    pub catches: Vec<Catch>,
+    pub finally: Option<Vec<Expr>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Catch {
+    pub class_name: Type,
+    /// The local the caught exception is bound to.
+    pub local: usize,
+    pub body: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub expr: Box<Expr>,
+    /// The local keeping the matched value.
+    pub local: usize,
+    pub tag_offset: usize,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    /// None for `_`.
+    pub tag: Option<usize>,
+    /// The offset of each bound payload slot and the local it is bound to.
+    pub bindings: Vec<(usize, usize)>,
+    pub guard: Option<Box<Expr>>,
+    pub body: Box<Expr>,
+}
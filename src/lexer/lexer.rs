@@ -1,14 +1,15 @@
 extern crate plex;
-use std::{
-    borrow::{Borrow, BorrowMut},
-    cell::RefCell,
-};
+use std::cell::RefCell;
 
-use crate::{ctx::CompileContext, lexer::EMPTY_POSITION, table::Tables};
+use crate::{
+    ctx::CompileContext,
+    diagnostic::{codes::E0001, Stage},
+    lexer::EMPTY_POSITION,
+};
 
 use plex::lexer;
 
-use super::{token::Token, LineNum, Off, Position};
+use super::{token::Token, BytePos, Position};
 
 lexer! {
 
@@ -17,10 +18,18 @@ lexer! {
     //* keywords */
     "int" => Token::IntRawType(text.to_owned()),
     "void" => Token::VoidRawType(text.to_owned()),
-    // "bool" => Token::BoolRawType(text.to_owned()),
+    "bool" => Token::BoolRawType(text.to_owned()),
     "str" => Token::StrRawType(text.to_owned()),
 
     "class" => Token::Class_(*EMPTY_POSITION,"".to_string()),
+    "enum" => Token::Enum(*EMPTY_POSITION,"".to_string()),
+    "match" => Token::Match(*EMPTY_POSITION),
+    "is" => Token::Is(*EMPTY_POSITION),
+    "as" => Token::As(*EMPTY_POSITION),
+    "throw" => Token::Throw(*EMPTY_POSITION),
+    "try" => Token::Try(*EMPTY_POSITION),
+    "catch" => Token::Catch(*EMPTY_POSITION),
+    "finally" => Token::Finally,
     "public" => Token::Public,
     "private" => Token::Private,
     "self" => Token::Self_(text.to_owned()),
@@ -30,6 +39,7 @@ lexer! {
     "return"=>Token::Return(*EMPTY_POSITION),
     "import"=>Token::Import,
     "from"=>Token::From,
+    "package"=>Token::Package,
     "if" => Token::If(*EMPTY_POSITION),
     "then" => Token::Then,
     "else" => Token::Else(*EMPTY_POSITION),
@@ -38,7 +48,8 @@ lexer! {
     "while" => Token::While(*EMPTY_POSITION),
     "for" => Token::For(*EMPTY_POSITION),
     "new"=>Token::New(*EMPTY_POSITION),
-    "null" => Token::Isnull,
+    "isnull" => Token::Isnull,
+    "null" => Token::Null,
     "!" => Token::Not(*EMPTY_POSITION),
     "true"=>Token::BoolConst(true),
     "false" => Token::BoolConst(false),
@@ -47,7 +58,7 @@ lexer! {
 
 
     //* const and id and typeid */
-    "[A-Z][a-zA-Z0-9_]*"=>Token::TypeId(text.to_owned()),
+    "[A-Z][a-zA-Z0-9_]*"=>Token::TypeId(text.to_owned(),*EMPTY_POSITION),
     "[a-z][a-zA-Z0-9_]*"=>Token::Identifier(text.to_owned(),*EMPTY_POSITION),
     "[-]*[0-9]+" => Token::IntConst(text.to_owned()),
     r#""[^"]*""# => parse_string(text),
@@ -67,7 +78,7 @@ lexer! {
 
     //* others */
     r#"\n"# => Token::Newline,
-    r#"[ ]+"# => Token::Whitespace(text.to_owned()),
+    r#"[ \t\r]+"# => Token::Whitespace(text.to_owned()),
     r#"/[*](~(.*[*]/.*))[*]/"# => Token::BlockComment(text.to_owned()),
     r#"//[^\n]*"# => Token::Comment,
     "{" => Token::Lbrace,
@@ -76,8 +87,13 @@ lexer! {
     r"\)" => Token::Rparen,
     ";" => Token::Semicolon,
     r"\." => Token::Period,
+    r"\?\." => Token::SafePeriod,
+    r"\?" => Token::Question,
+    r"\?\?" => Token::DoubleQuestion,
     r"," => Token::Comma,
     ":" => Token::Colon,
+    "::" => Token::DoubleColon,
+    "_" => Token::Underscore,
 
     "." => Token::Error(format!("Unexpected character: {}", text.to_owned())),
 
@@ -107,9 +123,8 @@ fn parse_string(text: &str) -> Token {
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
-    current_line: usize,
-    offset: usize,
-    remaining: &'a str,
+    /// Bytes read so far.
+    offset: BytePos,
     // tables: &'a mut Tables,
     // file_name: &'a str,
     pub ctx: &'a RefCell<CompileContext>,
@@ -120,9 +135,7 @@ impl<'a> Lexer<'a> {
     // impl Lexer {
     pub fn new(ctx: &'a RefCell<CompileContext>) -> Lexer<'a> {
         Lexer {
-            current_line: 1,
             offset: 0,
-            remaining: "",
             // tables: &mut ctx.tables,
             // file_name: &ctx.file_name,
             ctx,
@@ -131,13 +144,17 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// A lexical error that stops the parse, unexpected characters are only recorded and skipped.
 #[derive(Debug)]
-pub struct LexicalError {}
+pub struct LexicalError {
+    pub err_msg: String,
+    pub position: Position,
+}
 
 impl<'a> Iterator for Lexer<'a> {
     // impl Iterator for Lexer {
-    type Item = Result<(LineNum, Token, Off), LexicalError>;
-    fn next(&mut self) -> Option<Result<(LineNum, Token, Off), LexicalError>> {
+    type Item = Result<(BytePos, Token, BytePos), LexicalError>;
+    fn next(&mut self) -> Option<Result<(BytePos, Token, BytePos), LexicalError>> {
         loop {
             // self.remaining = &self.ctx.clone().borrow().content;
             // let tok = if let Some((tok, new_remaining)) = next_token(&self.ctx.borrow().content) {
@@ -151,8 +168,9 @@ impl<'a> Iterator for Lexer<'a> {
             let file_name = self.ctx.borrow().file_name.clone();
             let mut borrow_mut = self.ctx.borrow_mut();
 
+            let start = self.offset;
             let tok = if let Some((tok, new_remaining)) = next_token(&borrow_mut.content) {
-                self.offset += borrow_mut.content.chars().count() - new_remaining.chars().count();
+                self.offset += borrow_mut.content.len() - new_remaining.len();
                 borrow_mut.content = new_remaining.to_owned();
                 tok
             } else {
@@ -161,31 +179,33 @@ impl<'a> Iterator for Lexer<'a> {
             match tok {
                 Token::Comment => continue,
 
-                Token::Whitespace(_) => continue,
-
-                Token::BlockComment(s) => self.current_line += s.lines().count() - 1,
-
-                Token::Newline => {
-                    self.current_line += 1;
-                    self.offset = 0;
+                Token::Error(msg) => {
+                    let position = Position::new(start, self.offset);
+                    borrow_mut
+                        .diagnostics
+                        .error(Stage::Lexical, E0001, msg, &file_name, Some(position));
                     continue;
                 }
 
+                Token::Whitespace(_) => continue,
+
+                Token::BlockComment(_) | Token::Newline => continue,
+
                 Token::ASM => {
                     self.asm_flag = true;
-                    return Some(Ok((self.current_line, Token::ASM, self.offset)));
+                    return Some(Ok((start, Token::ASM, self.offset)));
                 }
 
                 Token::StringConst(text) => {
                     if self.asm_flag == false {
-                        if (!borrow_mut.tables.string_table.contains(&text)) {
+                        if !borrow_mut.tables.string_table.contains(&text) {
                             borrow_mut.tables.string_table.push(text.clone());
                         }
                     } else {
                         self.asm_flag = false;
                     }
                     return Some(Ok((
-                        self.current_line,
+                        start,
                         Token::StringConst(text),
                         self.offset,
                     )));
@@ -193,30 +213,34 @@ impl<'a> Iterator for Lexer<'a> {
                 Token::IntConst(text) => {
                     // self.ctx.borrow_mut().tables.int_table.insert(text.clone());
                     borrow_mut.tables.int_table.insert(text.clone());
-                    return Some(Ok((self.current_line, Token::IntConst(text), self.offset)));
+                    return Some(Ok((start, Token::IntConst(text), self.offset)));
                 }
                 Token::Identifier(text, _) => {
                     // self.ctx.borrow_mut().tables.id_table.insert(text.clone());
                     borrow_mut.tables.id_table.insert(text.clone());
 
                     return Some(Ok((
-                        self.current_line,
-                        Token::Identifier(text, Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Identifier(text, Position::new(start, self.offset)),
                         self.offset,
                     )));
                 }
-                Token::TypeId(text) => {
-                    if (!borrow_mut.tables.string_table.contains(&text)) {
+                Token::TypeId(text, _) => {
+                    if !borrow_mut.tables.string_table.contains(&text) {
                         borrow_mut.tables.string_table.push(text.clone());
                     }
-                    return Some(Ok((self.current_line, Token::TypeId(text), self.offset)));
+                    return Some(Ok((
+                        start,
+                        Token::TypeId(text, Position::new(start, self.offset)),
+                        self.offset,
+                    )));
                 }
 
                 Token::Class_(_, _) => {
                     return Some(Ok((
-                        self.current_line,
+                        start,
                         Token::Class_(
-                            Position::new(self.current_line, self.offset),
+                            Position::new(start, self.offset),
                             // self.ctx.borrow().file_name.to_string(),
                             file_name,
                         ),
@@ -224,96 +248,152 @@ impl<'a> Iterator for Lexer<'a> {
                     )));
                 }
 
+                Token::Enum(_, _) => {
+                    return Some(Ok((
+                        start,
+                        Token::Enum(Position::new(start, self.offset), file_name),
+                        self.offset,
+                    )));
+                }
+
+                Token::Match(_) => {
+                    return Some(Ok((
+                        start,
+                        Token::Match(Position::new(start, self.offset)),
+                        self.offset,
+                    )))
+                }
+
+                Token::Is(_) => {
+                    return Some(Ok((
+                        start,
+                        Token::Is(Position::new(start, self.offset)),
+                        self.offset,
+                    )))
+                }
+
+                Token::As(_) => {
+                    return Some(Ok((
+                        start,
+                        Token::As(Position::new(start, self.offset)),
+                        self.offset,
+                    )))
+                }
+
+                Token::Throw(_) => {
+                    return Some(Ok((
+                        start,
+                        Token::Throw(Position::new(start, self.offset)),
+                        self.offset,
+                    )))
+                }
+
+                Token::Try(_) => {
+                    return Some(Ok((
+                        start,
+                        Token::Try(Position::new(start, self.offset)),
+                        self.offset,
+                    )))
+                }
+
+                Token::Catch(_) => {
+                    return Some(Ok((
+                        start,
+                        Token::Catch(Position::new(start, self.offset)),
+                        self.offset,
+                    )))
+                }
+
                 Token::Function(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Function(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Function(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::Return(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Return(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Return(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::If(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::If(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::If(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::Else(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Else(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Else(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::Let(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Let(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Let(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::While(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::While(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::While(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::New(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::New(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::New(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::Assign(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Assign(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Assign(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::Not(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Not(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Not(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::For(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::For(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::For(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 Token::Constructor(_) => {
                     return Some(Ok((
-                        self.current_line,
-                        Token::Constructor(Position::new(self.current_line, self.offset)),
+                        start,
+                        Token::Constructor(Position::new(start, self.offset)),
                         self.offset,
                     )))
                 }
 
                 token => {
-                    return Some(Ok((self.current_line, token, self.offset)));
+                    return Some(Ok((start, token, self.offset)));
                 }
             }
         }
@@ -5,11 +5,12 @@ pub enum Token {
     // raw type
     IntRawType(String),
     VoidRawType(String),
-    // BoolRawType(String),
+    BoolRawType(String),
     StrRawType(String),
 
     // keywords
     Class_(Position, String),
+    Enum(Position, String),
     Self_(String),
     Function(Position),
     Return(Position),
@@ -21,20 +22,29 @@ pub enum Token {
     New(Position),
     Not(Position),
     Constructor(Position),
+    Match(Position),
+    Is(Position),
+    As(Position),
+    Throw(Position),
+    Try(Position),
+    Catch(Position),
+    Finally,
     Inherits,
     Isnull,
+    Null,
     Public,
     Private,
     Then,
     Import,
     From,
+    Package,
     ASM,
 
     // const and id and typeid
     StringConst(String),
     IntConst(String),
     BoolConst(bool),
-    TypeId(String),
+    TypeId(String, Position),
     Identifier(String, Position),
 
     // op
@@ -57,8 +67,13 @@ pub enum Token {
     Rparen,
     Semicolon,
     Period,
+    SafePeriod,
+    Question,
+    DoubleQuestion,
     Comma,
     Colon,
+    DoubleColon,
+    Underscore,
     Newline,
     Whitespace(String),
     Comment,
@@ -0,0 +1,219 @@
+//! The strawberry compiler as a library. Sources go in as strings and assembly comes out as
+//! one, nothing is read from or written to the disk and nothing is printed, so tools and tests
+//! can compile in-process:
+//!
+//! ```ignore
+//! let program = strawberry::parse_source(src)?;
+//! let typed = strawberry::check(program)?;
+//! let asm = strawberry::emit_asm(&typed);
+//! ```
+//!
+//! The `strawberry` binary is a driver around these functions that reads a project and runs gcc.
+
+#![feature(const_trait_impl)]
+#[macro_use]
+extern crate lalrpop_util;
+
+lalrpop_mod!(pub strawberry);
+
+use std::cell::RefCell;
+
+use ctx::CompileContext;
+use parser::ast::class::Class;
+use utils::table::{self};
+
+pub use diagnostic::{Diagnostic, Diagnostics, MessageFormat, Severity};
+pub use lexer::Position;
+pub use semantic::lint::{Level, LintLevels};
+pub use source_map::SourceMap;
+
+mod cgen;
+mod ctx;
+pub mod diagnostic;
+// mod grammar;
+mod ir;
+mod lexer;
+mod parser;
+// mod llvm;
+mod semantic;
+mod source_map;
+mod ty;
+mod utils;
+
+const STRING: &str = "String";
+const OBJECT: &str = "Object";
+const INT: &str = "int";
+const INTEGER: &str = "Integer";
+const BOOL: &str = "Bool";
+const RAW_BOOL: &str = "bool";
+const SELF: &str = "self";
+const VOID: &str = "Void";
+const PRIMSLOT: &str = "PrimSlot";
+// the type of the null literal, lower case so no class can take the name
+const NULL_TYPE: &str = "null";
+const EXCEPTION: &str = "Exception";
+const RUNTIME_ERR: &str = "A method was called on null! \\n";
+const CAST_ERR: &str = "A bad cast occurred! \\n";
+const DIV_ERR: &str = "Division by zero! \\n";
+const MATCH_ERR: &str = "No arm of the match fits the value! \\n";
+const UNCAUGHT_ERR: &str = "Uncaught exception: ";
+// const EMPTY_POSITION: (usize, usize) = (0, 0);
+
+const INT_CONST_VAL_OFFSET: usize = 24;
+const BOOL_CONST_VAL_OFFSET: usize = 24;
+const STRING_CONST_VAL_OFFSET: usize = 24;
+const DISPATCH_TABLE_OFFSET: usize = 16;
+const NULL_TAG_OFFSET: usize = 8;
+// the parent's dispatch table is stored right before a dispatch table
+const PARENT_TABLE_OFFSET: usize = 8;
+const FIELD_BASIC_OFFSET: usize = 24;
+
+/// The name `parse_source` gives its source.
+pub const MAIN_FILE: &str = "main.st";
+
+/// The std files are named after this in diagnostics.
+const STD_DIR: &str = "std/";
+
+/// The std classes every program is compiled with, in the order they are parsed.
+const STD: [(&str, &str); 6] = [
+    ("Object.st", include_str!("../std/Object.st")),
+    ("Integer.st", include_str!("../std/Integer.st")),
+    ("String.st", include_str!("../std/String.st")),
+    ("Bool.st", include_str!("../std/Bool.st")),
+    ("Void.st", include_str!("../std/Void.st")),
+    ("Exception.st", include_str!("../std/Exception.st")),
+];
+
+/// A file of a program, its diagnostics point into it by `name`.
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+    /// One of the std files, marked by `parse_sources` rather than told by its name.
+    pub std: bool,
+}
+
+impl Source {
+    pub fn new(name: &str, text: &str) -> Source {
+        return Source {
+            name: name.to_string(),
+            text: text.to_string(),
+            std: false,
+        };
+    }
+}
+
+/// The parsed classes of a program and of the std, ready to be checked.
+pub struct Program {
+    ctx: CompileContext,
+}
+
+impl Program {
+    /// The lint levels `check` reports the lints with, each lint is at its default otherwise.
+    pub fn with_lints(mut self, lints: LintLevels) -> Program {
+        self.ctx.lints = lints;
+        return self;
+    }
+
+    /// The program starts by creating `class` and calling its `method`, `Main` and `main`
+    /// unless set here.
+    pub fn with_entry(mut self, class: &str, method: &str) -> Program {
+        self.ctx.entry_class = class.to_string();
+        self.ctx.entry_method = method.to_string();
+        return self;
+    }
+
+    /// The syntax trees of the classes that aren't in the std.
+    pub fn ast(&self) -> String {
+        return format!("{:#?}", own_classes(&self.ctx.classes));
+    }
+}
+
+/// A program that passed the semantic check, lowered to the IR the backends take. The lints
+/// that fired on it are in `warnings`.
+pub struct TypedProgram {
+    classes: Vec<Class>,
+    program: ir::Program,
+    pub warnings: Diagnostics,
+}
+
+impl TypedProgram {
+    /// The syntax trees of the classes that aren't in the std, as the semantic check left them.
+    pub fn typed_ast(&self) -> String {
+        return format!("{:#?}", own_classes(&self.classes));
+    }
+
+    /// The IR of the whole program, the std included.
+    pub fn ir(&self) -> String {
+        return format!("{:#?}", self.program);
+    }
+}
+
+fn own_classes(classes: &Vec<Class>) -> Vec<&Class> {
+    return classes.iter().filter(|c| !c.std).collect();
+}
+
+/// The tokens of the files, one a line as `file:line:column token`. Lexical errors are left for
+/// `parse_sources` to report.
+pub fn tokens(sources: &Vec<Source>) -> String {
+    let mut out = String::new();
+    for source in sources {
+        let mut source_map = SourceMap::new();
+        source_map.add(&source.name, source.text.clone());
+        let ctx_ref = RefCell::new(CompileContext::new());
+        ctx_ref.borrow_mut().content = source.text.clone();
+        ctx_ref.borrow_mut().file_name = source.name.clone();
+        for token in lexer::lexer_parse(&ctx_ref) {
+            let (start, token) = match token {
+                Ok((start, token, _)) => (start, token),
+                Err(_) => break,
+            };
+            let (line, column) = source_map.line_column(&source.name, start);
+            out.push_str(&format!("{}:{}:{} {:?}\n", source.name, line, column, token));
+        }
+    }
+    return out;
+}
+
+/// Parses a program of one file, named `MAIN_FILE`.
+pub fn parse_source(src: &str) -> Result<Program, Diagnostics> {
+    return parse_sources(vec![Source::new(MAIN_FILE, src)]);
+}
+
+/// Parses the files of a program, and the std after them.
+pub fn parse_sources(sources: Vec<Source>) -> Result<Program, Diagnostics> {
+    let mut sources = sources;
+    for (name, text) in STD {
+        sources.push(Source {
+            std: true,
+            ..Source::new(&format!("{}{}", STD_DIR, name), text)
+        });
+    }
+    let ctx_ref = RefCell::new(CompileContext::new());
+    parser::parse_sources(sources, &ctx_ref);
+    let ctx = ctx_ref.into_inner();
+    if ctx.diagnostics.has_errors() {
+        return Err(ctx.diagnostics);
+    }
+    return Ok(Program { ctx });
+}
+
+/// Checks the program, then lints the classes that aren't in the std.
+pub fn check(program: Program) -> Result<TypedProgram, Diagnostics> {
+    let (ctx, program) = semantic::semantic_check(program.ctx);
+    match program {
+        Some(program) if !ctx.diagnostics.has_errors() => {
+            return Ok(TypedProgram {
+                classes: ctx.classes,
+                program,
+                warnings: ctx.diagnostics,
+            })
+        }
+        _ => return Err(ctx.diagnostics),
+    }
+}
+
+/// The x86-64 assembly of the program, in the GNU syntax gcc assembles.
+pub fn emit_asm(program: &TypedProgram) -> String {
+    return cgen::asm(&program.program);
+}
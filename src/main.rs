@@ -1,52 +1,17 @@
-#![feature(const_trait_impl)]
-#![allow(warnings)]
-#[macro_use]
-extern crate lalrpop_util;
 extern crate clap;
 
-lalrpop_mod!(pub strawberry);
-
-use clap::{Arg, ColorChoice};
+use clap::{Arg, ArgAction, ColorChoice};
 use owo_colors::OwoColorize;
-use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 
 use std::path::Path;
-use utils::table::{self};
-use lazy_static::lazy_static;
+use strawberry::{Level, MessageFormat};
 
-mod cgen;
 mod complier;
-mod ctx;
-// mod grammar;
-mod lexer;
-mod parser;
-// mod llvm;
-mod semantic;
-// mod ty;
-mod utils;
-
-const STRING: &str = "String";
-const OBJECT: &str = "Object";
-const INT: &str = "int";
-const INTEGER: &str = "Integer";
-const BOOL: &str = "Bool";
-const SELF: &str = "self";
-const VOID: &str = "Void";
-const PRIMSLOT: &str = "PrimSlot";
-const RUNTIME_ERR: &str = "Some runtime errors occurred and the program has crashed! \\n";
-// const EMPTY_POSITION: (usize, usize) = (0, 0);
-
-const INT_CONST_VAL_OFFSET: usize = 24;
-const BOOL_CONST_VAL_OFFSET: usize = 24;
-const STRING_CONST_VAL_OFFSET: usize = 24;
-const DISPATCH_TABLE_OFFSET: usize = 16;
-const NULL_TAG_OFFSET: usize = 8;
-const FIELD_BASIC_OFFSET: usize = 24;
-
-const DEBUG: bool = false;
+mod driver;
+mod manifest;
 
 const LOGO: &str = r#"
 ______     __                                       __                                               
@@ -64,12 +29,42 @@ _\$$$$$$\  | $$ __ | $$   \$$/      $$| $$ | $$ | $$| $$  | $$| $$    $$| $$   \
 "#;
 
 fn main() {
-    println!("\n{}", LOGO.green());
     let mut cmd = clap::Command::new("Strawberry")
         .color(ColorChoice::Auto)
         .version("0.1-beta")
         .about("A toy object-oriented programming language")
-        .subcommand(clap::Command::new("build").about("Build the current project directory"))
+        .subcommand(
+            compile_command("build", "Build the current project directory, or a single file")
+                .arg(
+                    Arg::new("emit")
+                        .long("emit")
+                        .value_parser(complier::Emit::NAMES)
+                        .default_value("exe")
+                        .help("What to stop at and write, the dumps are printed unless -o is given"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .value_name("PATH")
+                        .help("Write the output here instead of its default place"),
+                )
+                .args(toolchain_args()),
+        )
+        .subcommand(compile_command(
+            "check",
+            "Check the current project directory, or a single file, without generating code",
+        ))
+        .subcommand(
+            compile_command("run", "Build the current project directory, or a single file, and run it")
+                .args(toolchain_args())
+                .arg(
+                    Arg::new("args")
+                        .last(true)
+                        .num_args(0..)
+                        .help("The arguments of the program, after --"),
+                ),
+        )
+        .subcommand(clap::Command::new("clean").about("Remove the build directory"))
         .subcommand(
             clap::Command::new("new")
                 .about("Create a new empty project folder")
@@ -81,17 +76,110 @@ fn main() {
         );
     let matches = cmd.clone().get_matches();
 
-    if let Some(matches) = matches.subcommand_matches("new") {
-        let msg = format!("🎉 Congratulations, you successfully created the project, please use cd ./{}, and then use strawberry build to build the project!", matches.get_one::<String>("name").unwrap());
-        println!("{}", msg.green());
-        create_project_folder(matches.get_one::<String>("name").unwrap());
-    } else if let Some(_) = matches.subcommand_matches("build") {
-        complier::build();
-    } else {
-        let _ = cmd.print_long_help();
+    match matches.subcommand() {
+        Some(("new", matches)) => {
+            println!("\n{}", LOGO.green());
+            let msg = format!("🎉 Congratulations, you successfully created the project, please use cd ./{}, and then use strawberry build to build the project!", matches.get_one::<String>("name").unwrap());
+            println!("{}", msg.green());
+            create_project_folder(matches.get_one::<String>("name").unwrap());
+        }
+        Some(("build", matches)) => {
+            if complier::build(&options(matches)).is_none() {
+                std::process::exit(1);
+            }
+        }
+        Some(("check", matches)) => {
+            if !complier::check(&options(matches)) {
+                std::process::exit(1);
+            }
+        }
+        Some(("run", matches)) => {
+            let args = matches.get_many::<String>("args").into_iter().flatten().cloned().collect();
+            std::process::exit(complier::run(&options(matches), args));
+        }
+        Some(("clean", _)) => {
+            if !complier::clean() {
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            let _ = cmd.print_long_help();
+        }
     }
 }
 
+/// A command that compiles, with the arguments every such command takes.
+fn compile_command(name: &'static str, about: &'static str) -> clap::Command {
+    return clap::Command::new(name)
+        .about(about)
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("A .st file to compile on its own, without a project folder"),
+        )
+        .arg(
+            Arg::new("message-format")
+                .long("message-format")
+                .value_parser(["human", "json"])
+                .default_value("human")
+                .help("Print the errors and warnings for people or as JSON lines for tools"),
+        )
+        .arg(lint_arg("allow", 'A', "Don't report a lint"))
+        .arg(lint_arg("warn", 'W', "Report a lint as a warning"))
+        .arg(lint_arg("deny", 'D', "Report a lint as an error, -D warnings denies every warning"));
+}
+
+/// `--cc` and `--linker`, for the commands that assemble and link.
+fn toolchain_args() -> [Arg; 2] {
+    return [
+        Arg::new("cc")
+            .long("cc")
+            .value_name("PROGRAM")
+            .help("The C compiler that assembles the program, CC or gcc when not given"),
+        Arg::new("linker")
+            .long("linker")
+            .value_name("PROGRAM")
+            .help("The C compiler driver that links the program, the C compiler when not given"),
+    ];
+}
+
+fn options(matches: &clap::ArgMatches) -> complier::Options {
+    let format = match matches.get_one::<String>("message-format").unwrap().as_str() {
+        "json" => MessageFormat::Json,
+        _ => MessageFormat::Human,
+    };
+    //* a later flag wins, so deny beats warn beats allow
+    let mut lints = vec![];
+    for (id, level) in [("allow", Level::Allow), ("warn", Level::Warn), ("deny", Level::Deny)] {
+        for name in matches.get_many::<String>(id).into_iter().flatten() {
+            lints.push((name.clone(), level));
+        }
+    }
+    let emit = match matches.try_get_one::<String>("emit") {
+        //* clap lets only `Emit::NAMES` through
+        Ok(Some(emit)) => complier::Emit::from_name(emit).expect("an --emit clap accepted"),
+        _ => complier::Emit::Exe,
+    };
+    return complier::Options {
+        format,
+        lints,
+        file: matches.get_one::<String>("file").cloned(),
+        emit,
+        output: matches.try_get_one::<String>("output").ok().flatten().cloned(),
+        cc: matches.try_get_one::<String>("cc").ok().flatten().cloned(),
+        linker: matches.try_get_one::<String>("linker").ok().flatten().cloned(),
+    };
+}
+
+/// `-A`, `-W` or `-D`, which may be given many times.
+fn lint_arg(id: &'static str, short: char, help: &'static str) -> Arg {
+    return Arg::new(id)
+        .short(short)
+        .value_name("LINT")
+        .action(ArgAction::Append)
+        .help(help);
+}
+
 fn create_project_folder(name: &str) {
     let path = Path::new(name);
 
@@ -107,6 +195,9 @@ fn create_project_folder(name: &str) {
     let mut file = File::create(path.join("src/main.st")).expect("Failed to create main.st");
 
     file.write(
-        b"class Main { \n\tfun main() -> Int { \n\t\tprint(\"Hello world!\"); \n\t\treturn 0; \n\t};\n};",
+        b"class Main {\n\tfn main() -> int {\n\t\tprint(\"Hello world!\");\n\t\treturn 0;\n\t};\n};\n",
     ).unwrap();
+
+    let manifest = manifest::Manifest::new(name).to_toml();
+    fs::write(path.join(manifest::MANIFEST_FILE), manifest).expect("Failed to create strawberry.toml");
 }
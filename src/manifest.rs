@@ -0,0 +1,217 @@
+//! `strawberry.toml`, the manifest of a project. Every key may be left out, a project without
+//! the file builds with the defaults.
+//!
+//! ```toml
+//! [package]
+//! name = "example"
+//! version = "0.1.0"
+//! entry = "Main.main"
+//! output = "example"
+//! backend = "asm"
+//! linker-args = []
+//!
+//! [lints]
+//! unused_private = "allow"
+//! ```
+
+use std::fs;
+
+use strawberry::LintLevels;
+
+pub const MANIFEST_FILE: &str = "strawberry.toml";
+
+/// The backends a project may be built with, `asm` is x86-64 assembly that gcc assembles.
+const BACKENDS: [&str; 1] = ["asm"];
+
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    /// Names the package in the messages of a build and is the default `output`.
+    pub name: String,
+    /// `major.minor.patch`, like `0.1.0`, shown next to the name.
+    pub version: String,
+    /// The class the program starts by creating, qualified when it is in a package.
+    pub entry_class: String,
+    /// The method without parameters it then calls.
+    pub entry_method: String,
+    /// The executable, in the build directory, named after the package by default.
+    pub output: String,
+    /// What the program is generated as, see `BACKENDS`.
+    pub backend: String,
+    /// Given to the linker after everything else, like `["-lm"]`.
+    pub linker_args: Vec<String>,
+    pub lints: LintLevels,
+}
+
+impl Manifest {
+    pub fn new(name: &str) -> Manifest {
+        return Manifest {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            entry_class: "Main".to_string(),
+            entry_method: "main".to_string(),
+            output: name.to_string(),
+            backend: BACKENDS[0].to_string(),
+            linker_args: vec![],
+            lints: LintLevels::new(),
+        };
+    }
+
+    /// The manifest in `dir`, the defaults named after `dir` when there is none.
+    pub fn load(dir: &str) -> Result<Manifest, String> {
+        let path = format!("{}/{}", dir, MANIFEST_FILE);
+        let name = std::path::Path::new(dir)
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "main".to_string());
+        return match fs::read_to_string(&path) {
+            Ok(content) => Manifest::parse(&content, &name),
+            Err(_) => Ok(Manifest::new(&name)),
+        };
+    }
+
+    /// Reads a manifest, `name` is the package name when it gives none.
+    pub fn parse(content: &str, name: &str) -> Result<Manifest, String> {
+        let mut manifest = Manifest::new(name);
+        let project: toml::Table = match content.parse() {
+            Ok(project) => project,
+            Err(e) => return Err(format!("{} is not valid TOML: {}", MANIFEST_FILE, e.message())),
+        };
+        match project.get("package") {
+            Some(toml::Value::Table(package)) => manifest.package(package)?,
+            Some(_) => return Err(format!("[package] in {} must be a table", MANIFEST_FILE)),
+            None => {}
+        }
+        match project.get("lints") {
+            Some(toml::Value::Table(table)) => manifest.lints.load(table)?,
+            Some(_) => return Err(format!("[lints] in {} must be a table", MANIFEST_FILE)),
+            None => {}
+        }
+        return Ok(manifest);
+    }
+
+    fn package(&mut self, package: &toml::Table) -> Result<(), String> {
+        for (key, value) in package {
+            match key.as_str() {
+                "name" => {
+                    let name = string(key, value)?;
+                    if name.is_empty() || name.contains('/') {
+                        return Err(format!(
+                            "The name in {} must be a file name, not \"{}\"",
+                            MANIFEST_FILE, name
+                        ));
+                    }
+                    self.name = name;
+                }
+                "version" => {
+                    let version = string(key, value)?;
+                    let parts: Vec<&str> = version.split('.').collect();
+                    if parts.len() != 3 || parts.iter().any(|p| p.parse::<u64>().is_err()) {
+                        return Err(format!(
+                            "The version in {} must be three numbers like \"0.1.0\", not \"{}\"",
+                            MANIFEST_FILE, version
+                        ));
+                    }
+                    self.version = version;
+                }
+                "entry" => {
+                    let entry = string(key, value)?;
+                    match entry.rsplit_once('.') {
+                        Some((class, method)) if !class.is_empty() && !method.is_empty() => {
+                            self.entry_class = class.to_string();
+                            self.entry_method = method.to_string();
+                        }
+                        _ => {
+                            return Err(format!(
+                                "The entry in {} must be a class and a method like \"Main.main\", not \"{}\"",
+                                MANIFEST_FILE, entry
+                            ))
+                        }
+                    }
+                }
+                "output" => {
+                    let output = string(key, value)?;
+                    if output.is_empty() || output.contains('/') {
+                        return Err(format!(
+                            "The output in {} must be a file name, not \"{}\"",
+                            MANIFEST_FILE, output
+                        ));
+                    }
+                    self.output = output;
+                }
+                //* the code generator doesn't optimize, so 0 is kept for the manifests that name it
+                "opt-level" => match value.as_integer() {
+                    Some(0) => {}
+                    _ => {
+                        return Err(format!(
+                            "The opt-level in {} can only be 0, the asm backend doesn't optimize",
+                            MANIFEST_FILE
+                        ))
+                    }
+                },
+                "backend" => {
+                    let backend = string(key, value)?;
+                    if !BACKENDS.contains(&backend.as_str()) {
+                        return Err(format!(
+                            "There is no backend called {}, the backends are {}",
+                            backend,
+                            BACKENDS.join(", ")
+                        ));
+                    }
+                    self.backend = backend;
+                }
+                "linker-args" => {
+                    let args = value.as_array().and_then(|args| {
+                        args.iter().map(|a| a.as_str().map(|a| a.to_string())).collect()
+                    });
+                    match args {
+                        Some(args) => self.linker_args = args,
+                        None => {
+                            return Err(format!(
+                                "The linker-args in {} must be a list of strings",
+                                MANIFEST_FILE
+                            ))
+                        }
+                    }
+                }
+                _ => return Err(format!("There is no key called {} in [package]", key)),
+            }
+        }
+        //* the executable is named after the package unless it is named on its own
+        if !package.contains_key("output") {
+            self.output = self.name.clone();
+        }
+        return Ok(());
+    }
+
+    /// What `strawberry new` writes, every key at its default.
+    pub fn to_toml(&self) -> String {
+        let linker_args: Vec<String> = self.linker_args.iter().map(|a| format!("{:?}", a)).collect();
+        return format!(
+            "[package]
+name = {:?}
+version = {:?}
+entry = \"{}.{}\"
+output = {:?}
+backend = {:?}
+linker-args = [{}]
+
+[lints]
+",
+            self.name,
+            self.version,
+            self.entry_class,
+            self.entry_method,
+            self.output,
+            self.backend,
+            linker_args.join(", ")
+        );
+    }
+}
+
+fn string(key: &str, value: &toml::Value) -> Result<String, String> {
+    match value.as_str() {
+        Some(s) => return Ok(s.to_string()),
+        None => return Err(format!("The {} in {} must be a string", key, MANIFEST_FILE)),
+    }
+}
@@ -1,13 +1,15 @@
 
-use crate::{lexer::Position, utils::util::do_vecs_match};
-use std::collections::HashMap;
+use super::nullable;
+use crate::{lexer::Position, ty::Ty, INT, OBJECT, STRING};
 use std::{
     hash::{Hash, Hasher},
-    io::SeekFrom,
     ops::Deref,
 };
 
-use super::{expr::Expr, Identifier, ParamDecl, Type, EMPTY_POSITION};
+use super::{
+    expr::{Expr, Match, MatchArm, Pattern, Return, Self_, VariantPattern},
+    Identifier, ParamDecl, Type,
+};
 
 #[derive(Debug, Clone)]
 pub struct Class {
@@ -16,6 +18,9 @@ pub struct Class {
     pub features: Vec<Feature>,
     pub position: Position,
     pub file_name: String,
+    pub variants: Option<Vec<EnumVariant>>,
+    /// Declared in a std file, the lints and the printed trees leave it alone.
+    pub std: bool,
 }
 impl Eq for Class {}
 impl PartialEq for Class {
@@ -30,6 +35,140 @@ impl Hash for Class {
     }
 }
 
+impl Class {
+    /// An enum is an ordinary class (implicitly inheriting Object) whose objects carry a tag
+    /// field and one slot per payload field of its widest variant.
+    /// A `to_string` returning the variant name is generated unless the enum defines one.
+    pub fn new_enum(
+        name: Type,
+        variants: Vec<EnumVariant>,
+        mut features: Vec<Feature>,
+        position: Position,
+        file_name: String,
+    ) -> Class {
+        let slot_num = variants.iter().map(|v| v.fields.len()).max().unwrap_or(0);
+        let mut layout = vec![Feature::Attribute(VarDecl::new(
+            ENUM_TAG.to_string(),
+            Some(INT.to_string()),
+            None,
+            position,
+            Ownership::Private,
+        ))];
+        for i in 0..slot_num {
+            layout.push(Feature::Attribute(VarDecl::new(
+                enum_slot(i),
+                Some(nullable(OBJECT)),
+                None,
+                position,
+                Ownership::Private,
+            )));
+        }
+
+        let has_to_string = features.iter().any(|f| match f {
+            Feature::Method(m) => m.name == "to_string",
+            _ => false,
+        });
+        if !has_to_string {
+            let arms = variants
+                .iter()
+                .map(|v| MatchArm {
+                    pattern: Pattern::Variant(VariantPattern {
+                        name: v.name.clone(),
+                        bindings: vec![None; v.fields.len()],
+                        position: v.position,
+                    }),
+                    guard: None,
+                    body: Box::new(Expr::Str(v.name.clone(), v.position)),
+                })
+                .collect();
+            let body = Expr::Return(Return {
+                val: Some(Box::new(Expr::Match(Match {
+                    expr: Box::new(Expr::Self_(Self_ {
+                        type_: name.clone(),
+                        position,
+                    })),
+                    arms,
+                    position,
+                    type_: STRING.to_string(),
+                }))),
+                position,
+            });
+            features.push(Feature::Method(MethodDecl::new(
+                "to_string".to_string(),
+                vec![],
+                STRING.to_string(),
+                Some(vec![body]),
+                position,
+                Ownership::Public,
+            )));
+        }
+        layout.append(&mut features);
+
+        Class {
+            name,
+            parent: None,
+            features: layout,
+            position,
+            file_name,
+            variants: Some(variants),
+            std: false,
+        }
+    }
+
+    pub fn is_enum(&self) -> bool {
+        self.variants.is_some()
+    }
+
+    pub fn get_variant(&self, name: &Type) -> Option<(usize, &EnumVariant)> {
+        self.variants
+            .as_ref()?
+            .iter()
+            .enumerate()
+            .find(|(_, v)| &v.name == name)
+    }
+}
+
+pub const ENUM_TAG: &str = "__tag";
+
+/// The label constructors are emitted under, see `mangle_method`.
+pub const CONSTRUCTOR: &str = "Constructor";
+
+pub fn enum_slot(index: usize) -> Identifier {
+    format!("__slot{}", index)
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: Type,
+    pub fields: Box<Vec<ParamDecl>>,
+    pub position: Position,
+    /// The types of `fields`, see `resolve`.
+    pub field_tys: Vec<Ty>,
+}
+
+impl EnumVariant {
+    pub fn new(name: Type, fields: Vec<ParamDecl>, position: Position) -> EnumVariant {
+        let mut variant = EnumVariant {
+            name,
+            fields: Box::new(fields),
+            position,
+            field_tys: vec![],
+        };
+        variant.resolve();
+        return variant;
+    }
+
+    /// Works out `field_tys` again, after the field types are rewritten.
+    pub fn resolve(&mut self) {
+        self.field_tys = param_tys(&self.fields);
+    }
+}
+
+/// The types of parameters or variant fields.
+pub fn param_tys(params: &Vec<ParamDecl>) -> Vec<Ty> {
+    return params.iter().map(|p| Ty::parse(&p.1)).collect();
+}
+
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Feature {
@@ -39,26 +178,6 @@ pub enum Feature {
 }
 
 impl Feature {
-    pub fn check_param(&self, other: &Self) -> bool {
-        match self {
-            Self::Method(m) => {
-                return m.check_param(other);
-            }
-            _ => {}
-        }
-        return false;
-    }
-
-    pub fn check_return_type(&self, other: &Self) -> bool {
-        match self {
-            Self::Method(m) => {
-                return m.check_return_type(other);
-            }
-            _ => {}
-        }
-        return false;
-    }
-
     pub fn get_ownership(&self) -> &Ownership {
         match self {
             Self::Method(m) => return &m.ownership,
@@ -68,10 +187,10 @@ impl Feature {
     }
 
     pub fn get_position(&self) -> Position {
-        if let Self::Method(m) = self {
-            return m.position;
-        } else {
-            return *EMPTY_POSITION;
+        match self {
+            Self::Method(m) => return m.position,
+            Self::Attribute(a) => return a.position,
+            Self::Constructor(c) => return c.position,
         }
     }
 
@@ -107,9 +226,35 @@ pub struct VarDecl {
     pub init: Box<Option<Expr>>,
     pub position: Position,
     pub ownership: Ownership,
-    
+    /// The type `type_` names, see `resolve`.
+    pub ty: Option<Ty>,
 }
 
+impl VarDecl {
+    pub fn new(
+        name: Identifier,
+        type_: Option<Type>,
+        init: Option<Expr>,
+        position: Position,
+        ownership: Ownership,
+    ) -> VarDecl {
+        let mut decl = VarDecl {
+            name,
+            type_,
+            init: Box::new(init),
+            position,
+            ownership,
+            ty: None,
+        };
+        decl.resolve();
+        return decl;
+    }
+
+    /// Works out `ty` again, after `type_` is rewritten.
+    pub fn resolve(&mut self) {
+        self.ty = self.type_.as_deref().map(Ty::parse);
+    }
+}
 
 impl PartialEq for VarDecl {
     fn eq(&self, other: &Self) -> bool {
@@ -131,38 +276,83 @@ pub struct MethodDecl {
     pub body: Box<Option<Vec<Expr>>>,
     pub position: Position,
     pub ownership: Ownership,
+    /// The types of `param` and `return_type`, see `resolve`.
+    pub param_tys: Vec<Ty>,
+    pub return_ty: Ty,
 }
 
+/// Two methods are the same one when their names and parameter types are,
+/// otherwise they overload each other.
 impl PartialEq for MethodDecl {
     fn eq(&self, other: &Self) -> bool {
-        return self.name == other.name;
+        return self.name == other.name && self.param_types() == other.param_types();
     }
 }
 impl MethodDecl {
-    pub fn check_param(&self, other: &Feature) -> bool {
-        if let Feature::Method(m) = other {
-            return crate::utils::util::do_vecs_match::<(String, String)>(
-                &(*self.param),
-                &(m.param),
-            );
-        } else {
-            return false;
-        }
+    pub fn new(
+        name: Identifier,
+        param: Vec<ParamDecl>,
+        return_type: Type,
+        body: Option<Vec<Expr>>,
+        position: Position,
+        ownership: Ownership,
+    ) -> MethodDecl {
+        let mut method = MethodDecl {
+            name,
+            param: Box::new(param),
+            return_type,
+            body: Box::new(body),
+            position,
+            ownership,
+            param_tys: vec![],
+            return_ty: Ty::VOID,
+        };
+        method.resolve();
+        return method;
     }
 
-    pub fn check_return_type(&self, other: &Feature) -> bool {
-        if let Feature::Method(m) = other {
-            return self.return_type == m.return_type;
-        } else {
-            return false;
-        }
+    /// Works out `param_tys` and `return_ty` again, after the declared types are rewritten.
+    pub fn resolve(&mut self) {
+        self.param_tys = param_tys(&self.param);
+        self.return_ty = Ty::parse(&self.return_type);
+    }
+
+    pub fn param_types(&self) -> Vec<Type> {
+        return self.param.iter().map(|p| p.1.clone()).collect();
+    }
+
+    pub fn mangled_name(&self) -> String {
+        return mangle_method(&self.name, &self.param_types());
     }
+
+    /// How the method is declared, like `fn draw(s:Shape) -> Shape`.
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self.param.iter().map(|p| format!("{}:{}", p.0, p.1)).collect();
+        return format!("fn {}({}) -> {}", self.name, params.join(", "), self.return_type);
+    }
+}
+
+/// `name$3int$N4Node` for `name(a:int, b:Node?)`, so overloads get their own labels and
+/// dispatch table slots. No name can contain `$`, and each type is prefixed with its length,
+/// after an `N` when it is nullable, so two signatures never share a label.
+pub fn mangle_method(name: &Identifier, param_types: &Vec<Type>) -> String {
+    let mut mangled = name.clone();
+    for type_ in param_types {
+        let (nullable, class_name) = match type_.strip_suffix('?') {
+            Some(class_name) => ("N", class_name),
+            None => ("", type_.as_str()),
+        };
+        mangled.push_str(&format!("${}{}{}", nullable, class_name.len(), class_name));
+    }
+    return mangled;
 }
 
 #[derive(Debug, Clone)]
 pub struct MethodCall {
     pub fun_name: Identifier,
     pub actual: Box<Vec<Expr>>,
+    /// The parameter types of the overload the call resolves to.
+    pub param_types: Vec<Type>,
 }
 
 #[derive(Debug, Clone)]
@@ -170,13 +360,45 @@ pub struct ConstructorDecl {
     pub param: Box<Vec<ParamDecl>>,
     pub body: Box<Option<Vec<Expr>>>,
     pub position: Position,
+    /// The types of `param`, see `resolve`.
+    pub param_tys: Vec<Ty>,
 }
 
-
+/// Two constructors are the same one when their parameter types are.
 impl PartialEq for ConstructorDecl {
     fn eq(&self, other: &Self) -> bool {
-        let other_param = other.param.deref();
-        let self_param = self.param.deref();
-        return do_vecs_match(other_param, self_param);
+        return self.param_types() == other.param_types();
+    }
+}
+impl ConstructorDecl {
+    pub fn new(param: Vec<ParamDecl>, body: Option<Vec<Expr>>, position: Position) -> ConstructorDecl {
+        let mut constructor = ConstructorDecl {
+            param: Box::new(param),
+            body: Box::new(body),
+            position,
+            param_tys: vec![],
+        };
+        constructor.resolve();
+        return constructor;
+    }
+
+    /// Works out `param_tys` again, after the parameter types are rewritten.
+    pub fn resolve(&mut self) {
+        self.param_tys = param_tys(&self.param);
+    }
+
+    pub fn param_types(&self) -> Vec<Type> {
+        return self.param.iter().map(|p| p.1.clone()).collect();
+    }
+
+    /// `Constructor$2T1$2T2`, or `Constructor` for the one without parameters.
+    pub fn mangled_name(&self) -> String {
+        return mangle_method(&CONSTRUCTOR.to_string(), &self.param_types());
+    }
+
+    /// How the constructor is declared, like `constructor(s:Shape)`.
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self.param.iter().map(|p| format!("{}:{}", p.0, p.1)).collect();
+        return format!("constructor({})", params.join(", "));
     }
 }
@@ -1,8 +1,6 @@
-use std::fmt::{write, Debug, Display};
+use std::fmt::Debug;
 
-use crate::{
-    lexer::Position, semantic::semantic::SemanticError, utils::table::{ClassTable, SymbolTable}, BOOL, INT, OBJECT, STRING,
-};
+use crate::{lexer::Position, ty::Ty};
 
 use super::{
     class::{MethodCall, VarDecl},
@@ -14,6 +12,23 @@ pub enum MathOp {
     ComputeOp(ComputeOp),
     CondOp(CondOp),
 }
+impl MathOp {
+    /// The method a class defines to overload the operator, `Integer` has them all.
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            MathOp::ComputeOp(ComputeOp::Add) => "add",
+            MathOp::ComputeOp(ComputeOp::Minus) => "minus",
+            MathOp::ComputeOp(ComputeOp::Mul) => "mul",
+            MathOp::ComputeOp(ComputeOp::Divide) => "divide",
+            MathOp::CondOp(CondOp::Equal) => "equal",
+            MathOp::CondOp(CondOp::More) => "more",
+            MathOp::CondOp(CondOp::MoreE) => "moree",
+            MathOp::CondOp(CondOp::Less) => "less",
+            MathOp::CondOp(CondOp::LessE) => "lesse",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ComputeOp {
     Add,
@@ -42,6 +57,10 @@ pub struct Dispatch {
     pub expr: DispatchExpr,
     pub position: Position,
     pub type_: Type,
+    /// `target?.expr` skips the dispatch and gives null when target is null
+    pub safe: bool,
+    /// The type the check gave the target, the class the member is looked up on.
+    pub target_type: Option<Ty>,
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +84,32 @@ pub struct Math {
     pub op: Box<MathOp>,
     pub right: Box<Expr>,
     pub type_: Type,
+    /// `left.add(right)` and so on, when the left operand is a class defining the operator.
+    pub overload: Option<Box<Expr>>,
+    pub position: Position,
+}
+
+/// `left ?? right` gives right when left is null
+#[derive(Debug, Clone)]
+pub struct Coalesce {
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+    pub type_: Type,
+    pub position: Position,
+}
+
+/// A raw value wrapped into its box class where an object is expected.
+#[derive(Debug, Clone)]
+pub struct Boxing {
+    pub expr: Box<Expr>,
+    pub class_name: Type,
+}
+
+/// The raw value taken out of an `Integer` or `Bool`.
+#[derive(Debug, Clone)]
+pub struct Unboxing {
+    pub expr: Box<Expr>,
+    pub type_: Type,
 }
 
 #[derive(Debug, Clone)]
@@ -76,6 +121,7 @@ pub struct Return {
 #[derive(Debug, Clone)]
 pub struct Let {
     pub var_decls: Box<Vec<VarDecl>>,
+    pub position: Position,
 }
 
 #[derive(Debug, Clone)]
@@ -101,11 +147,14 @@ pub struct Not {
 #[derive(Debug, Clone)]
 pub struct Isnull {
     pub expr: Box<Expr>,
+    pub position: Position,
 }
 
 #[derive(Debug, Clone)]
 pub struct Self_ {
     pub type_: Type,
+    /// The call's own position when `self` is only implied, as in `f()`.
+    pub position: Position,
 }
 
 #[derive(Debug, Clone)]
@@ -118,23 +167,106 @@ pub struct For {
 }
 
 #[derive(Debug, Clone)]
+/// `from "a.b" import Shape;` lets a file name the class `a.b.Shape` as `Shape`.
 pub struct Import {
-    pub file_name: String,
+    pub package: String,
     pub class_name: Type,
+    pub position: Position,
 }
 #[derive(Debug, Clone)]
 pub struct ConstructorCall {
     pub class_name: String,
     pub param: Option<Box<Vec<Expr>>>,
     pub position: Position,
+    /// The parameter types of the constructor the call resolves to,
+    /// None when the class has no constructor and only its field initializers run.
+    pub param_types: Option<Vec<Type>>,
+}
+
+/// `expr is Class`
+#[derive(Debug, Clone)]
+pub struct TypeTest {
+    pub expr: Box<Expr>,
+    pub class_name: Type,
+    pub position: Position,
+}
+
+/// `expr as Class`, checked at runtime when it is a downcast
+#[derive(Debug, Clone)]
+pub struct Cast {
+    pub expr: Box<Expr>,
+    pub class_name: Type,
+    pub position: Position,
+    /// Set by the check, an upcast always succeeds so it isn't tested when it runs.
+    pub upcast: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Throw {
+    pub expr: Box<Expr>,
+    pub position: Position,
+}
+
+/// `catch (e: Class) { ... }`
+#[derive(Debug, Clone)]
+pub struct Catch {
+    pub name: Identifier,
+    pub class_name: Type,
+    pub body: Box<Vec<Expr>>,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub body: Box<Vec<Expr>>,
+    pub catches: Vec<Catch>,
+    pub finally: Option<Box<Vec<Expr>>>,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantCall {
+    pub enum_name: Type,
+    pub variant: Type,
+    pub actual: Box<Vec<Expr>>,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantPattern {
+    pub name: Type,
+    /// `None` for a `_` binding.
+    pub bindings: Vec<Option<Identifier>>,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Wildcard,
+    Variant(VariantPattern),
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Expr>>,
+    pub body: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub expr: Box<Expr>,
+    pub arms: Vec<MatchArm>,
+    pub position: Position,
+    pub type_: Type,
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Identifier(IdentifierSrtuct),
-    Bool(Boolean),
-    Int(Int),
-    Str(Str),
+    Bool(Boolean, Position),
+    Int(Int, Position),
+    Str(Str, Position),
     Assignment(Assignment),
     ASM(String),
     Dispatch(Dispatch),
@@ -145,32 +277,20 @@ pub enum Expr {
     Let(Let),
     New(ConstructorCall),
     Self_(Self_),
-    Isvoid(Box<Expr>),
     Math(Math),
     Not(Not),
     Isnull(Isnull),
     Return(Return),
-    Import(Import),
-}
-
-pub trait TypeGet: Debug {
-    fn get_type(&self) -> Type;
-}
-
-impl TypeGet for Expr {
-    fn get_type(&self) -> Type {
-        match self {
-            Expr::Bool(_) => return BOOL.to_string(),
-            Expr::Str(_) => return STRING.to_string(),
-            Expr::Int(_) => return INT.to_string(),
-            Expr::New(constructor_call) => return constructor_call.class_name.clone(),
-            Expr::Identifier(e) => return e.type_.clone(),
-            Expr::Dispatch(e) => return e.type_.clone(),
-            Expr::Self_(e) => return e.type_.clone(),
-            Expr::Math(e) => return e.type_.clone(),
-            _ => return OBJECT.to_string(),
-        }
-    }
+    Variant(VariantCall),
+    Match(Match),
+    Is(TypeTest),
+    As(Cast),
+    Null(Position),
+    Coalesce(Coalesce),
+    Throw(Throw),
+    Try(Try),
+    Boxing(Boxing),
+    Unboxing(Unboxing),
 }
 
 impl Expr {
@@ -181,6 +301,43 @@ impl Expr {
             return false;
         }
     }
+
+    /// Where the whole expression is, only an empty block or inline assembly has no position.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Expr::Identifier(e) => return Some(e.pos),
+            Expr::Bool(_, pos) | Expr::Int(_, pos) | Expr::Str(_, pos) | Expr::Null(pos) => {
+                return Some(*pos)
+            }
+            Expr::Self_(e) => return Some(e.position),
+            Expr::Isnull(e) => return Some(e.position),
+            Expr::Dispatch(e) => return Some(e.position),
+            Expr::Cond(e) => return Some(e.position),
+            Expr::While(e) => return Some(e.position),
+            Expr::For(e) => return Some(e.position),
+            Expr::Return(e) => return Some(e.position),
+            Expr::Assignment(e) => return Some(e.position),
+            Expr::Not(e) => return Some(e.position),
+            Expr::New(e) => return Some(e.position),
+            Expr::Is(e) => return Some(e.position),
+            Expr::As(e) => return Some(e.position),
+            Expr::Throw(e) => return Some(e.position),
+            Expr::Try(e) => return Some(e.position),
+            Expr::Variant(e) => return Some(e.position),
+            Expr::Match(e) => return Some(e.position),
+            Expr::Let(e) => return Some(e.position),
+            Expr::Math(e) => return Some(e.position),
+            Expr::Coalesce(e) => return Some(e.position),
+            Expr::Boxing(e) => return e.expr.position(),
+            Expr::Unboxing(e) => return e.expr.position(),
+            Expr::Block(body) => {
+                let first = body.iter().find_map(|e| e.position())?;
+                let last = body.iter().rev().find_map(|e| e.position())?;
+                return Some(Position::new(first.start, last.end));
+            }
+            Expr::ASM(_) => return None,
+        }
+    }
 }
 
 // impl Display for MathOp {
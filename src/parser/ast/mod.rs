@@ -1,22 +1,35 @@
-use lazy_static::lazy_static;
-
-use crate::lexer::Position;
+use crate::{lexer::Position, NULL_TYPE};
 pub mod class;
 pub mod expr;
 pub mod program;
+pub mod visit;
+pub mod visit_mut;
 
 pub type Identifier = String;
 pub type Type = String;
 pub type Boolean = bool;
 pub type Int = u64;
 pub type Str = String;
-pub type ParamDecl = (Identifier, Type);
+/// A parameter or an enum variant field, with where its name is.
+pub type ParamDecl = (Identifier, Type, Position);
 
 pub fn is_primitive(ty: &str) -> bool {
-    let tys = vec!["int", "str", "void"];
+    let tys = vec!["int", "str", "void", "bool"];
     return tys.contains(&ty);
 }
 
-lazy_static! {
-    static ref EMPTY_POSITION: Position = Position { row: 0, column: 0 };
+/// `Node?` may hold null, so may the null literal itself.
+pub fn is_nullable(ty: &str) -> bool {
+    return ty.ends_with('?') || ty == NULL_TYPE;
+}
+
+pub fn non_null(ty: &str) -> Type {
+    return ty.trim_end_matches('?').to_string();
+}
+
+pub fn nullable(ty: &str) -> Type {
+    if is_nullable(ty) {
+        return ty.to_string();
+    }
+    return format!("{}?", ty);
 }
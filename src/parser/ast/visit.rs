@@ -0,0 +1,327 @@
+//! Walking the AST without spelling out every kind of node. A pass implements `Visitor`,
+//! overrides the `visit_*` methods of the nodes it is about, and calls the matching `walk_*`
+//! function where it still wants their children visited. `VisitorMut` does the same on a
+//! tree it may change.
+//!
+//! The type check and the lowering to IR stay matches over `Expr`, as each node gives them a
+//! value made of the values of its children, and a walk hands nothing back.
+
+use crate::lexer::Position;
+
+use super::{
+    class::{Class, ConstructorDecl, Feature, MethodDecl, VarDecl},
+    expr::{
+        Assignment, Boxing, Cast, Catch, Coalesce, Cond, ConstructorCall, Dispatch, DispatchExpr,
+        Expr, For, IdentifierSrtuct, Isnull, Let, Match, MatchArm, Math, Not, Return,
+        Self_, Throw, Try, TypeTest, Unboxing, VariantCall, While,
+    },
+    Boolean, Int, Str,
+};
+
+pub trait Visitor: Sized {
+    fn visit_class(&mut self, class_: &Class) {
+        walk_class(self, class_);
+    }
+
+    fn visit_feature(&mut self, feature: &Feature) {
+        walk_feature(self, feature);
+    }
+
+    fn visit_attribute(&mut self, attr: &VarDecl) {
+        walk_attribute(self, attr);
+    }
+
+    fn visit_method(&mut self, method: &MethodDecl) {
+        walk_method(self, method);
+    }
+
+    fn visit_constructor(&mut self, constructor: &ConstructorDecl) {
+        walk_constructor(self, constructor);
+    }
+
+    /// The statements of a method, a block, a branch or a loop.
+    fn visit_body(&mut self, body: &[Expr]) {
+        walk_body(self, body);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_identifier(&mut self, _e: &IdentifierSrtuct) {}
+
+    fn visit_bool(&mut self, _value: &Boolean, _position: &Position) {}
+
+    fn visit_int(&mut self, _value: &Int, _position: &Position) {}
+
+    fn visit_str(&mut self, _value: &Str, _position: &Position) {}
+
+    fn visit_null(&mut self, _position: &Position) {}
+
+    fn visit_asm(&mut self, _asm: &String) {}
+
+    fn visit_self(&mut self, _e: &Self_) {}
+
+    fn visit_assignment(&mut self, e: &Assignment) {
+        walk_assignment(self, e);
+    }
+
+    fn visit_dispatch(&mut self, e: &Dispatch) {
+        walk_dispatch(self, e);
+    }
+
+    fn visit_cond(&mut self, e: &Cond) {
+        walk_cond(self, e);
+    }
+
+    fn visit_while(&mut self, e: &While) {
+        walk_while(self, e);
+    }
+
+    fn visit_for(&mut self, e: &For) {
+        walk_for(self, e);
+    }
+
+    fn visit_block(&mut self, body: &[Expr]) {
+        self.visit_body(body);
+    }
+
+    fn visit_let(&mut self, e: &Let) {
+        walk_let(self, e);
+    }
+
+    fn visit_new(&mut self, e: &ConstructorCall) {
+        walk_new(self, e);
+    }
+
+    fn visit_math(&mut self, e: &Math) {
+        walk_math(self, e);
+    }
+
+    fn visit_not(&mut self, e: &Not) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_isnull(&mut self, e: &Isnull) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_return(&mut self, e: &Return) {
+        walk_return(self, e);
+    }
+
+    fn visit_variant(&mut self, e: &VariantCall) {
+        walk_variant(self, e);
+    }
+
+    fn visit_match(&mut self, e: &Match) {
+        walk_match(self, e);
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        walk_match_arm(self, arm);
+    }
+
+    fn visit_is(&mut self, e: &TypeTest) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_as(&mut self, e: &Cast) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_coalesce(&mut self, e: &Coalesce) {
+        walk_coalesce(self, e);
+    }
+
+    fn visit_throw(&mut self, e: &Throw) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_try(&mut self, e: &Try) {
+        walk_try(self, e);
+    }
+
+    fn visit_catch(&mut self, catch: &Catch) {
+        walk_catch(self, catch);
+    }
+
+    fn visit_boxing(&mut self, e: &Boxing) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_unboxing(&mut self, e: &Unboxing) {
+        self.visit_expr(&e.expr);
+    }
+}
+
+pub fn walk_class<V: Visitor>(v: &mut V, class_: &Class) {
+    for feature in &class_.features {
+        v.visit_feature(feature);
+    }
+}
+
+pub fn walk_feature<V: Visitor>(v: &mut V, feature: &Feature) {
+    match feature {
+        Feature::Attribute(attr) => v.visit_attribute(attr),
+        Feature::Method(method) => v.visit_method(method),
+        Feature::Constructor(constructor) => v.visit_constructor(constructor),
+    }
+}
+
+pub fn walk_attribute<V: Visitor>(v: &mut V, attr: &VarDecl) {
+    if let Some(init) = &*attr.init {
+        v.visit_expr(init);
+    }
+}
+
+pub fn walk_method<V: Visitor>(v: &mut V, method: &MethodDecl) {
+    if let Some(body) = &*method.body {
+        v.visit_body(body);
+    }
+}
+
+pub fn walk_constructor<V: Visitor>(v: &mut V, constructor: &ConstructorDecl) {
+    if let Some(body) = &*constructor.body {
+        v.visit_body(body);
+    }
+}
+
+pub fn walk_body<V: Visitor>(v: &mut V, body: &[Expr]) {
+    for expr in body {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_expr<V: Visitor>(v: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Identifier(e) => v.visit_identifier(e),
+        Expr::Bool(value, position) => v.visit_bool(value, position),
+        Expr::Int(value, position) => v.visit_int(value, position),
+        Expr::Str(value, position) => v.visit_str(value, position),
+        Expr::Null(position) => v.visit_null(position),
+        Expr::ASM(asm) => v.visit_asm(asm),
+        Expr::Self_(e) => v.visit_self(e),
+        Expr::Assignment(e) => v.visit_assignment(e),
+        Expr::Dispatch(e) => v.visit_dispatch(e),
+        Expr::Cond(e) => v.visit_cond(e),
+        Expr::While(e) => v.visit_while(e),
+        Expr::For(e) => v.visit_for(e),
+        Expr::Block(body) => v.visit_block(body),
+        Expr::Let(e) => v.visit_let(e),
+        Expr::New(e) => v.visit_new(e),
+        Expr::Math(e) => v.visit_math(e),
+        Expr::Not(e) => v.visit_not(e),
+        Expr::Isnull(e) => v.visit_isnull(e),
+        Expr::Return(e) => v.visit_return(e),
+        Expr::Variant(e) => v.visit_variant(e),
+        Expr::Match(e) => v.visit_match(e),
+        Expr::Is(e) => v.visit_is(e),
+        Expr::As(e) => v.visit_as(e),
+        Expr::Coalesce(e) => v.visit_coalesce(e),
+        Expr::Throw(e) => v.visit_throw(e),
+        Expr::Try(e) => v.visit_try(e),
+        Expr::Boxing(e) => v.visit_boxing(e),
+        Expr::Unboxing(e) => v.visit_unboxing(e),
+    }
+}
+
+pub fn walk_assignment<V: Visitor>(v: &mut V, e: &Assignment) {
+    v.visit_expr(&e.compute);
+}
+
+pub fn walk_dispatch<V: Visitor>(v: &mut V, e: &Dispatch) {
+    v.visit_expr(&e.target);
+    if let DispatchExpr::Method(call) = &e.expr {
+        for actual in call.actual.iter() {
+            v.visit_expr(actual);
+        }
+    }
+}
+
+pub fn walk_cond<V: Visitor>(v: &mut V, e: &Cond) {
+    v.visit_expr(&e.test);
+    v.visit_body(&e.then_body);
+    v.visit_body(&e.else_body);
+}
+
+pub fn walk_while<V: Visitor>(v: &mut V, e: &While) {
+    v.visit_expr(&e.test);
+    v.visit_body(&e.body);
+}
+
+pub fn walk_for<V: Visitor>(v: &mut V, e: &For) {
+    for init in e.init.iter() {
+        v.visit_expr(init);
+    }
+    for test in e.test.iter() {
+        v.visit_expr(test);
+    }
+    v.visit_body(&e.body);
+    v.visit_body(&e.iter);
+}
+
+pub fn walk_let<V: Visitor>(v: &mut V, e: &Let) {
+    for decl in e.var_decls.iter() {
+        if let Some(init) = &*decl.init {
+            v.visit_expr(init);
+        }
+    }
+}
+
+pub fn walk_new<V: Visitor>(v: &mut V, e: &ConstructorCall) {
+    for param in e.param.iter().flat_map(|p| p.iter()) {
+        v.visit_expr(param);
+    }
+}
+
+/// The operands only, an overloaded operator's method call holds copies of them.
+pub fn walk_math<V: Visitor>(v: &mut V, e: &Math) {
+    v.visit_expr(&e.left);
+    v.visit_expr(&e.right);
+}
+
+pub fn walk_return<V: Visitor>(v: &mut V, e: &Return) {
+    if let Some(val) = &e.val {
+        v.visit_expr(val);
+    }
+}
+
+pub fn walk_variant<V: Visitor>(v: &mut V, e: &VariantCall) {
+    for actual in e.actual.iter() {
+        v.visit_expr(actual);
+    }
+}
+
+pub fn walk_match<V: Visitor>(v: &mut V, e: &Match) {
+    v.visit_expr(&e.expr);
+    for arm in e.arms.iter() {
+        v.visit_match_arm(arm);
+    }
+}
+
+pub fn walk_match_arm<V: Visitor>(v: &mut V, arm: &MatchArm) {
+    if let Some(guard) = &arm.guard {
+        v.visit_expr(guard);
+    }
+    v.visit_expr(&arm.body);
+}
+
+pub fn walk_coalesce<V: Visitor>(v: &mut V, e: &Coalesce) {
+    v.visit_expr(&e.left);
+    v.visit_expr(&e.right);
+}
+
+pub fn walk_try<V: Visitor>(v: &mut V, e: &Try) {
+    v.visit_body(&e.body);
+    for catch in e.catches.iter() {
+        v.visit_catch(catch);
+    }
+    if let Some(finally) = &e.finally {
+        v.visit_body(finally);
+    }
+}
+
+pub fn walk_catch<V: Visitor>(v: &mut V, catch: &Catch) {
+    v.visit_body(&catch.body);
+}
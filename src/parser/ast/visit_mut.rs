@@ -0,0 +1,322 @@
+//! `Visitor` for a tree that may be changed on the way, see `visit`. Every method and walk
+//! function is the one of `Visitor` with `_mut` at the end.
+
+use crate::lexer::Position;
+
+use super::{
+    class::{Class, ConstructorDecl, Feature, MethodDecl, VarDecl},
+    expr::{
+        Assignment, Boxing, Cast, Catch, Coalesce, Cond, ConstructorCall, Dispatch, DispatchExpr,
+        Expr, For, IdentifierSrtuct, Isnull, Let, Match, MatchArm, Math, Not, Return,
+        Self_, Throw, Try, TypeTest, Unboxing, VariantCall, While,
+    },
+    Boolean, Int, Str,
+};
+
+pub trait VisitorMut: Sized {
+    fn visit_class_mut(&mut self, class_: &mut Class) {
+        walk_class_mut(self, class_);
+    }
+
+    fn visit_feature_mut(&mut self, feature: &mut Feature) {
+        walk_feature_mut(self, feature);
+    }
+
+    fn visit_attribute_mut(&mut self, attr: &mut VarDecl) {
+        walk_attribute_mut(self, attr);
+    }
+
+    fn visit_method_mut(&mut self, method: &mut MethodDecl) {
+        walk_method_mut(self, method);
+    }
+
+    fn visit_constructor_mut(&mut self, constructor: &mut ConstructorDecl) {
+        walk_constructor_mut(self, constructor);
+    }
+
+    /// The statements of a method, a block, a branch or a loop.
+    fn visit_body_mut(&mut self, body: &mut [Expr]) {
+        walk_body_mut(self, body);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_identifier_mut(&mut self, _e: &mut IdentifierSrtuct) {}
+
+    fn visit_bool_mut(&mut self, _value: &mut Boolean, _position: &mut Position) {}
+
+    fn visit_int_mut(&mut self, _value: &mut Int, _position: &mut Position) {}
+
+    fn visit_str_mut(&mut self, _value: &mut Str, _position: &mut Position) {}
+
+    fn visit_null_mut(&mut self, _position: &mut Position) {}
+
+    fn visit_asm_mut(&mut self, _asm: &mut String) {}
+
+    fn visit_self_mut(&mut self, _e: &mut Self_) {}
+
+    fn visit_assignment_mut(&mut self, e: &mut Assignment) {
+        walk_assignment_mut(self, e);
+    }
+
+    fn visit_dispatch_mut(&mut self, e: &mut Dispatch) {
+        walk_dispatch_mut(self, e);
+    }
+
+    fn visit_cond_mut(&mut self, e: &mut Cond) {
+        walk_cond_mut(self, e);
+    }
+
+    fn visit_while_mut(&mut self, e: &mut While) {
+        walk_while_mut(self, e);
+    }
+
+    fn visit_for_mut(&mut self, e: &mut For) {
+        walk_for_mut(self, e);
+    }
+
+    fn visit_block_mut(&mut self, body: &mut [Expr]) {
+        self.visit_body_mut(body);
+    }
+
+    fn visit_let_mut(&mut self, e: &mut Let) {
+        walk_let_mut(self, e);
+    }
+
+    fn visit_new_mut(&mut self, e: &mut ConstructorCall) {
+        walk_new_mut(self, e);
+    }
+
+    fn visit_math_mut(&mut self, e: &mut Math) {
+        walk_math_mut(self, e);
+    }
+
+    fn visit_not_mut(&mut self, e: &mut Not) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_isnull_mut(&mut self, e: &mut Isnull) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_return_mut(&mut self, e: &mut Return) {
+        walk_return_mut(self, e);
+    }
+
+    fn visit_variant_mut(&mut self, e: &mut VariantCall) {
+        walk_variant_mut(self, e);
+    }
+
+    fn visit_match_mut(&mut self, e: &mut Match) {
+        walk_match_mut(self, e);
+    }
+
+    fn visit_match_arm_mut(&mut self, arm: &mut MatchArm) {
+        walk_match_arm_mut(self, arm);
+    }
+
+    fn visit_is_mut(&mut self, e: &mut TypeTest) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_as_mut(&mut self, e: &mut Cast) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_coalesce_mut(&mut self, e: &mut Coalesce) {
+        walk_coalesce_mut(self, e);
+    }
+
+    fn visit_throw_mut(&mut self, e: &mut Throw) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_try_mut(&mut self, e: &mut Try) {
+        walk_try_mut(self, e);
+    }
+
+    fn visit_catch_mut(&mut self, catch: &mut Catch) {
+        walk_catch_mut(self, catch);
+    }
+
+    fn visit_boxing_mut(&mut self, e: &mut Boxing) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_unboxing_mut(&mut self, e: &mut Unboxing) {
+        self.visit_expr_mut(&mut e.expr);
+    }
+}
+
+pub fn walk_class_mut<V: VisitorMut>(v: &mut V, class_: &mut Class) {
+    for feature in &mut class_.features {
+        v.visit_feature_mut(feature);
+    }
+}
+
+pub fn walk_feature_mut<V: VisitorMut>(v: &mut V, feature: &mut Feature) {
+    match feature {
+        Feature::Attribute(attr) => v.visit_attribute_mut(attr),
+        Feature::Method(method) => v.visit_method_mut(method),
+        Feature::Constructor(constructor) => v.visit_constructor_mut(constructor),
+    }
+}
+
+pub fn walk_attribute_mut<V: VisitorMut>(v: &mut V, attr: &mut VarDecl) {
+    if let Some(init) = &mut *attr.init {
+        v.visit_expr_mut(init);
+    }
+}
+
+pub fn walk_method_mut<V: VisitorMut>(v: &mut V, method: &mut MethodDecl) {
+    if let Some(body) = &mut *method.body {
+        v.visit_body_mut(body);
+    }
+}
+
+pub fn walk_constructor_mut<V: VisitorMut>(v: &mut V, constructor: &mut ConstructorDecl) {
+    if let Some(body) = &mut *constructor.body {
+        v.visit_body_mut(body);
+    }
+}
+
+pub fn walk_body_mut<V: VisitorMut>(v: &mut V, body: &mut [Expr]) {
+    for expr in body {
+        v.visit_expr_mut(expr);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut>(v: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Identifier(e) => v.visit_identifier_mut(e),
+        Expr::Bool(value, position) => v.visit_bool_mut(value, position),
+        Expr::Int(value, position) => v.visit_int_mut(value, position),
+        Expr::Str(value, position) => v.visit_str_mut(value, position),
+        Expr::Null(position) => v.visit_null_mut(position),
+        Expr::ASM(asm) => v.visit_asm_mut(asm),
+        Expr::Self_(e) => v.visit_self_mut(e),
+        Expr::Assignment(e) => v.visit_assignment_mut(e),
+        Expr::Dispatch(e) => v.visit_dispatch_mut(e),
+        Expr::Cond(e) => v.visit_cond_mut(e),
+        Expr::While(e) => v.visit_while_mut(e),
+        Expr::For(e) => v.visit_for_mut(e),
+        Expr::Block(body) => v.visit_block_mut(body),
+        Expr::Let(e) => v.visit_let_mut(e),
+        Expr::New(e) => v.visit_new_mut(e),
+        Expr::Math(e) => v.visit_math_mut(e),
+        Expr::Not(e) => v.visit_not_mut(e),
+        Expr::Isnull(e) => v.visit_isnull_mut(e),
+        Expr::Return(e) => v.visit_return_mut(e),
+        Expr::Variant(e) => v.visit_variant_mut(e),
+        Expr::Match(e) => v.visit_match_mut(e),
+        Expr::Is(e) => v.visit_is_mut(e),
+        Expr::As(e) => v.visit_as_mut(e),
+        Expr::Coalesce(e) => v.visit_coalesce_mut(e),
+        Expr::Throw(e) => v.visit_throw_mut(e),
+        Expr::Try(e) => v.visit_try_mut(e),
+        Expr::Boxing(e) => v.visit_boxing_mut(e),
+        Expr::Unboxing(e) => v.visit_unboxing_mut(e),
+    }
+}
+
+pub fn walk_assignment_mut<V: VisitorMut>(v: &mut V, e: &mut Assignment) {
+    v.visit_expr_mut(&mut e.compute);
+}
+
+pub fn walk_dispatch_mut<V: VisitorMut>(v: &mut V, e: &mut Dispatch) {
+    v.visit_expr_mut(&mut e.target);
+    if let DispatchExpr::Method(call) = &mut e.expr {
+        for actual in call.actual.iter_mut() {
+            v.visit_expr_mut(actual);
+        }
+    }
+}
+
+pub fn walk_cond_mut<V: VisitorMut>(v: &mut V, e: &mut Cond) {
+    v.visit_expr_mut(&mut e.test);
+    v.visit_body_mut(&mut e.then_body);
+    v.visit_body_mut(&mut e.else_body);
+}
+
+pub fn walk_while_mut<V: VisitorMut>(v: &mut V, e: &mut While) {
+    v.visit_expr_mut(&mut e.test);
+    v.visit_body_mut(&mut e.body);
+}
+
+pub fn walk_for_mut<V: VisitorMut>(v: &mut V, e: &mut For) {
+    for init in e.init.iter_mut() {
+        v.visit_expr_mut(init);
+    }
+    for test in e.test.iter_mut() {
+        v.visit_expr_mut(test);
+    }
+    v.visit_body_mut(&mut e.body);
+    v.visit_body_mut(&mut e.iter);
+}
+
+pub fn walk_let_mut<V: VisitorMut>(v: &mut V, e: &mut Let) {
+    for decl in e.var_decls.iter_mut() {
+        if let Some(init) = &mut *decl.init {
+            v.visit_expr_mut(init);
+        }
+    }
+}
+
+pub fn walk_new_mut<V: VisitorMut>(v: &mut V, e: &mut ConstructorCall) {
+    for param in e.param.iter_mut().flat_map(|p| p.iter_mut()) {
+        v.visit_expr_mut(param);
+    }
+}
+
+/// The operands only, an overloaded operator's method call holds copies of them.
+pub fn walk_math_mut<V: VisitorMut>(v: &mut V, e: &mut Math) {
+    v.visit_expr_mut(&mut e.left);
+    v.visit_expr_mut(&mut e.right);
+}
+
+pub fn walk_return_mut<V: VisitorMut>(v: &mut V, e: &mut Return) {
+    if let Some(val) = &mut e.val {
+        v.visit_expr_mut(val);
+    }
+}
+
+pub fn walk_variant_mut<V: VisitorMut>(v: &mut V, e: &mut VariantCall) {
+    for actual in e.actual.iter_mut() {
+        v.visit_expr_mut(actual);
+    }
+}
+
+pub fn walk_match_mut<V: VisitorMut>(v: &mut V, e: &mut Match) {
+    v.visit_expr_mut(&mut e.expr);
+    for arm in e.arms.iter_mut() {
+        v.visit_match_arm_mut(arm);
+    }
+}
+
+pub fn walk_match_arm_mut<V: VisitorMut>(v: &mut V, arm: &mut MatchArm) {
+    if let Some(guard) = &mut arm.guard {
+        v.visit_expr_mut(guard);
+    }
+    v.visit_expr_mut(&mut arm.body);
+}
+
+pub fn walk_coalesce_mut<V: VisitorMut>(v: &mut V, e: &mut Coalesce) {
+    v.visit_expr_mut(&mut e.left);
+    v.visit_expr_mut(&mut e.right);
+}
+
+pub fn walk_try_mut<V: VisitorMut>(v: &mut V, e: &mut Try) {
+    v.visit_body_mut(&mut e.body);
+    for catch in e.catches.iter_mut() {
+        v.visit_catch_mut(catch);
+    }
+    if let Some(finally) = &mut e.finally {
+        v.visit_body_mut(finally);
+    }
+}
+
+pub fn walk_catch_mut<V: VisitorMut>(v: &mut V, catch: &mut Catch) {
+    v.visit_body_mut(&mut catch.body);
+}
@@ -1,81 +1,96 @@
-use std::{cell::RefCell, fs::File, io::Read};
+use std::cell::RefCell;
 
-use owo_colors::OwoColorize;
+use lalrpop_util::ParseError;
 
 use crate::{
-    complier::print_err_msg,
     ctx::CompileContext,
-    lexer::{self, lexer::Lexer, Position},
-    strawberry,
+    diagnostic::{codes::*, Stage},
+    lexer::{
+        self,
+        lexer::{Lexer, LexicalError},
+        token::Token,
+        BytePos, Position,
+    },
+    semantic::packages::FileScope,
+    strawberry, Source,
 };
 
-use self::ast::class::Class;
-
 pub mod ast;
 
-pub fn parse_file(files: Vec<String>, ctx_ref: &RefCell<CompileContext>) {
-    let main_file = "./src/main.st".to_string();
-    if !files.contains(&main_file) {
-        let err = format!("❌ There is no main.st in your src directory!");
-        println!("{}", err.red());
-    }
-
-    for file_name in files {
-        let mut file = File::open(&file_name).unwrap();
-        let mut content = String::new();
-        if let Ok(_) = file.read_to_string(&mut content) {
-        } else {
-            println!("{}", "❌ Some unexpected errors occurred, maybe you can solve it by recreating the project".red());
-            return;
-        }
-        ctx_ref.borrow_mut().preprocess(content);
-        ctx_ref.borrow_mut().file_name = file_name;
+/// Parses every source into the classes of `ctx_ref`, a file that doesn't parse leaves its
+/// errors in the diagnostics.
+pub fn parse_sources(sources: Vec<Source>, ctx_ref: &RefCell<CompileContext>) {
+    for source in sources {
+        ctx_ref.borrow_mut().diagnostics.source_map.add(&source.name, source.text.clone());
+        ctx_ref.borrow_mut().content = source.text;
+        ctx_ref.borrow_mut().file_name = source.name;
         let lexer: Lexer = lexer::lexer_parse(&ctx_ref);
-        parse(lexer, &ctx_ref);
+        parse(lexer, &ctx_ref, source.std);
     }
-    println!(
-        "{}",
-        "🎉 Congratulations you passped the syntax analysis!".green()
-    );
 }
 
-fn parse(lexer: Lexer, ctx: &RefCell<CompileContext>) {
-    let program = strawberry::ProgramParser::new().parse(lexer);
+fn parse(lexer: Lexer, ctx: &RefCell<CompileContext>, std: bool) {
+    let mut errors = vec![];
+    let program = strawberry::ProgramParser::new().parse(&mut errors, lexer);
 
+    let file_name = ctx.borrow().file_name.clone();
+    let mut ctx = ctx.borrow_mut();
+    for recovery in errors {
+        let (code, msg, position) = syntax_error(recovery.error);
+        ctx.diagnostics.error(Stage::Syntax, code, msg, &file_name, position);
+    }
     match program {
-        Ok(mut v) => {
-            ctx.borrow_mut().classes.append(&mut v.1);
+        Ok((package, imports, mut classes)) => {
+            for class_ in classes.iter_mut() {
+                class_.std = std;
+            }
+            ctx.scopes.push(FileScope {
+                file_name,
+                package,
+                imports,
+            });
+            ctx.classes.append(&mut classes);
         }
         Err(e) => {
-            let err = format!(
-                "❌ Oops, syntax error has occurred in {}!",
-                &ctx.borrow().file_name
+            let (code, msg, position) = syntax_error(e);
+            ctx.diagnostics.error(Stage::Syntax, code, msg, &file_name, position);
+        }
+    }
+}
+
+fn syntax_error(
+    e: ParseError<BytePos, Token, LexicalError>,
+) -> (&'static str, String, Option<Position>) {
+    match e {
+        ParseError::UnrecognizedToken { token, expected } => {
+            return (
+                E0002,
+                format!(
+                    "There is an unrecognized token <{:?}>, maybe you can try {} here!",
+                    token.1,
+                    expected.join(" or ")
+                ),
+                Some(Position::new(token.0, token.2)),
+            );
+        }
+        ParseError::ExtraToken { token } => {
+            return (
+                E0003,
+                format!("There is an extra token <{:?}>!", token.1),
+                Some(Position::new(token.0, token.2)),
+            );
+        }
+        ParseError::UnrecognizedEOF { location, expected } => {
+            return (
+                E0004,
+                format!(
+                    "The file ended too early, maybe you can try {} at the end!",
+                    expected.join(" or ")
+                ),
+                Some(Position::new(location, location)),
             );
-            println!("{}", err.red());
-            print!("{}", "Err: ".red());
-            match e {
-                lalrpop_util::ParseError::UnrecognizedToken { token, expected } => {
-                    let err = format!("There is an unrecognized token <{:?}> !", token.1,);
-                    println!("{}", err.red());
-                    print_err_msg(
-                        Position::new(token.0, token.2),
-                        &ctx.borrow().file_name,
-                        &format!("Maybe you can try {} here!", expected.join(" or ")),
-                    );
-                }
-                lalrpop_util::ParseError::ExtraToken { token } => {
-                    let err = format!(
-                        "There is an extra token <{:?}> at {}:{}:{}",
-                        token.1,
-                        &ctx.borrow().file_name,
-                        token.0,
-                        token.2,
-                    );
-                    println!("{}", err.red());
-                }
-                _ => {}
-            }
-            return;
         }
+        ParseError::User { error } => return (E0001, error.err_msg, Some(error.position)),
+        _ => return (E0005, format!("There is an invalid token!"), None),
     }
 }
@@ -0,0 +1,311 @@
+use std::ops::Deref;
+
+use crate::{
+    lexer::Position,
+    parser::ast::{
+        expr::{
+            Assignment, Cond, Dispatch, DispatchExpr, Expr, For, IdentifierSrtuct, Let, Match,
+            Pattern, Try, While,
+        },
+        is_nullable,
+        visit::{walk_assignment, walk_body, walk_dispatch, walk_expr, Visitor},
+        Identifier,
+    },
+    diagnostic::codes::E0223,
+    ty::Ty,
+};
+
+use super::semantic::SemanticError;
+
+/// Whether control can never run past `body`, because every path through it returns or throws.
+pub fn always_returns(body: &[Expr]) -> bool {
+    return body.iter().any(returns);
+}
+
+fn returns(expr: &Expr) -> bool {
+    match expr {
+        Expr::Return(_) | Expr::Throw(_) => return true,
+        Expr::Block(body) => return always_returns(body),
+        Expr::Cond(e) => return always_returns(&e.then_body) && always_returns(&e.else_body),
+        //* a loop only ends by returning when its test is the literal true
+        Expr::While(e) => return matches!(e.test.deref(), Expr::Bool(true, _)),
+        Expr::For(e) => {
+            return match e.test.first() {
+                Some(test) => matches!(test, Expr::Bool(true, _)),
+                None => true,
+            }
+        }
+        Expr::Try(e) => {
+            if let Some(finally) = &e.finally {
+                if always_returns(finally) {
+                    return true;
+                }
+            }
+            return always_returns(&e.body) && e.catches.iter().all(|c| always_returns(&c.body));
+        }
+        //* matches are exhaustive, so one arm always runs
+        Expr::Match(e) => return e.arms.iter().all(|arm| returns(&arm.body)),
+        _ => return false,
+    }
+}
+
+/// A method returning `Void` may run off its end.
+pub fn needs_return(return_type: Ty) -> bool {
+    return return_type != Ty::VOID && return_type != Ty::RawVoid;
+}
+
+/// The statements that make the rest of their body unreachable, in every nested body.
+pub fn unreachable(body: &[Expr]) -> Vec<Position> {
+    let mut unreachable = Unreachable { positions: vec![] };
+    unreachable.visit_body(body);
+    return unreachable.positions;
+}
+
+struct Unreachable {
+    positions: Vec<Position>,
+}
+
+impl Visitor for Unreachable {
+    fn visit_body(&mut self, body: &[Expr]) {
+        for (index, expr) in body.iter().enumerate() {
+            self.visit_expr(expr);
+            if index + 1 < body.len() && returns(expr) {
+                self.positions.extend(position(expr));
+                break;
+            }
+        }
+    }
+}
+
+fn position(expr: &Expr) -> Option<Position> {
+    match expr {
+        Expr::Return(e) => return Some(e.position),
+        Expr::Throw(e) => return Some(e.position),
+        Expr::Cond(e) => return Some(e.position),
+        Expr::While(e) => return Some(e.position),
+        Expr::For(e) => return Some(e.position),
+        Expr::Try(e) => return Some(e.position),
+        Expr::Match(e) => return Some(e.position),
+        Expr::Block(body) => return body.iter().find(|e| returns(e)).and_then(position),
+        _ => return None,
+    }
+}
+
+/// The locals and fields in scope, innermost last, with whether each is certainly assigned,
+/// and the first read of one that may not be.
+#[derive(Clone)]
+struct Assigned {
+    vars: Vec<(Identifier, bool)>,
+    error: Option<SemanticError>,
+}
+
+impl Assigned {
+    fn find(&self, name: &Identifier) -> Option<usize> {
+        return self.vars.iter().rposition(|(n, _)| n == name);
+    }
+
+    fn read(&mut self, name: &Identifier, position: Position) {
+        if let Some(index) = self.find(name) {
+            if !self.vars[index].1 && self.error.is_none() {
+                self.error = Some(
+                    SemanticError::new(
+                        E0223,
+                        format!("{} may be read before it is assigned!", name),
+                        Some(position),
+                    )
+                    .with_help(format!("give {} a value on every path before this read", name)),
+                );
+            }
+        }
+    }
+
+    fn assign(&mut self, name: &Identifier) {
+        if let Some(index) = self.find(name) {
+            self.vars[index].1 = true;
+        }
+    }
+
+    fn declare(&mut self, name: &Identifier, assigned: bool) {
+        self.vars.push((name.clone(), assigned));
+    }
+
+    /// Keeps the first error, whether it was found here or on `path`.
+    fn error_from(&mut self, path: &mut Assigned) {
+        if self.error.is_none() {
+            self.error = path.error.take();
+        }
+    }
+
+    /// What is assigned after one of several paths ran, the ones that return don't count.
+    fn join(&mut self, paths: Vec<Option<Assigned>>) {
+        let mut paths = paths.into_iter().flatten();
+        if let Some(mut first) = paths.next() {
+            for path in paths {
+                for (var, other) in first.vars.iter_mut().zip(path.vars.iter()) {
+                    var.1 = var.1 && other.1;
+                }
+            }
+            self.vars = first.vars;
+        }
+    }
+
+    /// Runs `body` on a copy, None when it always returns.
+    fn branch(&mut self, body: &[Expr]) -> Option<Assigned> {
+        let mut path = self.clone();
+        path.visit_body(body);
+        self.error_from(&mut path);
+        if always_returns(body) {
+            return None;
+        }
+        return Some(path);
+    }
+}
+
+impl Visitor for Assigned {
+    //* the rest of the method isn't looked at once a read is rejected
+    fn visit_expr(&mut self, expr: &Expr) {
+        if self.error.is_none() {
+            walk_expr(self, expr);
+        }
+    }
+
+    fn visit_body(&mut self, body: &[Expr]) {
+        let len = self.vars.len();
+        walk_body(self, body);
+        self.vars.truncate(len);
+    }
+
+    fn visit_identifier(&mut self, e: &IdentifierSrtuct) {
+        self.read(&e.name, e.pos);
+    }
+
+    fn visit_let(&mut self, e: &Let) {
+        for decl in e.var_decls.iter() {
+            match decl.init.deref() {
+                Some(init) => {
+                    self.visit_expr(init);
+                    self.declare(&decl.name, true);
+                }
+                //* a nullable local starts out null
+                None => {
+                    let nullable = decl.type_.as_ref().map_or(false, |t| is_nullable(t));
+                    self.declare(&decl.name, nullable);
+                }
+            }
+        }
+    }
+
+    fn visit_assignment(&mut self, e: &Assignment) {
+        walk_assignment(self, e);
+        self.assign(&e.id);
+    }
+
+    fn visit_dispatch(&mut self, e: &Dispatch) {
+        walk_dispatch(self, e);
+        if let DispatchExpr::Field(field) = &e.expr {
+            if e.target.is_self_expr() {
+                self.read(field, e.position);
+            }
+        }
+    }
+
+    fn visit_cond(&mut self, e: &Cond) {
+        self.visit_expr(&e.test);
+        let then_ = self.branch(&e.then_body);
+        let else_ = self.branch(&e.else_body);
+        self.join(vec![then_, else_]);
+    }
+
+    //* a loop body may not run at all
+    fn visit_while(&mut self, e: &While) {
+        self.visit_expr(&e.test);
+        self.branch(&e.body);
+    }
+
+    fn visit_for(&mut self, e: &For) {
+        let len = self.vars.len();
+        for init in e.init.iter() {
+            self.visit_expr(init);
+        }
+        for test in e.test.iter() {
+            self.visit_expr(test);
+        }
+        let mut body = self.clone();
+        body.visit_body(&e.body);
+        body.visit_body(&e.iter);
+        self.error_from(&mut body);
+        self.vars.truncate(len);
+    }
+
+    fn visit_match(&mut self, e: &Match) {
+        self.visit_expr(&e.expr);
+        let mut paths = vec![];
+        for arm in &e.arms {
+            let mut path = self.clone();
+            if let Pattern::Variant(p) = &arm.pattern {
+                for binding in p.bindings.iter().flatten() {
+                    path.declare(binding, true);
+                }
+            }
+            if let Some(guard) = &arm.guard {
+                path.visit_expr(guard);
+            }
+            let body = std::slice::from_ref(arm.body.deref());
+            paths.push(path.branch(body));
+            self.error_from(&mut path);
+        }
+        self.join(paths);
+    }
+
+    //* a catch may run after any part of the body, so it starts from before the try
+    fn visit_try(&mut self, e: &Try) {
+        let before = self.clone();
+        let mut paths = vec![self.branch(&e.body)];
+        for catch in &e.catches {
+            let mut path = before.clone();
+            path.declare(&catch.name, true);
+            paths.push(path.branch(&catch.body));
+            self.error_from(&mut path);
+        }
+        self.join(paths);
+        if let Some(finally) = &e.finally {
+            let mut path = before.clone();
+            path.visit_body(finally);
+            self.error_from(&mut path);
+            for (var, other) in self.vars.iter_mut().zip(path.vars.iter()) {
+                var.1 = var.1 || other.1;
+            }
+        }
+    }
+}
+
+/// Rejects reads of locals and of `fields` before they are certainly assigned, and gives the
+/// fields that `body` may leave unassigned.
+pub fn check_assigned(
+    body: &[Expr],
+    params: &[Identifier],
+    fields: &[Identifier],
+) -> Result<Vec<Identifier>, SemanticError> {
+    let mut assigned = Assigned {
+        vars: vec![],
+        error: None,
+    };
+    for field in fields {
+        assigned.declare(field, false);
+    }
+    for param in params {
+        assigned.declare(param, true);
+    }
+    walk_body(&mut assigned, body);
+    if let Some(error) = assigned.error {
+        return Err(error);
+    }
+    if always_returns(body) {
+        return Ok(vec![]);
+    }
+    return Ok(assigned.vars[..fields.len()]
+        .iter()
+        .filter(|(_, assigned)| !assigned)
+        .map(|(name, _)| name.clone())
+        .collect());
+}
@@ -0,0 +1,540 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    ops::Deref,
+};
+
+use crate::{
+    ctx::CompileContext,
+    diagnostic::{codes::*, Diagnostic, Severity},
+    lexer::Position,
+    parser::ast::{
+        class::{Class, ConstructorDecl, Feature, MethodDecl, Ownership},
+        expr::{
+            Catch, Cond, CondOp, Dispatch, DispatchExpr, Expr, For, IdentifierSrtuct, Isnull, Let,
+            MatchArm, Math, MathOp, Pattern, While,
+        },
+        visit::{
+            walk_body, walk_cond, walk_dispatch, walk_match_arm, walk_math, walk_while, Visitor,
+        },
+        Identifier,
+    },
+    ty::{Symbol, Ty},
+    utils::table::ClassTable,
+};
+
+use super::{flow::unreachable, semantic::SemanticError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Allow,
+    Warn,
+    /// Reported as an error, so the build stops.
+    Deny,
+}
+
+impl Level {
+    pub fn parse(level: &str) -> Option<Level> {
+        match level {
+            "allow" => return Some(Level::Allow),
+            "warn" => return Some(Level::Warn),
+            "deny" => return Some(Level::Deny),
+            _ => return None,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Allow => write!(f, "allow"),
+            Level::Warn => write!(f, "warn"),
+            Level::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// One kind of suspicious code the lint pass looks for.
+#[derive(Debug)]
+pub struct Lint {
+    pub name: &'static str,
+    pub code: &'static str,
+    pub default: Level,
+}
+
+pub const UNREACHABLE_CODE: Lint = Lint {
+    name: "unreachable_code",
+    code: W0001,
+    default: Level::Warn,
+};
+pub const UNUSED_VARIABLES: Lint = Lint {
+    name: "unused_variables",
+    code: W0002,
+    default: Level::Warn,
+};
+pub const UNUSED_PRIVATE: Lint = Lint {
+    name: "unused_private",
+    code: W0003,
+    default: Level::Warn,
+};
+pub const SHADOWED_FIELDS: Lint = Lint {
+    name: "shadowed_fields",
+    code: W0004,
+    default: Level::Warn,
+};
+pub const CONSTANT_CONDITIONS: Lint = Lint {
+    name: "constant_conditions",
+    code: W0005,
+    default: Level::Warn,
+};
+pub const ISNULL_ON_NEW: Lint = Lint {
+    name: "isnull_on_new",
+    code: W0006,
+    default: Level::Warn,
+};
+
+pub const LINTS: [&Lint; 6] = [
+    &UNREACHABLE_CODE,
+    &UNUSED_VARIABLES,
+    &UNUSED_PRIVATE,
+    &SHADOWED_FIELDS,
+    &CONSTANT_CONDITIONS,
+    &ISNULL_ON_NEW,
+];
+
+/// The group of every lint, `-D warnings` turns each warning into an error.
+pub const WARNINGS: &str = "warnings";
+
+/// The level of every lint, from the `[lints]` table of strawberry.toml and then `-A`, `-W`
+/// and `-D`.
+#[derive(Debug, Clone)]
+pub struct LintLevels {
+    levels: HashMap<&'static str, Level>,
+    /// What the lints that are left at warn become.
+    warnings: Level,
+}
+
+impl LintLevels {
+    pub fn new() -> Self {
+        Self {
+            levels: LINTS.iter().map(|lint| (lint.name, lint.default)).collect(),
+            warnings: Level::Warn,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, level: Level) -> Result<(), String> {
+        if name == WARNINGS {
+            self.warnings = level;
+            return Ok(());
+        }
+        match LINTS.iter().find(|lint| lint.name == name) {
+            Some(lint) => {
+                self.levels.insert(lint.name, level);
+                return Ok(());
+            }
+            None => {
+                let names: Vec<&str> = LINTS.iter().map(|lint| lint.name).collect();
+                return Err(format!(
+                    "There is no lint called {}, the lints are {} and {}",
+                    name,
+                    names.join(", "),
+                    WARNINGS
+                ));
+            }
+        }
+    }
+
+    /// Reads `name = "allow" | "warn" | "deny"` pairs, as in the `[lints]` table.
+    pub fn load(&mut self, table: &toml::Table) -> Result<(), String> {
+        for (name, value) in table {
+            let level = match value.as_str().and_then(Level::parse) {
+                Some(level) => level,
+                None => {
+                    return Err(format!(
+                        "The level of the lint {} must be \"allow\", \"warn\" or \"deny\"",
+                        name
+                    ))
+                }
+            };
+            self.set(name, level)?;
+        }
+        return Ok(());
+    }
+
+    pub fn level(&self, lint: &Lint) -> Level {
+        match self.levels[lint.name] {
+            Level::Warn => return self.warnings,
+            level => return level,
+        }
+    }
+}
+
+/// A local or parameter in scope and whether anything reads it.
+struct Local {
+    name: Identifier,
+    position: Position,
+    param: bool,
+    read: bool,
+}
+
+/// Walks the bodies of one class, and remembers which methods and fields are used anywhere.
+struct Linter<'a> {
+    file_name: &'a String,
+    class_table: &'a ClassTable,
+    class_name: Symbol,
+    /// The fields of the class and of its superclasses.
+    fields: Vec<Identifier>,
+    scopes: Vec<Vec<Local>>,
+    found: Vec<(&'static Lint, SemanticError)>,
+    /// The methods called and the fields read, each with the class declaring it.
+    called: HashSet<(Symbol, Identifier)>,
+    read_fields: HashSet<(Symbol, Identifier)>,
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, lint: &'static Lint, msg: String, position: Position) {
+        let e = SemanticError::new(lint.code, msg, Some(position)).in_file(self.file_name);
+        self.found.push((lint, e));
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(vec![]);
+    }
+
+    fn exit_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            for local in scope.into_iter().filter(|local| !local.read) {
+                let kind = if local.param { "parameter" } else { "local" };
+                self.report(
+                    &UNUSED_VARIABLES,
+                    format!("The {} {} is never read!", kind, local.name),
+                    local.position,
+                );
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Identifier, position: Position, param: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Local {
+                name: name.clone(),
+                position,
+                param,
+                read: false,
+            });
+        }
+    }
+
+    /// Marks the innermost local called `name` as read, false when `name` isn't a local.
+    fn read(&mut self, name: &Identifier) -> bool {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(local) = scope.iter_mut().rev().find(|local| &local.name == name) {
+                local.read = true;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// Marks the member `name` as used on a value of type `type_`, in every class of its
+    /// inheritance declaring a member of that name.
+    fn used(&mut self, type_: Option<Ty>, name: &Identifier, method: bool) {
+        let class_name = match type_.and_then(|t| t.class_name()) {
+            Some(class_name) => class_name,
+            None => return,
+        };
+        for class_ in self.class_table.inheritance(class_name).into_iter().flatten() {
+            let declares = class_.features.iter().any(|feature| match feature {
+                Feature::Method(m) => method && &m.name == name,
+                Feature::Attribute(attr) => !method && &attr.name == name,
+                Feature::Constructor(_) => false,
+            });
+            if declares {
+                let used = (Symbol::intern(&class_.name), name.clone());
+                if method {
+                    self.called.insert(used);
+                } else {
+                    self.read_fields.insert(used);
+                }
+            }
+        }
+    }
+
+    /// Checks the test of an if or a loop, `while (true)` is how a loop runs until it returns.
+    fn condition(&mut self, test: &Expr, is_loop: bool) {
+        if is_loop && matches!(test, Expr::Bool(true, _)) {
+            return;
+        }
+        if let (Some(value), Some(position)) = (constant(test), test.position()) {
+            self.report(
+                &CONSTANT_CONDITIONS,
+                format!("This condition is always {}!", value),
+                position,
+            );
+        }
+    }
+
+    /// The body of a method or constructor, with its parameters in scope.
+    fn method(&mut self, params: &[Identifier], position: Position, body: &[Expr]) {
+        for position in unreachable(body) {
+            self.report(
+                &UNREACHABLE_CODE,
+                format!("The code after this statement is unreachable!"),
+                position,
+            );
+        }
+        self.enter_scope();
+        for param in params {
+            self.declare(param, position, true);
+        }
+        self.visit_body(body);
+        self.exit_scope();
+    }
+}
+
+impl<'a> Visitor for Linter<'a> {
+    fn visit_method(&mut self, method: &MethodDecl) {
+        if let Some(body) = method.body.deref() {
+            let params: Vec<Identifier> = method.param.iter().map(|p| p.0.clone()).collect();
+            self.method(&params, method.position, body);
+        }
+    }
+
+    fn visit_constructor(&mut self, constructor: &ConstructorDecl) {
+        if let Some(body) = constructor.body.deref() {
+            let params: Vec<Identifier> = constructor.param.iter().map(|p| p.0.clone()).collect();
+            self.method(&params, constructor.position, body);
+        }
+    }
+
+    fn visit_body(&mut self, body: &[Expr]) {
+        self.enter_scope();
+        walk_body(self, body);
+        self.exit_scope();
+    }
+
+    fn visit_identifier(&mut self, e: &IdentifierSrtuct) {
+        if !self.read(&e.name) {
+            self.used(Some(Ty::Class(self.class_name)), &e.name, false);
+        }
+    }
+
+    fn visit_let(&mut self, e: &Let) {
+        for decl in e.var_decls.iter() {
+            if let Some(init) = decl.init.deref() {
+                self.visit_expr(init);
+            }
+            if self.fields.contains(&decl.name) {
+                self.report(
+                    &SHADOWED_FIELDS,
+                    format!("The local {} hides the field of the same name!", decl.name),
+                    decl.position,
+                );
+            }
+            self.declare(&decl.name, decl.position, false);
+        }
+    }
+
+    fn visit_dispatch(&mut self, e: &Dispatch) {
+        match &e.expr {
+            DispatchExpr::Method(call) => self.used(e.target_type, &call.fun_name, true),
+            DispatchExpr::Field(field) => self.used(e.target_type, field, false),
+        }
+        walk_dispatch(self, e);
+    }
+
+    fn visit_math(&mut self, e: &Math) {
+        //* the operands are walked below, the call only says which of them defines the operator
+        if let Some(Expr::Dispatch(call)) = e.overload.as_deref() {
+            if let DispatchExpr::Method(method) = &call.expr {
+                self.used(call.target_type, &method.fun_name, true);
+            }
+        }
+        walk_math(self, e);
+    }
+
+    fn visit_cond(&mut self, e: &Cond) {
+        self.condition(&e.test, false);
+        walk_cond(self, e);
+    }
+
+    fn visit_while(&mut self, e: &While) {
+        self.condition(&e.test, true);
+        walk_while(self, e);
+    }
+
+    fn visit_for(&mut self, e: &For) {
+        self.enter_scope();
+        for init in e.init.iter() {
+            self.visit_expr(init);
+        }
+        for test in e.test.iter() {
+            self.condition(test, true);
+            self.visit_expr(test);
+        }
+        self.visit_body(&e.body);
+        self.visit_body(&e.iter);
+        self.exit_scope();
+    }
+
+    fn visit_isnull(&mut self, e: &Isnull) {
+        if let Expr::New(new) = unboxed(&e.expr) {
+            self.report(
+                &ISNULL_ON_NEW,
+                format!("A new {} is never null, this isnull is always false!", new.class_name),
+                e.position,
+            );
+        }
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        self.enter_scope();
+        if let Pattern::Variant(p) = &arm.pattern {
+            for binding in p.bindings.iter().flatten() {
+                self.declare(binding, p.position, false);
+            }
+        }
+        walk_match_arm(self, arm);
+        self.exit_scope();
+    }
+
+    //* a catch has to name its exception, so an unread one isn't reported
+    fn visit_catch(&mut self, catch: &Catch) {
+        self.enter_scope();
+        self.declare(&catch.name, catch.position, false);
+        self.read(&catch.name);
+        walk_body(self, &catch.body);
+        self.exit_scope();
+    }
+}
+
+fn unboxed(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Boxing(e) => return unboxed(&e.expr),
+        Expr::Unboxing(e) => return unboxed(&e.expr),
+        _ => return expr,
+    }
+}
+
+/// The value of a test made only of literals.
+fn constant(expr: &Expr) -> Option<bool> {
+    match unboxed(expr) {
+        Expr::Bool(value, _) => return Some(*value),
+        Expr::Not(e) => return constant(&e.expr).map(|value| !value),
+        Expr::Math(e) if e.overload.is_none() => {
+            let op = match e.op.deref() {
+                MathOp::CondOp(op) => op,
+                MathOp::ComputeOp(_) => return None,
+            };
+            if let (Expr::Int(left, _), Expr::Int(right, _)) = (unboxed(&e.left), unboxed(&e.right)) {
+                match op {
+                    CondOp::Equal => return Some(left == right),
+                    CondOp::More => return Some(left > right),
+                    CondOp::MoreE => return Some(left >= right),
+                    CondOp::Less => return Some(left < right),
+                    CondOp::LessE => return Some(left <= right),
+                }
+            }
+            if let (CondOp::Equal, Some(left), Some(right)) = (op, constant(&e.left), constant(&e.right)) {
+                return Some(left == right);
+            }
+            return None;
+        }
+        _ => return None,
+    }
+}
+
+/// Lints the checked classes of the project, the classes of the std are left alone.
+pub fn lint(ctx: &CompileContext) -> Vec<Diagnostic> {
+    let classes: Vec<&Class> = ctx
+        .classes
+        .iter()
+        .filter(|class| !class.std)
+        .collect();
+    let mut found = vec![];
+    let mut called = HashSet::new();
+    let mut read_fields = HashSet::new();
+
+    for class in &classes {
+        let mut fields = vec![];
+        for parent in ctx.class_table.inheritance(Symbol::intern(&class.name)).into_iter().flatten() {
+            for feature in &parent.features {
+                if let Feature::Attribute(attr) = feature {
+                    fields.push(attr.name.clone());
+                }
+            }
+        }
+        let mut linter = Linter {
+            file_name: &class.file_name,
+            class_table: &ctx.class_table,
+            class_name: Symbol::intern(&class.name),
+            fields,
+            scopes: vec![],
+            found: vec![],
+            called,
+            read_fields,
+        };
+        linter.visit_class(class);
+        found.append(&mut linter.found);
+        called = linter.called;
+        read_fields = linter.read_fields;
+    }
+
+    //* a private member is only used when a checked use resolves to its class
+    for class in &classes {
+        let class_name = Symbol::intern(&class.name);
+        for feature in &class.features {
+            match feature {
+                Feature::Method(method)
+                    if method.ownership == Ownership::Private
+                        && !called.contains(&(class_name, method.name.clone()))
+                        && !(class.name == ctx.entry_class && method.name == ctx.entry_method) =>
+                {
+                    let e = SemanticError::new(
+                        UNUSED_PRIVATE.code,
+                        format!("The private method {} of {} is never called!", method.name, class.name),
+                        Some(method.position),
+                    );
+                    found.push((&UNUSED_PRIVATE, e.in_file(&class.file_name)));
+                }
+                //* the tag and slots of an enum are read by generated code
+                Feature::Attribute(attr)
+                    if attr.ownership == Ownership::Private
+                        && !attr.name.starts_with("__")
+                        && !read_fields.contains(&(class_name, attr.name.clone())) =>
+                {
+                    let e = SemanticError::new(
+                        UNUSED_PRIVATE.code,
+                        format!("The private field {} of {} is never read!", attr.name, class.name),
+                        Some(attr.position),
+                    );
+                    found.push((&UNUSED_PRIVATE, e.in_file(&class.file_name)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut diagnostics = vec![];
+    let mut explained = HashSet::new();
+    for (lint, e) in found {
+        let severity = match ctx.lints.level(lint) {
+            Level::Allow => continue,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        //* the first diagnostic of each lint says where its level comes from
+        let e = if explained.insert(lint.name) {
+            e.with_help(format!(
+                "the {} lint is set to {}, levels are set under [lints] in strawberry.toml",
+                lint.name,
+                ctx.lints.level(lint)
+            ))
+        } else {
+            e
+        };
+        diagnostics.push(e.diagnostic(severity));
+    }
+    return diagnostics;
+}
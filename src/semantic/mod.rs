@@ -1,47 +1,37 @@
-use std::{cell::RefCell, process::Command};
-
-use owo_colors::OwoColorize;
-
-use crate::{
-    cgen::cgen::CodeGenerator, complier::print_err_msg, ctx::CompileContext,
-    parser::ast::class::Class,
-};
+use crate::{ctx::CompileContext, diagnostic::Severity, ir::{self, Program}, parser::ast::class::Class};
 
 use self::semantic::{SemanticChecker, SemanticError};
 
+pub mod flow;
+pub mod lint;
+pub mod packages;
 pub mod semantic;
 pub mod type_checker;
 
-pub fn semantic_check(ctx_ref: RefCell<CompileContext>) -> Result<CompileContext, SemanticError> {
-    let mut semantic_checker: SemanticChecker = SemanticChecker::new(ctx_ref.into_inner());
-    let result: Result<Vec<Class>, SemanticError> = semantic_checker.check();
-    match result {
-        Ok(v) => {
-            println!(
-                "{}",
-                "🎺 Congratulations you passped the semantic check!".green()
-            );
-            return Ok(semantic_checker.ctx);
-            // print!("{:?}", v);
-            // let ctx = Context::create();
-            // let module = ctx.create_module("test");
-            // let builder = ctx.create_builder();
-
-            // let mut codegen: IrGenerator<'_> =
-            //     IrGenerator::new(v.clone(), &ctx, module, builder, &mut class_table, table);
-            // unsafe {
-            //     codegen.ir_generate();
-            // }
+/// Checks the program, then lints the project's own classes once they type check and lowers
+/// them to the IR the backends take.
+pub fn semantic_check(mut ctx: CompileContext) -> (CompileContext, Option<Program>) {
+    let errors = packages::resolve(&mut ctx);
+    if !errors.is_empty() {
+        for e in errors {
+            ctx.diagnostics.push(e.diagnostic(Severity::Error));
         }
-        Err(e) => {
-            println!("{}", "❌ Oops, semantic error has occurred!".red());
-            if let Some(pos) = e.position {
-                print_err_msg(pos, &e.file_name, &e.err_msg);
-            } else {
-                println!("{}{}", format!("--> ").blue(), e.file_name.blue());
-                println!("\t{}", e.err_msg.blue());
-            }
-            return Err(e);
+        return (ctx, None);
+    }
+    let mut semantic_checker: SemanticChecker = SemanticChecker::new(ctx);
+    let result: Result<Vec<Class>, Vec<SemanticError>> = semantic_checker.check();
+    let mut ctx = semantic_checker.ctx;
+    let mut program = None;
+    if result.is_ok() {
+        for d in lint::lint(&ctx) {
+            ctx.diagnostics.push(d);
+        }
+        program = Some(ir::lower::lower(&ctx));
+    }
+    if let Err(errors) = result {
+        for e in errors {
+            ctx.diagnostics.push(e.diagnostic(Severity::Error));
         }
     }
+    return (ctx, program);
 }
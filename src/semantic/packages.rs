@@ -0,0 +1,197 @@
+//! Packages. A file that starts with `package a.b;` puts its classes in the package `a.b`, and
+//! from here on they are known by their qualified names like `a.b.Shape`. A file without it is
+//! in the root package, with the std. In a file a short class name is, in this order, the class
+//! it imports, the class of its own package or the class of the root package.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ctx::CompileContext,
+    diagnostic::codes::{E0122, E0123},
+    parser::ast::{
+        class::{Class, ConstructorDecl, MethodDecl, VarDecl},
+        expr::{Cast, Catch, ConstructorCall, Import, Let, TypeTest, VariantCall},
+        is_primitive,
+        visit_mut::{
+            walk_attribute_mut, walk_catch_mut, walk_class_mut, walk_constructor_mut, walk_let_mut, walk_method_mut,
+            walk_new_mut, walk_variant_mut, VisitorMut,
+        },
+        Type,
+    },
+};
+
+use super::semantic::SemanticError;
+
+/// The package of a file and the classes it imports.
+#[derive(Debug, Clone)]
+pub struct FileScope {
+    pub file_name: String,
+    pub package: Option<String>,
+    pub imports: Vec<Import>,
+}
+
+/// Gives every class its qualified name and makes every class a type names qualified.
+pub fn resolve(ctx: &mut CompileContext) -> Vec<SemanticError> {
+    let mut errors = vec![];
+    for class in ctx.classes.iter_mut() {
+        let scope = ctx.scopes.iter().find(|s| s.file_name == class.file_name);
+        if let Some(package) = scope.and_then(|s| s.package.as_ref()) {
+            class.name = format!("{}.{}", package, class.name);
+        }
+    }
+    let known: HashSet<Type> = ctx.classes.iter().map(|c| c.name.clone()).collect();
+
+    let mut imports: HashMap<&String, HashMap<Type, Type>> = HashMap::new();
+    for scope in &ctx.scopes {
+        let mut imported: HashMap<Type, Type> = HashMap::new();
+        for import in &scope.imports {
+            let qualified = format!("{}.{}", import.package, import.class_name);
+            if !known.contains(&qualified) {
+                errors.push(
+                    SemanticError::new(
+                        E0122,
+                        format!("There is no class {} in the package {}!", import.class_name, import.package),
+                        Some(import.position),
+                    )
+                    .in_file(&scope.file_name),
+                );
+                continue;
+            }
+            match imported.get(&import.class_name) {
+                Some(other) if other != &qualified => {
+                    errors.push(
+                        SemanticError::new(
+                            E0123,
+                            format!("{} and {} are both imported as {}!", other, qualified, import.class_name),
+                            Some(import.position),
+                        )
+                        .in_file(&scope.file_name)
+                        .with_help(format!(
+                            "import one of them and name the other by its qualified name"
+                        )),
+                    );
+                }
+                _ => {
+                    imported.insert(import.class_name.clone(), qualified);
+                }
+            }
+        }
+        imports.insert(&scope.file_name, imported);
+    }
+
+    let no_imports = HashMap::new();
+    for class in ctx.classes.iter_mut() {
+        let package = ctx
+            .scopes
+            .iter()
+            .find(|s| s.file_name == class.file_name)
+            .and_then(|s| s.package.clone());
+        let mut resolver = Resolver {
+            known: &known,
+            imports: imports.get(&class.file_name).unwrap_or(&no_imports),
+            package,
+        };
+        resolver.visit_class_mut(class);
+    }
+    return errors;
+}
+
+/// Qualifies the class names of the types in one file.
+struct Resolver<'a> {
+    known: &'a HashSet<Type>,
+    imports: &'a HashMap<Type, Type>,
+    package: Option<String>,
+}
+
+impl<'a> Resolver<'a> {
+    /// `type_` with its class qualified, an unknown class is left for the type check to report.
+    fn resolve(&self, type_: &mut Type) {
+        let name = type_.trim_end_matches('?');
+        if name.contains('.') || is_primitive(name) {
+            return;
+        }
+        let qualified = match (self.imports.get(name), &self.package) {
+            (Some(qualified), _) => qualified.clone(),
+            (None, Some(package)) if self.known.contains(&format!("{}.{}", package, name)) => {
+                format!("{}.{}", package, name)
+            }
+            _ => return,
+        };
+        *type_ = format!("{}{}", qualified, &type_[name.len()..]);
+    }
+}
+
+impl<'a> VisitorMut for Resolver<'a> {
+    fn visit_class_mut(&mut self, class_: &mut Class) {
+        if let Some(parent) = &mut class_.parent {
+            self.resolve(parent);
+        }
+        for variant in class_.variants.iter_mut().flatten() {
+            for field in variant.fields.iter_mut() {
+                self.resolve(&mut field.1);
+            }
+            variant.resolve();
+        }
+        walk_class_mut(self, class_);
+    }
+
+    fn visit_attribute_mut(&mut self, attr: &mut VarDecl) {
+        if let Some(type_) = &mut attr.type_ {
+            self.resolve(type_);
+        }
+        attr.resolve();
+        walk_attribute_mut(self, attr);
+    }
+
+    fn visit_method_mut(&mut self, method: &mut MethodDecl) {
+        for param in method.param.iter_mut() {
+            self.resolve(&mut param.1);
+        }
+        self.resolve(&mut method.return_type);
+        method.resolve();
+        walk_method_mut(self, method);
+    }
+
+    fn visit_constructor_mut(&mut self, constructor: &mut ConstructorDecl) {
+        for param in constructor.param.iter_mut() {
+            self.resolve(&mut param.1);
+        }
+        constructor.resolve();
+        walk_constructor_mut(self, constructor);
+    }
+
+    fn visit_let_mut(&mut self, e: &mut Let) {
+        for decl in e.var_decls.iter_mut() {
+            if let Some(type_) = &mut decl.type_ {
+                self.resolve(type_);
+            }
+            decl.resolve();
+        }
+        walk_let_mut(self, e);
+    }
+
+    fn visit_new_mut(&mut self, e: &mut ConstructorCall) {
+        self.resolve(&mut e.class_name);
+        walk_new_mut(self, e);
+    }
+
+    fn visit_is_mut(&mut self, e: &mut TypeTest) {
+        self.resolve(&mut e.class_name);
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_as_mut(&mut self, e: &mut Cast) {
+        self.resolve(&mut e.class_name);
+        self.visit_expr_mut(&mut e.expr);
+    }
+
+    fn visit_variant_mut(&mut self, e: &mut VariantCall) {
+        self.resolve(&mut e.enum_name);
+        walk_variant_mut(self, e);
+    }
+
+    fn visit_catch_mut(&mut self, catch: &mut Catch) {
+        self.resolve(&mut catch.class_name);
+        walk_catch_mut(self, catch);
+    }
+}
@@ -1,39 +1,160 @@
-use std::{cell::RefCell, collections::HashSet, ops::DerefMut};
+use std::{collections::HashSet, ops::DerefMut};
 
 use crate::{
     ctx::CompileContext,
+    diagnostic::{codes::*, Diagnostic, Label, Severity, Stage},
     lexer::Position,
     // parser::ast::{Class, Feature, MethodDecl},
     parser::ast::{
         class::{Class, ConstructorDecl, Feature},
-        expr::Expr,
-        Identifier, Type,
+        expr::{Expr, IdentifierSrtuct},
+        visit::Visitor,
+        Identifier,
     },
     table::ClassTable,
-    utils::table::SymbolTable,
-    DEBUG,
+    ty::{sym, Symbol, Ty},
+    utils::{table::SymbolTable, util::closest},
     SELF,
 };
 
-use super::type_checker::TypeChecker;
+use super::{
+    flow::{always_returns, check_assigned, needs_return},
+    type_checker::{coerce, TypeChecker, RETURN_TYPE},
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SemanticError {
+    pub code: &'static str,
     pub err_msg: String,
     pub position: Option<Position>,
     pub file_name: String,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
 }
 
 impl SemanticError {
-    pub fn new(err_msg_: String, position_: Option<Position>) -> SemanticError {
+    pub fn new(code: &'static str, err_msg_: String, position_: Option<Position>) -> SemanticError {
         SemanticError {
+            code,
             err_msg: err_msg_,
             position: position_,
             file_name: "".to_string(),
+            labels: vec![],
+            help: None,
+        }
+    }
+
+    pub fn in_file(mut self, file_name: &String) -> SemanticError {
+        for label in self.labels.iter_mut().filter(|l| l.file_name.is_empty()) {
+            label.file_name = file_name.clone();
+        }
+        self.file_name = file_name.clone();
+        return self;
+    }
+
+    pub fn with_label(mut self, msg: String, file_name: &String, position: Position) -> SemanticError {
+        self.labels.push(Label {
+            msg,
+            file_name: file_name.clone(),
+            position,
+        });
+        return self;
+    }
+
+    /// A label in the file of the error, which `in_file` gives it later.
+    pub fn with_label_here(mut self, msg: String, position: Position) -> SemanticError {
+        self.labels.push(Label {
+            msg,
+            file_name: self.file_name.clone(),
+            position,
+        });
+        return self;
+    }
+
+    pub fn with_help(mut self, help: String) -> SemanticError {
+        self.help = Some(help);
+        return self;
+    }
+
+    /// Helps with the candidate `name` was probably meant to be, if one is close enough.
+    pub fn with_suggestion<'a>(
+        self,
+        name: &str,
+        candidates: impl IntoIterator<Item = &'a String>,
+    ) -> SemanticError {
+        match closest(name, candidates) {
+            Some(candidate) => return self.with_help(format!("did you mean {}?", candidate)),
+            None => return self,
+        }
+    }
+
+    pub fn diagnostic(self, severity: Severity) -> Diagnostic {
+        Diagnostic {
+            severity,
+            stage: Stage::Semantic,
+            code: self.code,
+            msg: self.err_msg,
+            file_name: self.file_name,
+            position: self.position,
+            labels: self.labels,
+            help: self.help,
         }
     }
 }
 
+/// Checks the statements of a body one by one, so one broken statement doesn't hide the
+/// errors in the others.
+fn check_body(
+    body: &mut Vec<Expr>,
+    symbol_table: &mut SymbolTable<Symbol, Ty>,
+    class_table: &ClassTable,
+    file_name: &String,
+    errors: &mut Vec<SemanticError>,
+) {
+    //* the locals whose type is unknown after an error, the statements that read them are
+    //* skipped since their errors would only repeat it
+    let mut unknown: Vec<Symbol> = vec![];
+    for expr in body.iter_mut() {
+        if !unknown.is_empty() && reads_any(expr, &unknown) {
+            if let Expr::Let(e) = expr {
+                unknown.extend(e.var_decls.iter().map(|decl| Symbol::intern(&decl.name)));
+            }
+            continue;
+        }
+        let depth = symbol_table.scopes.len();
+        if let Err(e) = expr.check_type(symbol_table, class_table) {
+            //* an error inside an expression without a position points at its statement
+            let position = e.position.or_else(|| expr.position());
+            errors.push(SemanticError { position, ..e }.in_file(file_name));
+            symbol_table.scopes.truncate(depth);
+            if let Expr::Let(e) = expr {
+                for decl in e.var_decls.iter() {
+                    match &decl.ty {
+                        Some(type_) => symbol_table.add(&Symbol::intern(&decl.name), type_),
+                        None => unknown.push(Symbol::intern(&decl.name)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether `expr` reads one of `names`.
+fn reads_any(expr: &Expr, names: &Vec<Symbol>) -> bool {
+    struct Reads<'a> {
+        names: &'a Vec<Symbol>,
+        found: bool,
+    }
+    impl<'a> Visitor for Reads<'a> {
+        fn visit_identifier(&mut self, e: &IdentifierSrtuct) {
+            self.found |= self.names.contains(&Symbol::intern(&e.name));
+        }
+    }
+    let mut reads = Reads { names, found: false };
+    reads.visit_expr(expr);
+    return reads.found;
+}
+
 /// * install constants and basic classes.
 /// * get all classes not just user defined but also include IO, Object and so on.
 /// * check Main
@@ -42,7 +163,7 @@ impl SemanticError {
 /// * check method override
 /// * check all expressions
 pub struct SemanticChecker {
-    symbol_table: SymbolTable<Identifier, Type>,
+    symbol_table: SymbolTable<Symbol, Ty>,
     pub ctx: CompileContext,
 }
 impl SemanticChecker {
@@ -52,100 +173,246 @@ impl SemanticChecker {
             ctx,
         }
     }
-    pub fn check(&mut self) -> Result<Vec<Class>, SemanticError> {
+    /// Every error found, the type checking only runs on a sound class table.
+    pub fn check(&mut self) -> Result<Vec<Class>, Vec<SemanticError>> {
+        let mut errors: Vec<SemanticError> = vec![];
         let mut main_flag = false;
         let mut main_method_flag = false;
+        let (entry_class, entry_method) = (&self.ctx.entry_class, &self.ctx.entry_method);
 
         //* check repeat class */
         for i in &self.ctx.classes {
-            if i.name == "Main".to_string() {
+            if &i.name == entry_class {
                 main_flag = true;
                 for feature in &i.features {
                     if let Feature::Method(m) = feature {
-                        if m.name == "main".to_string() {
+                        if &m.name == entry_method {
                             main_method_flag = true;
                         }
                     }
                 }
             }
-            if self.ctx.class_table.classes.contains_key(&i.name) {
-                return Err(SemanticError {
-                    err_msg: format!("Class {} has been redefined!", i.name),
-                    file_name: i.file_name.clone(),
-                    position: Some(i.position),
-                });
+            if let Some(first) = self.ctx.class_table.get(Symbol::intern(&i.name)) {
+                errors.push(
+                    SemanticError::new(
+                        E0102,
+                        format!("Class {} has been redefined!", i.name),
+                        Some(i.position),
+                    )
+                    .in_file(&i.file_name)
+                    .with_label(
+                        format!("{} is first defined here", i.name),
+                        &first.file_name,
+                        first.position,
+                    ),
+                );
             } else {
-                self.ctx
-                    .class_table
-                    .classes
-                    .insert(i.name.clone(), i.clone());
+                self.ctx.class_table.add(i.clone());
             }
         }
 
         //* chech main */
         if !main_flag {
-            return Err(SemanticError {
-                err_msg: format!("Your program is missing the Main class"),
-                file_name: "".to_string(),
-                position: None,
-            });
+            errors.push(
+                SemanticError::new(E0100, format!("Your program is missing the {} class", entry_class), None)
+                    .with_help(format!(
+                        "add class {} {{ fun {}() -> int {{ return 0; }}; }}; to src/main.st",
+                        entry_class, entry_method
+                    )),
+            );
         }
         if !main_method_flag {
-            return Err(SemanticError {
-                err_msg: format!("Your program is missing the Main function"),
-                file_name: "".to_string(),
-                position: None,
-            });
+            errors.push(
+                SemanticError::new(
+                    E0101,
+                    format!("Your program is missing the {}.{} function", entry_class, entry_method),
+                    None,
+                )
+                .with_help(format!(
+                    "add fun {}() -> int {{ return 0; }}; to the {} class",
+                    entry_method, entry_class
+                )),
+            );
         }
 
         //* check inheritance */
         for i in &self.ctx.classes {
-            let mut inherit_vec: Vec<Class> = Vec::new();
-            inherit_vec.insert(0, i.clone());
+            let class_id = self.ctx.class_table.id(Symbol::intern(&i.name)).unwrap();
+            let mut inherit_vec = vec![class_id];
+            //* a walk that reaches a cycle it doesn't start in must stop too
+            let mut visited = HashSet::from([class_id]);
             let mut curr_parent = i.parent.clone();
 
             while let Some(ref parent_name) = curr_parent {
                 if parent_name == "None" {
                     // current is object
                     break;
-                } else if parent_name == &i.name {
-                    return Err(SemanticError {
-                        err_msg: format!(
-                            "There is an inheritance cycle about Class {}!",
-                            parent_name
-                        ),
-                        file_name: i.file_name.clone(),
-                        position: Some(i.position),
-                    });
                 } else {
-                    let parent_class = self.ctx.class_table.classes.get(parent_name);
-                    match parent_class {
-                        Some(parent_class) => {
-                            inherit_vec.insert(0, parent_class.clone());
+                    let parent_id = self.ctx.class_table.id(Symbol::intern(parent_name));
+                    match parent_id.map(|id| (id, self.ctx.class_table.class(id))) {
+                        Some((parent_id, _)) if !visited.insert(parent_id) => {
+                            errors.push(SemanticError::new(
+                                E0105,
+                                format!(
+                                    "There is an inheritance cycle about Class {}!",
+                                    parent_name
+                                ),
+                                Some(i.position),
+                            ).in_file(&i.file_name));
+                            break;
+                        }
+                        Some((_, parent_class)) if parent_class.is_enum() => {
+                            errors.push(SemanticError::new(
+                                E0104,
+                                format!(
+                                    "Your Class {} cannot inherit the enum {} !",
+                                    i.name, parent_name
+                                ),
+                                Some(i.position),
+                            ).in_file(&i.file_name));
+                            break;
+                        }
+                        Some((parent_id, parent_class)) => {
+                            inherit_vec.insert(0, parent_id);
                             curr_parent = parent_class.parent.clone();
                         }
                         None => {
-                            return Err(SemanticError {
-                                err_msg: format!(
+                            errors.push(SemanticError::new(
+                                E0103,
+                                format!(
                                     "Your Class {} inherits an undefined Class {} !",
                                     i.name, parent_name
                                 ),
-                                file_name: i.file_name.clone(),
-                                position: Some(i.position),
-                            });
+                                Some(i.position),
+                            ).in_file(&i.file_name));
+                            break;
                         }
                     }
                 }
             }
 
-            if let Some(object_class) = self.ctx.class_table.classes.get(&"Object".to_string()) {
-                inherit_vec.insert(0, object_class.clone());
+            if let Some(object_id) = self.ctx.class_table.id(sym::OBJECT) {
+                inherit_vec.insert(0, object_id);
             }
 
-            self.ctx
-                .class_table
-                .inheritance
-                .insert(i.name.clone(), inherit_vec);
+            self.ctx.class_table.set_inheritance(class_id, inherit_vec);
+        }
+
+        //* check enum variants */
+        for i in &self.ctx.classes {
+            if let Some(variants) = &i.variants {
+                for (index, variant) in variants.iter().enumerate() {
+                    if let Some(first) = variants[..index].iter().find(|v| v.name == variant.name) {
+                        errors.push(
+                            SemanticError::new(
+                                E0111,
+                                format!(
+                                    "The variant {} of enum {} has been redefined!",
+                                    variant.name, i.name
+                                ),
+                                Some(variant.position),
+                            )
+                            .in_file(&i.file_name)
+                            .with_label(
+                                format!("{} is first defined here", variant.name),
+                                &i.file_name,
+                                first.position,
+                            ),
+                        );
+                    }
+                    for (field_index, field) in variant.fields.iter().enumerate() {
+                        if variant.fields[..field_index].iter().any(|f| f.0 == field.0) {
+                            errors.push(
+                                SemanticError::new(
+                                    E0112,
+                                    format!(
+                                        "The field <{}> of the variant {}::{} has been redefined!",
+                                        field.0, i.name, variant.name
+                                    ),
+                                    Some(variant.position),
+                                )
+                                .in_file(&i.file_name),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        //* check repeat methods, fields and parameters */
+        for i in &self.ctx.classes {
+            for (index, feature) in i.features.iter().enumerate() {
+                let earlier = &i.features[..index];
+                match feature {
+                    Feature::Method(method) => {
+                        let first = earlier.iter().find_map(|f| match f {
+                            Feature::Method(m) if m == method => Some(m),
+                            _ => None,
+                        });
+                        if let Some(first) = first {
+                            errors.push(
+                                SemanticError::new(
+                                    E0118,
+                                    format!(
+                                        "Class {} declares the method {} twice!",
+                                        i.name,
+                                        method.signature()
+                                    ),
+                                    Some(method.position),
+                                )
+                                .in_file(&i.file_name)
+                                .with_label(
+                                    format!("{} is first declared here", method.name),
+                                    &i.file_name,
+                                    first.position,
+                                ),
+                            );
+                        }
+                    }
+                    Feature::Attribute(attr) => {
+                        let first = earlier.iter().find_map(|f| match f {
+                            Feature::Attribute(a) if a == attr => Some(a),
+                            _ => None,
+                        });
+                        if let Some(first) = first {
+                            errors.push(
+                                SemanticError::new(
+                                    E0119,
+                                    format!("Class {} declares the field {} twice!", i.name, attr.name),
+                                    Some(attr.position),
+                                )
+                                .in_file(&i.file_name)
+                                .with_label(
+                                    format!("{} is first declared here", attr.name),
+                                    &i.file_name,
+                                    first.position,
+                                ),
+                            );
+                        }
+                    }
+                    Feature::Constructor(_) => {}
+                }
+                if let Feature::Method(_) | Feature::Constructor(_) = feature {
+                    let params = feature.get_param();
+                    for (param_index, param) in params.iter().enumerate() {
+                        if let Some(first) = params[..param_index].iter().find(|p| p.0 == param.0) {
+                            errors.push(
+                                SemanticError::new(
+                                    E0120,
+                                    format!("The parameter {} is declared twice!", param.0),
+                                    Some(param.2),
+                                )
+                                .in_file(&i.file_name)
+                                .with_label(
+                                    format!("{} is first declared here", first.0),
+                                    &i.file_name,
+                                    first.2,
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
         }
 
         //* check construtor */
@@ -155,81 +422,171 @@ impl SemanticChecker {
             for feature in &i.features {
                 match feature {
                     Feature::Constructor(constructor_decl) => {
-                        if !construtor_vec.contains(constructor_decl) {
-                            construtor_vec.push(constructor_decl.clone());
+                        if let Some(first) = construtor_vec.iter().find(|c| *c == constructor_decl) {
+                            errors.push(
+                                SemanticError::new(
+                                    E0110,
+                                    format!("The parameter declaration for this constructor method duplicates"),
+                                    Some(constructor_decl.position),
+                                )
+                                .in_file(&i.file_name)
+                                .with_label(
+                                    format!("the same parameters are first declared here"),
+                                    &i.file_name,
+                                    first.position,
+                                ),
+                            );
                         } else {
-                            return Err(SemanticError {
-                                 err_msg:format!("The parameter declaration for this constructor method duplicates"),
-                                  position: Some(constructor_decl.position),
-                                  file_name:i.file_name.clone(),
-                                 });
+                            construtor_vec.push(constructor_decl.clone());
                         }
                     }
                     _ => {}
                 }
             }
+            //* the program starts with `new Main`
+            if i.name == self.ctx.entry_class
+                && !construtor_vec.is_empty()
+                && construtor_vec.iter().all(|c| !c.param.is_empty())
+            {
+                errors.push(
+                    SemanticError::new(
+                        E0121,
+                        format!(
+                            "Class {} can't be created, it has no constructor without parameters!",
+                            i.name
+                        ),
+                        Some(construtor_vec[0].position),
+                    )
+                    .in_file(&i.file_name)
+                    .with_help(format!("add constructor() {{ }}; to the {} class", i.name)),
+                );
+            }
             self.ctx
                 .class_table
-                .class_constructors
-                .insert(i.name.clone(), construtor_vec);
+                .set_constructors(Symbol::intern(&i.name), construtor_vec);
         }
 
         //* check  method */
         for i in &self.ctx.classes {
             // Main:  Main -> Object -> A
 
-            if let Some(v) = self.ctx.class_table.inheritance.get(&(i.name.clone())) {
-                for curr_parent in v.iter().rev() {
+            if let Some(v) = self.ctx.class_table.inheritance(Symbol::intern(&i.name)) {
+                for curr_parent in v.rev() {
                     for feature in &curr_parent.features {
                         match feature {
-                            Feature::Method(method_) => {
-                                //*  just check name, find it if the class override curr_parent's method
-                                if i.features.contains(&feature) {
-                                    //* check returan_type and attr and ownership
-                                    let index =
-                                        i.features.iter().position(|r| r == feature).unwrap();
-
-                                    //* check param
-                                    if !i.features[index].check_param(&feature) {
-                                        return Err(SemanticError {
-                                            err_msg: format!(
-                                                "An error occurred in the parameter type of the method <{}> overridden by Class {}!",method_.name,i.name
-                                            ),
-                                            position: Some(i.features[index].get_position()),
-                                            file_name:  i.file_name.clone().clone(),
-                                        });
+                            Feature::Method(method_) if curr_parent.name != i.name => {
+                                //* an override may take supertypes and return a subtype
+                                let overriders = self.ctx.class_table.overriders(i, method_);
+                                if overriders.is_empty() {
+                                    continue;
+                                }
+                                let overrider = match self.ctx.class_table.overrider(i, method_) {
+                                    Some(overrider) => overrider,
+                                    None => {
+                                        let signatures: Vec<String> =
+                                            overriders.iter().map(|m| m.signature()).collect();
+                                        errors.push(
+                                            SemanticError::new(
+                                                E0107,
+                                                format!(
+                                                    "Class {} overrides {} of Class {} with {}, and none of them is more specific than the others!",
+                                                    i.name,
+                                                    method_.signature(),
+                                                    curr_parent.name,
+                                                    signatures.join(" and ")
+                                                ),
+                                                Some(overriders[0].position),
+                                            )
+                                            .in_file(&i.file_name)
+                                            .with_label(
+                                                format!("the overridden method is declared in {} here", curr_parent.name),
+                                                &curr_parent.file_name,
+                                                method_.position,
+                                            )
+                                            .with_help(format!(
+                                                "declare {} in {} to say which one overrides it",
+                                                method_.signature(),
+                                                i.name
+                                            )),
+                                        );
+                                        continue;
                                     }
-                                    //* check return type
-                                    if !i.features[index].check_return_type(&feature) {
-                                        return Err(SemanticError {
-                                            err_msg: format!(
-                                                "An error occurred in the return type of the method <{}> overridden by Class {}!",method_.name,i.name
+                                };
+                                //* check return type
+                                if !self
+                                    .ctx
+                                    .class_table
+                                    .is_less_or_equal_unboxed(overrider.return_ty, method_.return_ty)
+                                {
+                                    errors.push(
+                                        SemanticError::new(
+                                            E0108,
+                                            format!(
+                                                "{} in Class {} overrides {} of Class {}, but its return type {} doesn't fit {}!",
+                                                overrider.signature(),
+                                                i.name,
+                                                method_.signature(),
+                                                curr_parent.name,
+                                                overrider.return_type,
+                                                method_.return_type
                                             ),
-                                            position: Some(i.features[index].get_position()),
-                                            file_name:  i.file_name.clone(),
-                                        });
-                                    }
-                                    //* check ownership
-                                    if feature.get_ownership() != i.features[index].get_ownership()
-                                    {
-                                        return Err(SemanticError {
-                                            err_msg: format!(
-                                                "An error occurred in the ownership of the method <{}> overridden by Class {}!",method_.name,i.name
+                                            Some(overrider.position),
+                                        )
+                                        .in_file(&i.file_name)
+                                        .with_label(
+                                            format!("the overridden method is declared in {} here", curr_parent.name),
+                                            &curr_parent.file_name,
+                                            method_.position,
+                                        )
+                                        .with_help(format!(
+                                            "an override may return {} or a subclass of it",
+                                            method_.return_type
+                                        )),
+                                    );
+                                }
+                                //* check ownership
+                                if overrider.ownership != method_.ownership {
+                                    errors.push(
+                                        SemanticError::new(
+                                            E0109,
+                                            format!(
+                                                "{} in Class {} overrides {} of Class {}, but only one of them is public!",
+                                                overrider.signature(),
+                                                i.name,
+                                                method_.signature(),
+                                                curr_parent.name
                                             ),
-                                            position: Some(i.features[index].get_position()),
-                                            file_name:  i.file_name.clone(),
-                                        });
-                                    }
+                                            Some(overrider.position),
+                                        )
+                                        .in_file(&i.file_name)
+                                        .with_label(
+                                            format!("the overridden method is declared in {} here", curr_parent.name),
+                                            &curr_parent.file_name,
+                                            method_.position,
+                                        ),
+                                    );
                                 }
                             }
+                            Feature::Method(_) => {}
 
                             Feature::Attribute(attr) => {
                                 if &curr_parent.name != &i.name && i.features.contains(&feature) {
-                                    return Err(SemanticError { err_msg: format!("You cannot define the same field <{}> in the subclass {} as the superclass {}",
-                                        attr.name,i.name,curr_parent.name),
-                                        file_name:  i.file_name.clone(),
-                                        position: Some(i.position),
-                                    });
+                                    errors.push(
+                                        SemanticError::new(
+                                            E0106,
+                                            format!(
+                                                "You cannot define the same field <{}> in the subclass {} as the superclass {}",
+                                                attr.name, i.name, curr_parent.name
+                                            ),
+                                            Some(i.position),
+                                        )
+                                        .in_file(&i.file_name)
+                                        .with_label(
+                                            format!("the field of {} is declared here", curr_parent.name),
+                                            &curr_parent.file_name,
+                                            attr.position,
+                                        ),
+                                    );
                                 }
                             }
 
@@ -240,17 +597,44 @@ impl SemanticChecker {
             }
         }
 
+        //* the class table is too broken to type check against
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
         //* mut to add type to expression;
         for i in &mut self.ctx.classes {
+            //* fields left for the constructors need a constructor to assign them
+            let class_name = Symbol::intern(&i.name);
+            let fields = self.ctx.class_table.constructor_fields(class_name);
+            let has_constructor = i.features.iter().any(|f| matches!(f, Feature::Constructor(_)));
+            for (attr, file_name) in fields.iter().filter(|_| !has_constructor) {
+                let attr_type = attr.type_.as_ref().unwrap();
+                errors.push(
+                    SemanticError::new(
+                        E0114,
+                        format!(
+                            "The field {} has non-nullable type {} and must be initialized by its declaration or a constructor of {}, declare it as {}? if it may be null!",
+                            attr.name, attr_type, i.name, attr_type
+                        ),
+                        Some(attr.position),
+                    )
+                    .in_file(file_name),
+                );
+            }
+            let declared = fields.clone();
+            let fields: Vec<Identifier> =
+                fields.into_iter().map(|(attr, _)| attr.name.clone()).collect();
+
             self.symbol_table.enter_scope();
-            self.symbol_table.add(&SELF.to_string(), &i.name);
+            self.symbol_table.add(&Symbol::intern(SELF), &Ty::Class(class_name));
 
-            if let Some(v) = self.ctx.class_table.inheritance.get(&(i.name.clone())) {
-                for curr_parent in v.iter() {
+            if let Some(v) = self.ctx.class_table.inheritance(class_name) {
+                for curr_parent in v {
                     for feature in &curr_parent.features {
                         if let Feature::Attribute(attr) = feature {
                             self.symbol_table
-                                .add(&attr.name, &attr.type_.clone().unwrap())
+                                .add(&Symbol::intern(&attr.name), attr.ty.as_ref().unwrap())
                         }
                     }
                 }
@@ -260,93 +644,95 @@ impl SemanticChecker {
                 match j {
                     Feature::Method(method) => {
                         self.symbol_table.enter_scope();
-                        for param in &*method.param {
-                            self.symbol_table.add(&param.0, &param.1);
+                        for (param, type_) in method.param.iter().zip(method.param_tys.iter()) {
+                            self.symbol_table.add(&Symbol::intern(&param.0), type_);
                         }
+                        self.symbol_table.add(&Symbol::intern(RETURN_TYPE), &method.return_ty);
                         if let Some(v) = method.body.deref_mut() {
-                            let mut return_ = false;
-                            for expr in v {
-                                match expr {
-                                    Expr::Return(re) => {
-                                        return_ = true;
-                                        match re.check_type(
-                                            &mut self.symbol_table,
-                                            &mut self.ctx.class_table,
-                                        ) {
-                                            Err(e) => {
-                                                return Err(SemanticError {
-                                                    err_msg: e.err_msg,
-                                                    file_name: i.file_name.clone(),
-                                                    position: e.position,
-                                                });
-                                            }
-                                            Ok(type_) => {
-                                                if !self
-                                                    .ctx
-                                                    .class_table
-                                                    .is_less_or_equal(&type_, &method.return_type)
-                                                {
-                                                    return Err(SemanticError {
-                                                     err_msg: format!("The return type of your {} method is different from the declared type!",
-                                                               method.name),
-                                                    file_name:  i.file_name.clone(),
-                                                    position: Some(re.position),
-                                                    }
-                                                );
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        if let Err(e) = expr.check_type(
-                                            &mut self.symbol_table,
-                                            &mut self.ctx.class_table,
-                                        ) {
-                                            return Err(SemanticError {
-                                                err_msg: e.err_msg,
-                                                file_name: i.file_name.clone(),
-                                                position: e.position,
-                                            });
-                                        }
-                                    }
-                                }
+                            check_body(
+                                v,
+                                &mut self.symbol_table,
+                                &self.ctx.class_table,
+                                &i.file_name,
+                                &mut errors,
+                            );
+                            if needs_return(method.return_ty) && !always_returns(v) {
+                                errors.push(
+                                    SemanticError::new(
+                                        E0117,
+                                        format!(
+                                            "Not every path through the {} method returns a value!",
+                                            method.name
+                                        ),
+                                        Some(method.position),
+                                    )
+                                    .in_file(&i.file_name)
+                                    .with_help(format!(
+                                        "add a return or a throw at the end of {}",
+                                        method.name
+                                    )),
+                                );
                             }
-                            if !return_ {
-                                return Err(SemanticError {
-                                err_msg: format!(
-                                    "Your method needs a return expression, even though you may return in an if or while.",
-                                ),
-                                file_name:  i.file_name.clone(),
-                                position: Some(method.position),
-                            });
+                            let params: Vec<Identifier> =
+                                method.param.iter().map(|p| p.0.clone()).collect();
+                            if let Err(e) = check_assigned(v, &params, &[]) {
+                                errors.push(e.in_file(&i.file_name));
                             }
                         }
                         self.symbol_table.exit_scope();
                     }
                     Feature::Constructor(constructor) => {
                         self.symbol_table.enter_scope();
-                        for param in &*constructor.param {
-                            self.symbol_table.add(&param.0, &param.1);
+                        for (param, type_) in constructor.param.iter().zip(constructor.param_tys.iter()) {
+                            self.symbol_table.add(&Symbol::intern(&param.0), type_);
                         }
                         if let Some(v) = constructor.body.deref_mut() {
-                            for expr in v {
+                            for expr in v.iter() {
                                 if let Expr::Return(re) = expr {
-                                    return Err(SemanticError {
-                                        err_msg: format!(
-                                            "You cannot add a Return expression in constructor.",
-                                        ),
-                                        file_name: i.file_name.clone(),
-                                        position: Some(re.position),
-                                    });
+                                    errors.push(
+                                        SemanticError::new(
+                                            E0116,
+                                            format!("You cannot add a Return expression in constructor."),
+                                            Some(re.position),
+                                        )
+                                        .in_file(&i.file_name),
+                                    );
                                 }
-                                if let Err(e) = expr
-                                    .check_type(&mut self.symbol_table, &mut self.ctx.class_table)
-                                {
-                                    return Err(SemanticError {
-                                        err_msg: e.err_msg,
-                                        file_name: i.file_name.clone(),
-                                        position: e.position,
-                                    });
+                            }
+                            check_body(
+                                v,
+                                &mut self.symbol_table,
+                                &self.ctx.class_table,
+                                &i.file_name,
+                                &mut errors,
+                            );
+                            let params: Vec<Identifier> =
+                                constructor.param.iter().map(|p| p.0.clone()).collect();
+                            match check_assigned(v, &params, &fields) {
+                                Ok(unassigned) => {
+                                    for field in &unassigned {
+                                        let (attr, file_name) =
+                                            declared.iter().find(|(a, _)| &a.name == field).unwrap();
+                                        errors.push(
+                                            SemanticError::new(
+                                                E0115,
+                                                format!(
+                                                    "This constructor doesn't assign the field {} on every path!",
+                                                    field
+                                                ),
+                                                Some(constructor.position),
+                                            )
+                                            .in_file(&i.file_name)
+                                            .with_label(
+                                                format!("{} is declared here", field),
+                                                file_name,
+                                                attr.position,
+                                            ),
+                                        );
+                                    }
+                                }
+                                Err(e) => {
+                                    errors.push(e.in_file(&i.file_name));
                                 }
                             }
                         }
@@ -357,32 +743,30 @@ impl SemanticChecker {
                         self.symbol_table.enter_scope();
                         if let Some(init_expr) = attr.init.deref_mut() {
                             match init_expr
-                                .check_type(&mut self.symbol_table, &mut self.ctx.class_table)
+                                .check_type(&mut self.symbol_table, &self.ctx.class_table)
                             {
                                 Ok(init_type) => {
-                                    if let Some(attr_type) = &attr.type_ {
-                                        if !self
-                                            .ctx
-                                            .class_table
-                                            .is_less_or_equal(attr_type, &init_type)
+                                    if let Some(attr_type) = attr.ty {
+                                        if coerce(
+                                            init_expr,
+                                            init_type,
+                                            attr_type,
+                                            &self.ctx.class_table,
+                                        )
+                                        .is_none()
                                         {
-                                            return Err(SemanticError {
-                                                err_msg: format!(
-                                                "Some semantic errors occurred in your Assignment!"
-                                            ),
-                                                position: Some(attr.position),
-                                                file_name: i.file_name.clone(),
-                                            });
+                                            errors.push(
+                                                SemanticError::new(
+                                                    E0211,
+                                                    format!("Some semantic errors occurred in your Assignment!"),
+                                                    Some(attr.position),
+                                                )
+                                                .in_file(&i.file_name),
+                                            );
                                         }
                                     }
                                 }
-                                Err(e) => {
-                                    return Err(SemanticError {
-                                        err_msg: e.err_msg,
-                                        file_name: i.file_name.clone(),
-                                        position: e.position,
-                                    })
-                                }
+                                Err(e) => errors.push(e.in_file(&i.file_name)),
                             }
                         }
                         self.symbol_table.exit_scope();
@@ -392,6 +776,9 @@ impl SemanticChecker {
             self.symbol_table.exit_scope();
         }
 
+        if !errors.is_empty() {
+            return Err(errors);
+        }
         return Ok(self.ctx.classes.clone());
     }
 }
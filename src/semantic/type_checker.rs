@@ -1,72 +1,80 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::{
+    diagnostic::codes::*,
+    lexer::Position,
     parser::ast::{
-        class::{Feature, Ownership},
+        class::{Feature, MethodCall, MethodDecl, Ownership, VarDecl},
         expr::{
-            Assignment, ComputeOp, Cond, ConstructorCall, Dispatch, DispatchExpr, Expr, For,
-            Isnull, Let, Math, MathOp, Not, Return, While,
+            Assignment, Boxing, Cast, Coalesce, ComputeOp, CondOp, Cond, ConstructorCall,
+            Dispatch, DispatchExpr, Expr, For, Isnull, Let, Match, Math, MathOp, Not, Pattern,
+            Return, Throw, Try, TypeTest, Unboxing, VariantCall, While,
         },
-        is_primitive, Identifier, Type,
+        visit::{walk_assignment, Visitor},
+        Identifier, Type,
     },
-    utils::{
-        table::{ClassTable, SymbolTable},
-        util::do_vecs_match,
-    },
-    BOOL, INT, OBJECT, SELF,STRING, VOID,
+    ty::{Symbol, Ty},
+    utils::table::{ClassTable, SymbolTable},
+    SELF,
 };
 
-use super::semantic::SemanticError;
+use super::{flow::needs_return, semantic::SemanticError};
 
 pub trait TypeChecker {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
-        unreachable!()
-    }
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError>;
 }
 
 impl TypeChecker for Expr {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         match self {
-            Expr::Bool(_) => return Ok(BOOL.to_string()),
-            Expr::Str(_) => return Ok(STRING.to_string()),
-            Expr::Int(_) => return Ok(INT.to_string()),
+            Expr::Bool(..) => return Ok(Ty::BOOL),
+            Expr::Str(..) => return Ok(Ty::STRING),
+            Expr::Int(..) => return Ok(Ty::Int),
+            Expr::Null(_) => return Ok(Ty::Null),
             Expr::New(constructor_call) => {
                 return constructor_call.check_type(symbol_table, class_table)
             }
 
             Expr::ASM(_) => {
-                return Ok(OBJECT.to_string());
+                return Ok(Ty::OBJECT);
             }
 
             Expr::Identifier(e) => {
-                if let Some(s) = symbol_table.find(&e.name) {
-                    e.type_ = s.clone();
-                    return Ok(s.clone());
+                if let Some(s) = symbol_table.find(&Symbol::intern(&e.name)) {
+                    e.type_ = s.non_null().to_string();
+                    return Ok(*s);
                 } else {
+                    let names: Vec<String> = symbol_table
+                        .names()
+                        .map(|name| name.to_string())
+                        .filter(|name| name != SELF && name != RETURN_TYPE)
+                        .collect();
                     return Err(SemanticError::new(
+                        E0200,
                         format!(
                             "The identifier {} does not exist or it has gone out of scope!",
                             e.name
                         ),
                         Some(e.pos),
-                    ));
+                    )
+                    .with_suggestion(&e.name, names.iter()));
                 }
             }
 
             Expr::Self_(e) => {
-                if let Some(s) = symbol_table.find(&SELF.to_string()) {
-                    e.type_ = s.clone();
-                    return Ok(s.clone());
+                if let Some(s) = symbol_table.find(&Symbol::intern(SELF)) {
+                    e.type_ = s.to_string();
+                    return Ok(*s);
                 }
-                return Ok(OBJECT.to_string());
+                return Ok(Ty::OBJECT);
             }
 
             Expr::Let(e) => return e.check_type(symbol_table, class_table),
@@ -89,185 +97,500 @@ impl TypeChecker for Expr {
 
             Expr::For(e) => return e.check_type(symbol_table, class_table),
 
-            _ => {}
+            Expr::Variant(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::Match(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::Is(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::As(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::Coalesce(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::Throw(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::Try(e) => return e.check_type(symbol_table, class_table),
+
+            Expr::Boxing(e) => {
+                let raw = e.expr.check_type(symbol_table, class_table)?;
+                return Ok(raw.box_class().unwrap_or(raw));
+            }
+
+            Expr::Unboxing(e) => {
+                let boxed = e.expr.check_type(symbol_table, class_table)?;
+                return Ok(boxed.unbox_type().unwrap_or(boxed));
+            }
+
+            Expr::Block(body) => {
+                symbol_table.enter_scope();
+                for expr in body.deref_mut() {
+                    expr.check_type(symbol_table, class_table)?;
+                }
+                symbol_table.exit_scope();
+                return Ok(Ty::VOID);
+            }
+        }
+    }
+}
+
+//* the method being checked is declared under a name no variable can take
+pub const RETURN_TYPE: &str = "return";
+
+fn box_expr(expr: &mut Expr, class_name: Ty) {
+    let raw = std::mem::replace(expr, Expr::Null(Position::new(0, 0)));
+    *expr = Expr::Boxing(Boxing {
+        expr: Box::new(raw),
+        class_name: class_name.to_string(),
+    });
+}
+
+fn unbox_expr(expr: &mut Expr, type_: Ty) {
+    let boxed = std::mem::replace(expr, Expr::Null(Position::new(0, 0)));
+    *expr = Expr::Unboxing(Unboxing {
+        expr: Box::new(boxed),
+        type_: type_.to_string(),
+    });
+}
+
+/// The type `expr` has once it is used as `to`, boxing a raw value where an object is
+/// expected and unboxing an `Integer` or `Bool` where its raw type is.
+/// None when `from` doesn't fit `to` at all.
+pub fn coerce(expr: &mut Expr, from: Ty, to: Ty, class_table: &ClassTable) -> Option<Ty> {
+    if from == to {
+        return Some(from);
+    }
+    if let Some(class_name) = from.box_class() {
+        if class_table.is_less_or_equal(from, to) {
+            box_expr(expr, class_name);
+            return Some(class_name);
         }
-        return Ok(OBJECT.to_string());
+        return None;
+    }
+    if from.unbox_type() == Some(to) {
+        unbox_expr(expr, to);
+        return Some(to);
     }
+    if class_table.is_less_or_equal(from, to) {
+        return Some(from);
+    }
+    return None;
+}
+
+/// Conditions are `Bool`, a raw `bool` is boxed.
+fn check_test(
+    test: &mut Expr,
+    symbol_table: &mut SymbolTable<Symbol, Ty>,
+    class_table: &ClassTable,
+) -> Result<bool, SemanticError> {
+    let type_ = test.check_type(symbol_table, class_table)?;
+    return Ok(coerce(test, type_, Ty::BOOL, class_table).is_some());
 }
 
 impl TypeChecker for ConstructorCall {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
-        if let Some(_) = class_table.classes.get(&self.class_name) {
-            // find the class
-            match &mut self.param {
-                // check params
-                Some(params) => {
-                    let mut type_vec: Vec<Type> = vec![];
-                    for i in params.deref_mut() {
-                        match i.check_type(symbol_table, class_table) {
-                            Ok(type_) => {
-                                type_vec.push(type_.to_string());
-                            }
-                            Err(e) => return Err(e),
-                        }
-                    }
-                    let constructor_decls = class_table
-                        .class_constructors
-                        .get(&self.class_name)
-                        .unwrap();
-                    for decl in constructor_decls {
-                        //  is there a constructor match?
-                        let decl_type = decl.param.deref();
-                        let iter = decl_type.iter().map(|x| x.1.clone());
-                        if do_vecs_match(&type_vec, &(iter.collect())) {
-                            return Ok(self.class_name.clone());
-                        }
-                    }
-                    return Err(SemanticError::new(
-                        format!(
-                            "class {} has no constructor that takes ({:?}) as parameters!",
-                            &self.class_name, type_vec
-                        ),
-                        Some(self.position.clone()),
-                    ));
-                }
-                None => return Ok(self.class_name.clone()),
-            }
-        } else {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let class_name = Symbol::intern(&self.class_name);
+        if !class_table.contains(class_name) {
             return Err(SemanticError::new(
+                E0201,
                 format!("There is no class called {}!", &self.class_name),
-                None,
-            ));
+                Some(self.position),
+            )
+            .with_suggestion(&self.class_name, class_table.names()));
+        }
+
+        let mut actual_types: Vec<Ty> = vec![];
+        for actual in self.param.iter_mut().flat_map(|p| p.iter_mut()) {
+            actual_types.push(actual.check_type(symbol_table, class_table)?);
+        }
+        let constructors = class_table.constructors(class_name);
+
+        //* a class without constructors is only made by its field initializers,
+        //* which may leave fields for a constructor to assign
+        if constructors.is_empty() && actual_types.is_empty() {
+            if let Some((attr, _)) = class_table.constructor_fields(class_name).first() {
+                return Err(SemanticError::new(
+                    E0210,
+                    format!(
+                        "Class {} must be created with a constructor, which assigns its field {}!",
+                        &self.class_name, attr.name
+                    ),
+                    Some(self.position.clone()),
+                ));
+            }
+            self.param_types = None;
+            return Ok(Ty::Class(class_name));
+        }
+
+        //* `new C` and `new C()` both run the constructor without parameters
+        let param_types: Vec<Vec<Ty>> = constructors.iter().map(|c| c.param_tys.clone()).collect();
+        match most_specific(&param_types, &actual_types, class_table) {
+            Ok(best) => {
+                for ((actual, actual_type), param_type) in self
+                    .param
+                    .iter_mut()
+                    .flat_map(|p| p.iter_mut())
+                    .zip(actual_types.iter())
+                    .zip(param_types[best].iter())
+                {
+                    coerce(actual, *actual_type, *param_type, class_table);
+                }
+                self.param_types = Some(constructors[best].param_types());
+                return Ok(Ty::Class(class_name));
+            }
+            Err(applicable) if applicable.is_empty() => {
+                let signatures: Vec<String> = constructors.iter().map(|c| c.signature()).collect();
+                let help = if signatures.is_empty() {
+                    format!("{} declares no constructor, create it with new {}", self.class_name, self.class_name)
+                } else {
+                    format!("the constructors of {} are {}", self.class_name, signatures.join(", "))
+                };
+                return Err(SemanticError::new(
+                    E0209,
+                    format!(
+                        "Class {} has no constructor that takes ({})!",
+                        &self.class_name,
+                        type_list(&actual_types)
+                    ),
+                    Some(self.position.clone()),
+                )
+                .with_help(help));
+            }
+            Err(applicable) => {
+                let signatures: Vec<String> =
+                    applicable.iter().map(|i| constructors[*i].signature()).collect();
+                return Err(SemanticError::new(
+                    E0208,
+                    format!(
+                        "The call to the constructor of {} is ambiguous between {}!",
+                        &self.class_name,
+                        signatures.join(" and ")
+                    ),
+                    Some(self.position.clone()),
+                ));
+            }
         }
     }
 }
 
+/// Types as a message lists them, like `int, Shape`.
+fn type_list(types: &[Ty]) -> String {
+    let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+    return types.join(", ");
+}
+
 impl TypeChecker for Dispatch {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
-        let target_type = self.target.check_type(symbol_table, class_table)?;
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let mut target_type = self.target.check_type(symbol_table, class_table)?;
+
+        //* `a.to_string()` on an int calls it on the boxed Integer
+        if let Some(class_name) = target_type.box_class() {
+            box_expr(self.target.deref_mut(), class_name);
+            target_type = class_name;
+        }
 
-        if is_primitive(&target_type) {
+        self.target_type = Some(target_type);
+
+        if target_type.is_primitive() {
             return Err(SemanticError::new(
+                E0214,
                 "Raw type can't use <.> operator!".to_owned(),
                 Some(self.position.clone()),
             ));
         }
 
-        if let Some(class_) = class_table.get_classes().get(&target_type) {
-            if let Some(v) = class_table.get_inheritance().get(&class_.name) {
-                for curr_parent in v {
-                    match &mut self.expr {
-                        DispatchExpr::Method(method_call) => {
-                            for f in &curr_parent.features {
-                                if let Feature::Method(method) = f {
-                                    if method.name == method_call.fun_name {
-                                        let flag = match method.ownership {
-                                            Ownership::Private if !self.target.is_self_expr() => {
-                                                false
-                                            }
-                                            _ => true,
-                                        };
-
-                                        if flag {
-                                            let method_param = method.param.deref();
-                                            let actuals = method_call.actual.deref_mut();
-                                            if actuals.len() != method_param.len() {
-                                                return Err(SemanticError::new(
-                                                    "The actual number of parameters is not equal to the number of declared formal parameters!".to_string(),
-                                                    Some(self.position.clone()),
-                                                ));
-                                            }
-
-                                            for (actual, param) in
-                                                actuals.iter_mut().zip(method_param.iter())
-                                            {
-                                                let actual_type =
-                                                    actual.check_type(symbol_table, class_table)?;
-                                                if !class_table
-                                                    .is_less_or_equal(&actual_type, &param.1)
-                                                {
-                                                    return Err(SemanticError::new(
-                                                        "The actual parameter type is not the same as the declared formal parameter type!".to_owned(),
-                                                        Some(self.position.clone()),
-                                                    ));
-                                                }
-                                            }
-
-                                            self.type_ = method.return_type.clone();
-                                            return Ok(method.return_type.clone());
-                                        }
-                                    }
-                                }
-                            }
+        if target_type.is_nullable() && !self.safe {
+            return Err(SemanticError::new(
+                E0215,
+                format!(
+                    "The target has nullable type {}, use <?.> or check it with isnull first!",
+                    target_type
+                ),
+                Some(self.position.clone()),
+            ));
+        }
+
+        let class_name = match target_type.class_name() {
+            Some(class_name) => class_name,
+            None => return Ok(Ty::OBJECT),
+        };
+        if let Some(v) = class_table.inheritance(class_name) {
+            for curr_parent in v.clone() {
+                match &mut self.expr {
+                    DispatchExpr::Method(method_call) => {
+                        let candidates = method_candidates(
+                            class_table,
+                            class_name,
+                            &method_call.fun_name,
+                            self.target.is_self_expr(),
+                        );
+                        if candidates.is_empty() {
+                            break;
+                        }
+
+                        let mut actual_types = vec![];
+                        for actual in method_call.actual.deref_mut() {
+                            actual_types.push(actual.check_type(symbol_table, class_table)?);
+                        }
+                        let method =
+                            resolve_overload(&candidates, &actual_types, class_table, self.position)?;
+                        method_call.param_types = method.param_types();
+                        for ((actual, actual_type), param_type) in method_call
+                            .actual
+                            .iter_mut()
+                            .zip(actual_types.iter())
+                            .zip(method.param_tys.iter())
+                        {
+                            coerce(actual, *actual_type, *param_type, class_table);
                         }
-                        DispatchExpr::Field(field) => {
-                            for f in &curr_parent.features {
-                                if let Feature::Attribute(attr) = f {
-                                    if attr.name == *field {
-                                        let flag = match attr.ownership {
-                                            Ownership::Public => true,
-                                            Ownership::Private if self.target.is_self_expr() => {
-                                                true
-                                            }
-                                            _ => false,
-                                        };
-
-                                        if flag {
-                                            self.type_ = attr.type_.clone().unwrap();
-                                            return Ok(self.type_.clone());
-                                        } else {
-                                            return Err(SemanticError::new(
-                                                format!("The field {} is private!", field),
-                                                Some(self.position.clone()),
-                                            ));
-                                        }
+                        return self.result_type(return_type_on(class_table, class_name, method));
+                    }
+                    DispatchExpr::Field(field) => {
+                        for f in &curr_parent.features {
+                            if let Feature::Attribute(attr) = f {
+                                if attr.name == *field {
+                                    let flag = match attr.ownership {
+                                        Ownership::Public => true,
+                                        Ownership::Private if self.target.is_self_expr() => true,
+                                        _ => false,
+                                    };
+
+                                    if flag {
+                                        return self.result_type(attr.ty.unwrap());
+                                    } else {
+                                        return Err(SemanticError::new(
+                                            E0204,
+                                            format!("The field {} is private!", field),
+                                            Some(self.position.clone()),
+                                        ));
                                     }
                                 }
                             }
                         }
                     }
                 }
+            }
+            //* only members of the same kind are suggested
+            let (name, members) = match &self.expr {
+                DispatchExpr::Method(method_call) => (&method_call.fun_name, true),
+                DispatchExpr::Field(field) => (field, false),
+            };
+            let names = v
+                .flat_map(|parent| parent.features.iter())
+                .filter_map(|f| match f {
+                    Feature::Method(method) if members => Some(&method.name),
+                    Feature::Attribute(attr) if !members => Some(&attr.name),
+                    _ => None,
+                });
+            return Err(SemanticError::new(
+                E0203,
+                format!(
+                    "Class {} may not have the method or field you want!",
+                    class_name
+                ),
+                Some(self.position.clone()),
+            )
+            .with_suggestion(name, names));
+        }
+
+        Ok(Ty::OBJECT)
+    }
+}
+
+/// Every overload of `name` visible on `class_name`, the first one of each signature. The
+/// private ones are only visible on `self`.
+fn method_candidates<'a>(
+    class_table: &'a ClassTable,
+    class_name: Symbol,
+    name: &str,
+    with_private: bool,
+) -> Vec<&'a MethodDecl> {
+    let mut candidates: Vec<&MethodDecl> = vec![];
+    for parent in class_table.inheritance(class_name).into_iter().flatten() {
+        for f in &parent.features {
+            if let Feature::Method(method) = f {
+                let visible = with_private || method.ownership != Ownership::Private;
+                if visible && method.name == name && !candidates.contains(&method) {
+                    candidates.push(method);
+                }
+            }
+        }
+    }
+    return candidates;
+}
+
+/// What `method` returns when called on `class_name`, the override closest to the class may
+/// return a subclass.
+fn return_type_on(class_table: &ClassTable, class_name: Symbol, method: &MethodDecl) -> Ty {
+    return class_table
+        .inheritance(class_name)
+        .and_then(|v| v.rev().find_map(|c| class_table.overrider(c, method)))
+        .map_or(method.return_ty, |m| m.return_ty);
+}
+
+/// The overload whose parameters fit the actual types and are the most specific. Err holds
+/// every overload that fits when none is more specific than the others, and is empty when
+/// none fits.
+fn most_specific(
+    candidates: &Vec<Vec<Ty>>,
+    actual_types: &Vec<Ty>,
+    class_table: &ClassTable,
+) -> Result<usize, Vec<usize>> {
+    let fits = |args: &Vec<Ty>, params: &Vec<Ty>| {
+        args.len() == params.len()
+            && args
+                .iter()
+                .zip(params.iter())
+                .all(|(arg, param)| class_table.is_less_or_equal(*arg, *param))
+    };
+    let applicable: Vec<usize> = (0..candidates.len())
+        .filter(|i| fits(actual_types, &candidates[*i]))
+        .collect();
+
+    //* the best one fits the parameters of every other applicable overload
+    let best: Vec<&usize> = applicable
+        .iter()
+        .filter(|i| {
+            applicable
+                .iter()
+                .all(|other| fits(&candidates[**i], &candidates[*other]))
+        })
+        .collect();
+    if best.len() == 1 {
+        return Ok(*best[0]);
+    }
+    return Err(applicable);
+}
+
+/// Picks the overload whose parameters fit the actual types and are the most specific.
+fn resolve_overload<'a>(
+    candidates: &Vec<&'a MethodDecl>,
+    actual_types: &Vec<Ty>,
+    class_table: &ClassTable,
+    position: Position,
+) -> Result<&'a MethodDecl, SemanticError> {
+    let param_types: Vec<Vec<Ty>> = candidates.iter().map(|m| m.param_tys.clone()).collect();
+    let applicable = match most_specific(&param_types, actual_types, class_table) {
+        Ok(best) => return Ok(candidates[best]),
+        Err(applicable) => applicable,
+    };
+
+    if applicable.is_empty() {
+        if candidates.len() == 1 {
+            if candidates[0].param.len() != actual_types.len() {
                 return Err(SemanticError::new(
-                    format!(
-                        "Class {} may not have the method or field you want!",
-                        class_.name
-                    ),
-                    Some(self.position.clone()),
+                    E0205,
+                    "The actual number of parameters is not equal to the number of declared formal parameters!".to_string(),
+                    Some(position),
                 ));
             }
+            return Err(SemanticError::new(
+                E0206,
+                "The actual parameter type is not the same as the declared formal parameter type!"
+                    .to_owned(),
+                Some(position),
+            ));
+        }
+        return Err(SemanticError::new(
+            E0207,
+            format!(
+                "No overload of method {} takes ({})!",
+                candidates[0].name,
+                type_list(actual_types)
+            ),
+            Some(position),
+        ));
+    }
+
+    let signatures: Vec<String> = applicable
+        .iter()
+        .map(|i| format!("{}({})", candidates[*i].name, type_list(&param_types[*i])))
+        .collect();
+    return Err(SemanticError::new(
+        E0208,
+        format!(
+            "The call to {} is ambiguous between {}!",
+            candidates[0].name,
+            signatures.join(" and ")
+        ),
+        Some(position),
+    ));
+}
+
+impl Dispatch {
+    /// A safe dispatch gives null when its target is null, so its result is nullable.
+    fn result_type(&mut self, type_: Ty) -> Result<Ty, SemanticError> {
+        self.type_ = type_.non_null().to_string();
+        if !self.safe || type_ == Ty::VOID {
+            return Ok(type_);
+        }
+        if type_.is_primitive() {
+            return Err(SemanticError::new(
+                E0214,
+                format!(
+                    "<?.> can't give raw type {}, because the result may be null!",
+                    type_
+                ),
+                Some(self.position.clone()),
+            ));
         }
+        return Ok(type_.nullable());
+    }
+}
 
-        Ok(OBJECT.to_string())
+/// A scope may declare a name only once.
+fn declare_local(
+    symbol_table: &mut SymbolTable<Symbol, Ty>,
+    decl: &VarDecl,
+    type_: Ty,
+) -> Result<(), SemanticError> {
+    if let Some(first) = symbol_table.declare(&Symbol::intern(&decl.name), &type_, decl.position) {
+        return Err(SemanticError::new(
+            E0225,
+            format!("The local {} is declared twice in the same scope!", decl.name),
+            Some(decl.position),
+        )
+        .with_label_here(format!("{} is first declared here", decl.name), first));
     }
+    return Ok(());
 }
 
 impl TypeChecker for Let {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         for i in self.var_decls.deref_mut() {
             match i.init.deref_mut() {
                 Some(e) => match e.check_type(symbol_table, class_table) {
                     Ok(type_) => {
-                        if let Some(decl_type) = &i.type_ {
-                            if class_table.is_less_or_equal(&type_, decl_type) {
-                                symbol_table.add(&i.name, decl_type);
+                        if let Some(decl_type) = i.ty {
+                            if coerce(e, type_, decl_type, class_table).is_some() {
+                                declare_local(symbol_table, i, decl_type)?;
                             } else {
-                                return Err(SemanticError::new(format!("The type of your let expression init is inconsistent with the declared type!",),Some(i.position.clone()) ));
+                                return Err(SemanticError::new(E0211, format!("The type of your let expression init is inconsistent with the declared type!",),e.position().or(Some(i.position)) ));
                             }
+                        } else if type_ == Ty::Null {
+                            return Err(SemanticError::new(
+                                E0216,
+                                format!(
+                                    "Cannot infer the type of {} from null, declare it as T?!",
+                                    i.name
+                                ),
+                                Some(i.position.clone()),
+                            ));
                         } else {
-                            i.type_ = Some(type_.clone());
-                            symbol_table.add(&i.name, &type_);
+                            i.type_ = Some(type_.to_string());
+                            i.ty = Some(type_);
+                            declare_local(symbol_table, i, type_)?;
                         }
                     }
                     Err(e) => {
@@ -275,89 +598,235 @@ impl TypeChecker for Let {
                     }
                 },
                 None => {
-                    if let Some(decl_type) = &i.type_ {
-                        symbol_table.add(&i.name, decl_type);
+                    if let Some(decl_type) = i.ty {
+                        declare_local(symbol_table, i, decl_type)?;
                     }
                 }
             }
         }
-        return Ok(OBJECT.to_string());
+        return Ok(Ty::OBJECT);
     }
 }
 
 impl TypeChecker for Assignment {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
-        let compute_type = (*self.compute).check_type(symbol_table, class_table);
-        if let Some(id_type) = symbol_table.find(&self.id) {
-            if let Ok(t) = compute_type {
-                if class_table.is_less_or_equal(id_type, &t) {
-                    return Ok(id_type.clone());
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let compute_type = (*self.compute).check_type(symbol_table, class_table)?;
+        let id = Symbol::intern(&self.id);
+        if let Some(id_type) = declared_type(symbol_table, id) {
+            if coerce(self.compute.deref_mut(), compute_type, id_type, class_table).is_some() {
+                if compute_type.is_nullable() {
+                    widen(symbol_table, id);
                 }
+                return Ok(id_type);
             }
         }
         return Err(SemanticError::new(
+            E0211,
             format!("Some semantic errors occurred in your Assignment!",),
             Some(self.position.clone()),
         ));
     }
 }
 
+//* A branch that proves a variable non-null binds it again in its own scope,
+//* next to a `name?` marker that remembers the declared type.
+fn narrow_marker(name: Symbol) -> Symbol {
+    return Symbol::intern(&format!("{}?", name));
+}
+
+fn narrow(symbol_table: &mut SymbolTable<Symbol, Ty>, name: Symbol) {
+    //* any call may set a field back to null, only locals and parameters stay proven
+    if is_field(symbol_table, name) {
+        return;
+    }
+    if let Some(declared) = symbol_table.find(&name).copied() {
+        if declared.is_nullable() && declared != Ty::Null {
+            symbol_table.add(&name, &declared.non_null());
+            symbol_table.add(&narrow_marker(name), &declared);
+        }
+    }
+}
+
+/// Whether `name` is a field, the fields are declared in the scope of the class next to `self`.
+fn is_field(symbol_table: &SymbolTable<Symbol, Ty>, name: Symbol) -> bool {
+    for scope in symbol_table.scopes.iter().rev() {
+        if scope.type_map.contains_key(&name) {
+            return scope.type_map.contains_key(&Symbol::intern(SELF));
+        }
+    }
+    return false;
+}
+
+/// The type a variable was declared with, whatever it was narrowed to.
+fn declared_type(symbol_table: &SymbolTable<Symbol, Ty>, name: Symbol) -> Option<Ty> {
+    for scope in symbol_table.scopes.iter().rev() {
+        if let Some(type_) = scope.type_map.get(&name) {
+            return Some(*scope.type_map.get(&narrow_marker(name)).unwrap_or(type_));
+        }
+    }
+    return None;
+}
+
+/// Assigning a value that may be null undoes every narrowing of the variable.
+fn widen(symbol_table: &mut SymbolTable<Symbol, Ty>, name: Symbol) {
+    let marker = narrow_marker(name);
+    for scope in symbol_table.scopes.iter_mut().rev() {
+        if scope.type_map.contains_key(&name) {
+            match scope.type_map.get(&marker).copied() {
+                Some(declared) => {
+                    scope.type_map.insert(name, declared);
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+/// The variables a loop may assign a value that can be null, in any iteration and in its
+/// nested loops too.
+struct NullableAssignments {
+    names: Vec<Symbol>,
+}
+
+impl Visitor for NullableAssignments {
+    fn visit_assignment(&mut self, e: &Assignment) {
+        //* only values that are never null are known before the value is checked
+        let never_null = matches!(
+            e.compute.deref(),
+            Expr::New(_)
+                | Expr::Int(..)
+                | Expr::Str(..)
+                | Expr::Bool(..)
+                | Expr::Variant(_)
+                | Expr::Not(_)
+                | Expr::Isnull(_)
+                | Expr::Is(_)
+                | Expr::Self_(_)
+        );
+        if !never_null {
+            self.names.push(Symbol::intern(&e.id));
+        }
+        walk_assignment(self, e);
+    }
+}
+
+/// The state at the head of a loop joins the state it is entered with and the state every
+/// iteration leaves, so what the loop may widen is widened before it is checked once.
+fn widen_loop<'a>(symbol_table: &mut SymbolTable<Symbol, Ty>, exprs: impl Iterator<Item = &'a Expr>) {
+    let mut assignments = NullableAssignments { names: vec![] };
+    for expr in exprs {
+        assignments.visit_expr(expr);
+    }
+    for name in assignments.names {
+        widen(symbol_table, name);
+    }
+}
+
+/// `isnull(x)` and `!isnull(x)` on a variable, with whether x is null when the test holds.
+fn null_test(test: &Expr) -> Option<(Symbol, bool)> {
+    match test {
+        Expr::Isnull(e) => match e.expr.deref() {
+            Expr::Identifier(id) => return Some((Symbol::intern(&id.name), true)),
+            _ => return None,
+        },
+        Expr::Not(e) => return null_test(e.expr.deref()).map(|(name, is_null)| (name, !is_null)),
+        _ => return None,
+    }
+}
+
 impl TypeChecker for Math {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         let left_type = (*self.left).check_type(symbol_table, class_table);
         let right_type = (*self.right).check_type(symbol_table, class_table);
 
         match left_type {
-            Ok(left) => match right_type {
-                Ok(right) => {
-                    if left == INT.to_string() && right == INT.to_string() {
+            Ok(mut left) => match right_type {
+                Ok(mut right) => {
+                    // * next to a raw value, an Integer or Bool is unboxed
+                    let raw_left = left.unbox_type().unwrap_or(left);
+                    let raw_right = right.unbox_type().unwrap_or(right);
+                    if raw_left == raw_right
+                        && raw_left.box_class().is_some()
+                        && (left.box_class().is_some() || right.box_class().is_some())
+                    {
+                        if left != raw_left {
+                            unbox_expr(self.left.deref_mut(), raw_left);
+                        }
+                        if right != raw_right {
+                            unbox_expr(self.right.deref_mut(), raw_right);
+                        }
+                        left = raw_left;
+                        right = raw_right;
+                    }
+
+                    if left == Ty::Int && right == Ty::Int {
                         match self.op.deref() {
                             MathOp::ComputeOp(_) => {
-                                self.type_ = INT.to_string();
-                                return Ok(INT.to_string());
+                                self.type_ = Ty::Int.to_string();
+                                return Ok(Ty::Int);
                             }
                             MathOp::CondOp(_) => {
-                                self.type_ = BOOL.to_string();
-                                return Ok(BOOL.to_string());
+                                self.type_ = Ty::BOOL.to_string();
+                                return Ok(Ty::BOOL);
                             }
                         }
                     }
+                    else if left == Ty::RawBool && right == Ty::RawBool {
+                        if let MathOp::CondOp(CondOp::Equal) = self.op.deref() {
+                            self.type_ = Ty::BOOL.to_string();
+                            return Ok(Ty::BOOL);
+                        }
+                        return Err(SemanticError::new(
+                            E0213,
+                            format!("A bool can only be compared with <==>!"),
+                            Some(self.position),
+                        ));
+                    }
                     // ! for string plus
-                    else if left == STRING.to_string() && right == STRING.to_string() {
+                    else if left == Ty::STRING && right == Ty::STRING {
                         match self.op.deref() {
                             MathOp::ComputeOp(op_) => {
                                 if let ComputeOp::Add = op_ {
-                                    self.type_ = STRING.to_string();
-                                    return Ok(STRING.to_string());
+                                    self.type_ = Ty::STRING.to_string();
+                                    return Ok(Ty::STRING);
                                 } else {
                                     return Err(SemanticError::new(
+                                        E0213,
                                         format!(
                                             "String cannot be used for mathematical operations other than addition"
                                         ),
-                                        None,
+                                        Some(self.position),
                                     ));
                                 }
                             }
                             MathOp::CondOp(_) => {
                                 return Err(SemanticError::new(
+                                    E0213,
                                     format!("String cannot be used in conditional operations"),
-                                    None,
+                                    Some(self.position),
                                 ))
                             }
                         }
                     }
+                    // * a class gets the operator by defining its method
+                    else if let Some((call, type_)) = operator_call(self, left, right, class_table)? {
+                        self.type_ = type_.non_null().to_string();
+                        self.overload = Some(Box::new(Expr::Dispatch(call)));
+                        return Ok(type_);
+                    }
                     else {
                         return Err(SemanticError::new(
+                            E0213,
                             format!("The left and right sides of your mathematical operation are not all INT types!"),
-                            None
+                            Some(self.position),
                         ));
                     }
                 }
@@ -368,25 +837,71 @@ impl TypeChecker for Math {
     }
 }
 
+/// The call an operator on class operands stands for, built from the operand types already
+/// checked. `a + b` calls `a.add(b)` and `a < b` calls `a.less(b)`, always on the left operand
+/// so the operands run in the order they are written.
+fn operator_call(
+    e: &mut Math,
+    left: Ty,
+    right: Ty,
+    class_table: &ClassTable,
+) -> Result<Option<(Dispatch, Ty)>, SemanticError> {
+    let name = e.op.method_name();
+    let class_name = match left.class_name() {
+        Some(class_name) if !left.is_nullable() => class_name,
+        _ => return Ok(None),
+    };
+    let candidates = method_candidates(class_table, class_name, name, e.left.is_self_expr());
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    let method = resolve_overload(&candidates, &vec![right], class_table, e.position)?;
+    let type_ = return_type_on(class_table, class_name, method);
+    if let MathOp::CondOp(_) = e.op.deref() {
+        if type_ != Ty::BOOL {
+            return Err(SemanticError::new(
+                E0113,
+                format!("The operator method {}.{} must return Bool!", left, name),
+                Some(e.position),
+            ));
+        }
+    }
+    coerce(e.right.deref_mut(), right, method.param_tys[0], class_table);
+    let call = Dispatch {
+        target: e.left.clone(),
+        expr: DispatchExpr::Method(MethodCall {
+            fun_name: name.to_string(),
+            actual: Box::new(vec![e.right.deref().clone()]),
+            param_types: method.param_types(),
+        }),
+        position: e.position,
+        type_: type_.non_null().to_string(),
+        safe: false,
+        target_type: Some(left),
+    };
+    return Ok(Some((call, type_)));
+}
+
 impl TypeChecker for Cond {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         symbol_table.enter_scope();
 
-        let test_type = (*self.test).check_type(symbol_table, class_table);
-        match test_type {
-            Ok(test) => {
-                if test != BOOL.to_string() {
-                    return Err(SemanticError::new(
-                        format!("The type in your If condition is not BOOL",),
-                        Some(self.position.clone()),
-                    ));
-                }
-            }
-            Err(e) => return Err(e),
+        if !check_test(self.test.deref_mut(), symbol_table, class_table)? {
+            return Err(SemanticError::new(
+                E0212,
+                format!("The type in your If condition is not BOOL",),
+                Some(self.position.clone()),
+            ));
+        }
+        let narrowed = null_test(self.test.deref());
+
+        symbol_table.enter_scope();
+        if let Some((name, false)) = &narrowed {
+            narrow(symbol_table, *name);
         }
         for then_expr in self.then_body.deref_mut() {
             let then_type = then_expr.check_type(symbol_table, class_table);
@@ -395,7 +910,12 @@ impl TypeChecker for Cond {
                 _ => {}
             }
         }
+        symbol_table.exit_scope();
 
+        symbol_table.enter_scope();
+        if let Some((name, true)) = &narrowed {
+            narrow(symbol_table, *name);
+        }
         for else_expr in self.else_body.deref_mut() {
             let else_type = else_expr.check_type(symbol_table, class_table);
             match else_type {
@@ -403,31 +923,33 @@ impl TypeChecker for Cond {
                 _ => {}
             }
         }
+        symbol_table.exit_scope();
 
         symbol_table.exit_scope();
-        return Ok(OBJECT.to_string());
+        return Ok(Ty::OBJECT);
     }
 }
 
 impl TypeChecker for While {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         symbol_table.enter_scope();
 
-        let test_type = (*self.test).check_type(symbol_table, class_table);
-        match test_type {
-            Ok(test) => {
-                if test != BOOL.to_string() {
-                    return Err(SemanticError::new(
-                        format!("The type in your Loop condition is not BOOL",),
-                        Some(self.position.clone()),
-                    ));
-                }
-            }
-            Err(e) => return Err(e),
+        let narrowed = null_test(self.test.deref());
+        widen_loop(symbol_table, std::iter::once(self.test.deref()).chain(self.body.iter()));
+        if !check_test(self.test.deref_mut(), symbol_table, class_table)? {
+            return Err(SemanticError::new(
+                E0212,
+                format!("The type in your Loop condition is not BOOL",),
+                Some(self.position.clone()),
+            ));
+        }
+        symbol_table.enter_scope();
+        if let Some((name, false)) = &narrowed {
+            narrow(symbol_table, *name);
         }
         for body_expr in self.body.deref_mut() {
             let body_type = body_expr.check_type(symbol_table, class_table);
@@ -437,22 +959,50 @@ impl TypeChecker for While {
             }
         }
         symbol_table.exit_scope();
-        return Ok(OBJECT.to_string());
+        symbol_table.exit_scope();
+        return Ok(Ty::OBJECT);
     }
 }
 
 impl TypeChecker for Return {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let return_type = symbol_table.find(&Symbol::intern(RETURN_TYPE)).copied();
         match &mut self.val {
-            Some(e) => match e.deref_mut().check_type(symbol_table, class_table) {
-                Ok(e) => return Ok(e),
-                Err(e) => return Err(e),
-            },
-            None => return Ok(VOID.to_string()),
+            Some(e) => {
+                let type_ = e.deref_mut().check_type(symbol_table, class_table)?;
+                if let Some(return_type) = return_type {
+                    match coerce(e, type_, return_type, class_table) {
+                        Some(type_) => return Ok(type_),
+                        None => {
+                            return Err(SemanticError::new(
+                                E0222,
+                                format!(
+                                    "The returned {} is different from the declared return type {}!",
+                                    type_, return_type
+                                ),
+                                Some(self.position),
+                            ))
+                        }
+                    }
+                }
+                return Ok(type_);
+            }
+            None => {
+                if let Some(return_type) = return_type {
+                    if needs_return(return_type) {
+                        return Err(SemanticError::new(
+                            E0222,
+                            format!("The method returns {}, so a value must be returned!", return_type),
+                            Some(self.position),
+                        ));
+                    }
+                }
+                return Ok(Ty::VOID);
+            }
         }
     }
 }
@@ -460,39 +1010,31 @@ impl TypeChecker for Return {
 impl TypeChecker for Not {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
-        let e = self.expr.deref_mut();
-        let expr_type = e.check_type(symbol_table, class_table);
-        match expr_type {
-            Ok(type_) => {
-                if type_ != BOOL.to_string() {
-                    return Err(SemanticError::new(
-                        format!("The type in your Not expression is not BOOL",),
-                        Some(self.position.clone()),
-                    ));
-                }
-                return Ok(type_);
-            }
-            Err(e) => {
-                return Err(e);
-            }
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        if !check_test(self.expr.deref_mut(), symbol_table, class_table)? {
+            return Err(SemanticError::new(
+                E0212,
+                format!("The type in your Not expression is not BOOL",),
+                Some(self.position.clone()),
+            ));
         }
+        return Ok(Ty::BOOL);
     }
 }
 
 impl TypeChecker for Isnull {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         let e = self.expr.deref_mut();
         let expr_type = e.check_type(symbol_table, class_table);
         match expr_type {
             Ok(_) => {
-                return Ok(BOOL.to_string());
+                return Ok(Ty::BOOL);
             }
             Err(e) => {
                 return Err(e);
@@ -504,13 +1046,14 @@ impl TypeChecker for Isnull {
 impl TypeChecker for For {
     fn check_type(
         &mut self,
-        symbol_table: &mut SymbolTable<Identifier, Type>,
-        class_table: &mut ClassTable,
-    ) -> Result<Type, SemanticError> {
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
         symbol_table.enter_scope();
 
         if self.init.deref().len() > 1 || self.test.deref().len() > 1 {
             return Err(SemanticError::new(
+                E0224,
                 format!(
                     " There can only be one initial expression and one judgment expression in the for loop!"
                 ),
@@ -524,38 +1067,446 @@ impl TypeChecker for For {
                 _ => {}
             }
         }
+        let narrowed = self.test.first().and_then(null_test);
+        widen_loop(
+            symbol_table,
+            self.test.iter().chain(self.body.iter()).chain(self.iter.iter()),
+        );
         for test_ in self.test.deref_mut() {
-            let test_type: Result<String, SemanticError> =
-                test_.check_type(symbol_table, class_table);
-            match test_type {
+            if !check_test(test_, symbol_table, class_table)? {
+                return Err(SemanticError::new(
+                    E0212,
+                    format!(
+                        "The type of the conditional expression in the for loop is not BOOL!"
+                    ),
+                    Some(self.position)
+                ));
+            }
+        }
+        symbol_table.enter_scope();
+        if let Some((name, false)) = &narrowed {
+            narrow(symbol_table, *name);
+        }
+        for body_ in self.body.deref_mut() {
+            let body_type = body_.check_type(symbol_table, class_table);
+            match body_type {
                 Err(e) => return Err(e),
-                Ok(type_) => {
-                    if type_ != BOOL.to_string() {
+                _ => {}
+            }
+        }
+        for iter_ in self.iter.deref_mut() {
+            let iter_type = iter_.check_type(symbol_table, class_table);
+            match iter_type {
+                Err(e) => return Err(e),
+                _ => {}
+            }
+        }
+        symbol_table.exit_scope();
+        symbol_table.exit_scope();
+        Ok(Ty::OBJECT)
+    }
+}
+
+impl TypeChecker for VariantCall {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let enum_name = Symbol::intern(&self.enum_name);
+        let variant = match class_table.get(enum_name) {
+            Some(class_) if class_.is_enum() => match class_.get_variant(&self.variant) {
+                Some((_, variant)) => variant,
+                None => {
+                    let names = class_.variants.iter().flatten().map(|v| &v.name);
+                    return Err(SemanticError::new(
+                        E0202,
+                        format!(
+                            "Enum {} has no variant called {}!",
+                            &self.enum_name, &self.variant
+                        ),
+                        Some(self.position),
+                    )
+                    .with_suggestion(&self.variant, names));
+                }
+            },
+            Some(_) => {
+                return Err(SemanticError::new(
+                    E0202,
+                    format!("Class {} is not an enum!", &self.enum_name),
+                    Some(self.position),
+                ))
+            }
+            None => {
+                let names = class_table
+                    .classes()
+                    .filter(|c| c.is_enum())
+                    .map(|c| &c.name);
+                return Err(SemanticError::new(
+                    E0202,
+                    format!("There is no enum called {}!", &self.enum_name),
+                    Some(self.position),
+                )
+                .with_suggestion(&self.enum_name, names));
+            }
+        };
+
+        let actuals = self.actual.deref_mut();
+        if actuals.len() != variant.fields.len() {
+            return Err(SemanticError::new(
+                E0220,
+                format!(
+                    "The variant {}::{} has {} fields, but {} were given!",
+                    &self.enum_name,
+                    &self.variant,
+                    variant.fields.len(),
+                    actuals.len()
+                ),
+                Some(self.position),
+            ));
+        }
+        for ((actual, field), field_type) in
+            actuals.iter_mut().zip(variant.fields.iter()).zip(variant.field_tys.iter())
+        {
+            let actual_type = actual.check_type(symbol_table, class_table)?;
+            if coerce(actual, actual_type, *field_type, class_table).is_none() {
+                return Err(SemanticError::new(
+                    E0211,
+                    format!(
+                        "The field <{}> of the variant {}::{} expects {}, but the actual type is {}!",
+                        field.0, &self.enum_name, &self.variant, field.1, actual_type
+                    ),
+                    Some(self.position),
+                ));
+            }
+        }
+        return Ok(Ty::Class(enum_name));
+    }
+}
+
+impl TypeChecker for Match {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let expr_type = self.expr.check_type(symbol_table, class_table)?;
+        let variants = match expr_type.class_name().and_then(|c| class_table.get(c)) {
+            Some(class_) if class_.is_enum() && !expr_type.is_nullable() => {
+                class_.variants.as_ref().unwrap()
+            }
+            _ => {
+                return Err(SemanticError::new(
+                    E0219,
+                    format!("Only enums can be matched, but the type here is {}!", expr_type),
+                    Some(self.position),
+                ))
+            }
+        };
+
+        let mut covered: Vec<&Identifier> = vec![];
+        let mut wildcard = false;
+        let mut arm_types: Vec<Ty> = vec![];
+        for arm in &mut self.arms {
+            symbol_table.enter_scope();
+            match &arm.pattern {
+                Pattern::Wildcard => {
+                    if arm.guard.is_none() {
+                        wildcard = true;
+                    }
+                }
+                Pattern::Variant(pattern) => {
+                    let variant = match variants.iter().find(|v| v.name == pattern.name) {
+                        Some(v) => v,
+                        None => {
+                            let names = variants.iter().map(|v| &v.name);
+                            return Err(SemanticError::new(
+                                E0202,
+                                format!(
+                                    "Enum {} has no variant called {}!",
+                                    expr_type, pattern.name
+                                ),
+                                Some(pattern.position),
+                            )
+                            .with_suggestion(&pattern.name, names));
+                        }
+                    };
+                    // * a bare variant name ignores the payload
+                    if !pattern.bindings.is_empty()
+                        && pattern.bindings.len() != variant.fields.len()
+                    {
                         return Err(SemanticError::new(
+                            E0220,
                             format!(
-                                "The type of the conditional expression in the for loop is not BOOL!"
+                                "The variant {}::{} has {} fields, but the pattern binds {}!",
+                                expr_type,
+                                variant.name,
+                                variant.fields.len(),
+                                pattern.bindings.len()
                             ),
-                            Some(self.position)
+                            Some(pattern.position),
                         ));
                     }
+                    for (binding, type_) in pattern.bindings.iter().zip(variant.field_tys.iter()) {
+                        if let Some(name) = binding {
+                            if symbol_table.declare(&Symbol::intern(name), type_, pattern.position).is_some() {
+                                return Err(SemanticError::new(
+                                    E0225,
+                                    format!("The pattern {} binds {} twice!", pattern.name, name),
+                                    Some(pattern.position),
+                                ));
+                            }
+                        }
+                    }
+                    if arm.guard.is_none() && !covered.contains(&&variant.name) {
+                        covered.push(&variant.name);
+                    }
                 }
             }
+
+            if let Some(guard) = &mut arm.guard {
+                if !check_test(guard, symbol_table, class_table)? {
+                    return Err(SemanticError::new(
+                        E0212,
+                        format!("The type in your match guard is not BOOL"),
+                        Some(self.position),
+                    ));
+                }
+            }
+            arm_types.push(arm.body.check_type(symbol_table, class_table)?);
+            symbol_table.exit_scope();
         }
-        for iter_ in self.iter.deref_mut() {
-            let iter_type = iter_.check_type(symbol_table, class_table);
-            match iter_type {
-                Err(e) => return Err(e),
-                _ => {}
+
+        if !wildcard {
+            let missing: Vec<String> = variants
+                .iter()
+                .filter(|v| !covered.contains(&&v.name))
+                .map(|v| format!("{}::{}", expr_type, v.name))
+                .collect();
+            if !missing.is_empty() {
+                return Err(SemanticError::new(
+                    E0221,
+                    format!(
+                        "Non-exhaustive match, these variants are not covered: {}!",
+                        missing.join(", ")
+                    ),
+                    Some(self.position),
+                )
+                .with_help(format!("add an arm for each of them, or a _ arm")));
             }
         }
-        for body_ in self.body.deref_mut() {
-            let body_type = body_.check_type(symbol_table, class_table);
-            match body_type {
-                Err(e) => return Err(e),
-                _ => {}
+
+        //* arms with incompatible types make the match a statement
+        let mut type_ = arm_types[0];
+        for arm_type in &arm_types[1..] {
+            match class_table.join(type_, *arm_type) {
+                Some(t) => type_ = t,
+                None => {
+                    type_ = Ty::VOID;
+                    break;
+                }
+            }
+        }
+        if type_ != Ty::VOID {
+            for (arm, arm_type) in self.arms.iter_mut().zip(arm_types.iter()) {
+                coerce(arm.body.deref_mut(), *arm_type, type_, class_table);
+            }
+        }
+        self.type_ = type_.to_string();
+        return Ok(type_);
+    }
+}
+
+/// Both `is` and `as` need a class on the right and two related types.
+fn check_cast(
+    from: Ty,
+    to: &Type,
+    position: Position,
+    class_table: &ClassTable,
+) -> Result<Ty, SemanticError> {
+    let to_type = Ty::parse(to);
+    if !matches!(to_type, Ty::Class(c) if class_table.contains(c)) {
+        return Err(SemanticError::new(
+            E0201,
+            format!("There is no class called {}!", to),
+            Some(position),
+        )
+        .with_suggestion(to, class_table.names()));
+    }
+    if from.is_primitive() {
+        return Err(SemanticError::new(
+            E0214,
+            format!("Raw type {} can't be tested or cast!", from),
+            Some(position),
+        ));
+    }
+    if !class_table.is_less_or_equal(from, to_type) && !class_table.is_less_or_equal(to_type, from)
+    {
+        return Err(SemanticError::new(
+            E0217,
+            format!(
+                "Cannot cast {} to {}, because neither of them inherits the other!",
+                from, to
+            ),
+            Some(position),
+        ));
+    }
+    Ok(to_type)
+}
+
+impl TypeChecker for TypeTest {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let expr_type = self.expr.check_type(symbol_table, class_table)?;
+        check_cast(expr_type.non_null(), &self.class_name, self.position, class_table)?;
+        return Ok(Ty::BOOL);
+    }
+}
+
+impl TypeChecker for Cast {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let expr_type = self.expr.check_type(symbol_table, class_table)?;
+        let class_name =
+            check_cast(expr_type.non_null(), &self.class_name, self.position, class_table)?;
+        self.upcast = class_table.is_less_or_equal(expr_type.non_null(), class_name);
+        //* null passes through a cast
+        if expr_type.is_nullable() {
+            return Ok(class_name.nullable());
+        }
+        return Ok(class_name);
+    }
+}
+
+impl TypeChecker for Coalesce {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let left_type = self.left.check_type(symbol_table, class_table)?;
+        let right_type = self.right.check_type(symbol_table, class_table)?;
+        if left_type.is_primitive() {
+            return Err(SemanticError::new(
+                E0214,
+                format!("Raw type {} is never null, <??> can't be used on it!", left_type),
+                Some(self.position),
+            ));
+        }
+        match class_table.join(left_type.non_null(), right_type) {
+            Some(type_) => {
+                coerce(self.right.deref_mut(), right_type, type_, class_table);
+                self.type_ = type_.non_null().to_string();
+                return Ok(type_);
             }
+            None => {
+                return Err(SemanticError::new(
+                    E0213,
+                    format!(
+                        "The two sides of <??> have incompatible types {} and {}!",
+                        left_type, right_type
+                    ),
+                    Some(self.position),
+                ));
+            }
+        }
+    }
+}
+
+impl TypeChecker for Throw {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        let expr_type = self.expr.check_type(symbol_table, class_table)?;
+        if !class_table.is_less_or_equal(expr_type, Ty::EXCEPTION) {
+            return Err(SemanticError::new(
+                E0218,
+                format!("Only exceptions can be thrown, but the type is {}!", expr_type),
+                Some(self.position),
+            ));
+        }
+        return Ok(Ty::VOID);
+    }
+}
+
+impl TypeChecker for Try {
+    fn check_type(
+        &mut self,
+        symbol_table: &mut SymbolTable<Symbol, Ty>,
+        class_table: &ClassTable,
+    ) -> Result<Ty, SemanticError> {
+        symbol_table.enter_scope();
+        for expr in self.body.deref_mut() {
+            expr.check_type(symbol_table, class_table)?;
         }
         symbol_table.exit_scope();
-        Ok(OBJECT.to_string())
+
+        //* the classes of the catches before the one being checked
+        let mut caught: Vec<Ty> = vec![];
+        for index in 0..self.catches.len() {
+            let (earlier, rest) = self.catches.split_at_mut(index);
+            let catch = &mut rest[0];
+            let class_name = Symbol::intern(&catch.class_name);
+            if !class_table.contains(class_name) {
+                return Err(SemanticError::new(
+                    E0201,
+                    format!("There is no class called {}!", catch.class_name),
+                    Some(catch.position),
+                )
+                .with_suggestion(&catch.class_name, class_table.names()));
+            }
+            if !class_table.is_less_or_equal(Ty::Class(class_name), Ty::EXCEPTION) {
+                return Err(SemanticError::new(
+                    E0218,
+                    format!(
+                        "Only exceptions can be caught, but {} is not one!",
+                        catch.class_name
+                    ),
+                    Some(catch.position),
+                ));
+            }
+            let shadowing = earlier
+                .iter()
+                .zip(caught.iter())
+                .find(|(_, ty)| class_table.is_less_or_equal(Ty::Class(class_name), **ty))
+                .map(|(e, _)| e);
+            if let Some(shadowing) = shadowing {
+                return Err(SemanticError::new(
+                    E0226,
+                    format!(
+                        "This catch is unreachable, every {} is already caught as {}!",
+                        catch.class_name, shadowing.class_name
+                    ),
+                    Some(catch.position),
+                )
+                .with_label_here(format!("{} is caught here", shadowing.class_name), shadowing.position)
+                .with_help(format!("catch the more specific exception first")));
+            }
+            symbol_table.enter_scope();
+            //* declared, so a local of the catch body can't hide the exception it names
+            symbol_table.declare(&Symbol::intern(&catch.name), &Ty::Class(class_name), catch.position);
+            for expr in catch.body.deref_mut() {
+                expr.check_type(symbol_table, class_table)?;
+            }
+            symbol_table.exit_scope();
+            caught.push(Ty::Class(class_name));
+        }
+
+        if let Some(finally) = &mut self.finally {
+            symbol_table.enter_scope();
+            for expr in finally.deref_mut() {
+                expr.check_type(symbol_table, class_table)?;
+            }
+            symbol_table.exit_scope();
+        }
+        return Ok(Ty::VOID);
     }
 }
@@ -0,0 +1,90 @@
+use crate::lexer::{BytePos, Position};
+
+/// Tabs are shown as this many spaces, so the underline stays under its code.
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Clone)]
+struct SourceFile {
+    name: String,
+    src: String,
+    /// The byte offset every line starts at.
+    line_starts: Vec<BytePos>,
+}
+
+/// The text of every file in the compile, so a `Position` can be turned into lines and columns
+/// without reading the file again.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self { files: vec![] }
+    }
+
+    pub fn add(&mut self, name: &String, src: String) {
+        let mut line_starts = vec![0];
+        line_starts.extend(src.match_indices('\n').map(|(i, _)| i + 1));
+        self.files.push(SourceFile {
+            name: name.clone(),
+            src,
+            line_starts,
+        });
+    }
+
+    fn file(&self, name: &String) -> Option<&SourceFile> {
+        return self.files.iter().find(|f| &f.name == name);
+    }
+
+    /// The line and column of `offset`, both counted from 1, a tab is one column.
+    pub fn line_column(&self, file_name: &String, offset: BytePos) -> (usize, usize) {
+        let file = match self.file(file_name) {
+            Some(file) => file,
+            None => return (0, 0),
+        };
+        let offset = offset.min(file.src.len());
+        let line = file.line_starts.partition_point(|start| *start <= offset);
+        let line_start = file.line_starts[line - 1];
+        return (line, file.src[line_start..offset].chars().count() + 1);
+    }
+
+    /// The text of `line`, counted from 1, without its line break.
+    pub fn line(&self, file_name: &String, line: usize) -> &str {
+        let file = match self.file(file_name) {
+            Some(file) => file,
+            None => return "",
+        };
+        if line == 0 || line > file.line_starts.len() {
+            return "";
+        }
+        let start = file.line_starts[line - 1];
+        let end = match file.line_starts.get(line) {
+            Some(next) => next - 1,
+            None => file.src.len(),
+        };
+        return file.src[start..end].trim_end_matches('\r');
+    }
+
+    /// The first line of `pos` with tabs expanded, the columns the underline starts at and how
+    /// long it is. A position over several lines is underlined to the end of its first line.
+    pub fn snippet(&self, file_name: &String, pos: Position) -> (usize, String, usize, usize) {
+        let (line, column) = self.line_column(file_name, pos.start);
+        if line == 0 {
+            return (0, String::new(), 0, 1);
+        }
+        let text = self.line(file_name, line);
+        let (end_line, end_column) = self.line_column(file_name, pos.end);
+        let end_column = if end_line == line {
+            end_column
+        } else {
+            text.chars().count() + 1
+        };
+        let width = |chars: &mut dyn Iterator<Item = char>| -> usize {
+            chars.map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+        };
+        let indent = width(&mut text.chars().take(column - 1));
+        let len = width(&mut text.chars().skip(column - 1).take(end_column.saturating_sub(column)));
+        return (line, text.replace('\t', &" ".repeat(TAB_WIDTH)), indent, len.max(1));
+    }
+}
@@ -0,0 +1,201 @@
+//! Interned names and the types the semantic check works with. A name is interned once and
+//! is a `Symbol` from then on, so names and types compare and hash as integers.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+
+use crate::{BOOL, EXCEPTION, INT, INTEGER, NULL_TYPE, OBJECT, RAW_BOOL, STRING, VOID};
+
+/// An interned name, two symbols are equal when their names are.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// The names every program uses, interned before any other in this order.
+const PREDEFINED: [&str; 6] = [OBJECT, STRING, INTEGER, BOOL, VOID, EXCEPTION];
+
+pub mod sym {
+    use super::Symbol;
+
+    pub const OBJECT: Symbol = Symbol(0);
+    pub const STRING: Symbol = Symbol(1);
+    pub const INTEGER: Symbol = Symbol(2);
+    pub const BOOL: Symbol = Symbol(3);
+    pub const VOID: Symbol = Symbol(4);
+    pub const EXCEPTION: Symbol = Symbol(5);
+}
+
+/// Owns every interned name, a symbol is the index of its name.
+struct Interner {
+    symbols: HashMap<Arc<str>, Symbol>,
+    names: Vec<Arc<str>>,
+}
+
+impl Interner {
+    fn new() -> Interner {
+        let mut interner = Interner {
+            symbols: HashMap::new(),
+            names: vec![],
+        };
+        for name in PREDEFINED {
+            interner.intern(name);
+        }
+        return interner;
+    }
+
+    fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return *symbol;
+        }
+        let name: Arc<str> = Arc::from(name);
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(name.clone());
+        self.symbols.insert(name, symbol);
+        return symbol;
+    }
+}
+
+lazy_static! {
+    //* one interner for every thread, so a symbol names the same class wherever it is used
+    static ref INTERNER: Mutex<Interner> = Mutex::new(Interner::new());
+}
+
+impl Symbol {
+    pub fn intern(name: &str) -> Symbol {
+        return INTERNER.lock().unwrap().intern(name);
+    }
+
+    /// The name, shared with the interner rather than copied.
+    pub fn as_str(self) -> Arc<str> {
+        return INTERNER.lock().unwrap().names[self.0 as usize].clone();
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(&self.as_str());
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{:?}", &*self.as_str());
+    }
+}
+
+/// A type as the semantic check sees it. A class type is the interned name of the class,
+/// the class table finds the class itself, and an undeclared class only fits itself.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ty {
+    /// The raw `int`.
+    Int,
+    /// The raw `bool`.
+    RawBool,
+    RawStr,
+    RawVoid,
+    /// The type of the null literal.
+    Null,
+    Class(Symbol),
+    /// `Class?`, the class or null.
+    Nullable(Symbol),
+}
+
+impl Ty {
+    pub const OBJECT: Ty = Ty::Class(sym::OBJECT);
+    pub const STRING: Ty = Ty::Class(sym::STRING);
+    pub const INTEGER: Ty = Ty::Class(sym::INTEGER);
+    pub const BOOL: Ty = Ty::Class(sym::BOOL);
+    pub const VOID: Ty = Ty::Class(sym::VOID);
+    pub const EXCEPTION: Ty = Ty::Class(sym::EXCEPTION);
+
+    /// The type an annotation like `int` or `Node?` names.
+    pub fn parse(type_: &str) -> Ty {
+        match type_ {
+            INT => return Ty::Int,
+            RAW_BOOL => return Ty::RawBool,
+            "str" => return Ty::RawStr,
+            "void" => return Ty::RawVoid,
+            NULL_TYPE => return Ty::Null,
+            _ => {}
+        }
+        let name = type_.trim_end_matches('?');
+        if name.len() != type_.len() {
+            return Ty::Nullable(Symbol::intern(name));
+        }
+        return Ty::Class(Symbol::intern(name));
+    }
+
+    pub fn is_primitive(self) -> bool {
+        return matches!(self, Ty::Int | Ty::RawBool | Ty::RawStr | Ty::RawVoid);
+    }
+
+    /// `Node?` may hold null, so may the null literal itself.
+    pub fn is_nullable(self) -> bool {
+        return matches!(self, Ty::Nullable(_) | Ty::Null);
+    }
+
+    /// The class of a class type, nullable or not.
+    pub fn class_name(self) -> Option<Symbol> {
+        match self {
+            Ty::Class(name) | Ty::Nullable(name) => return Some(name),
+            _ => return None,
+        }
+    }
+
+    pub fn non_null(self) -> Ty {
+        match self {
+            Ty::Nullable(name) => return Ty::Class(name),
+            _ => return self,
+        }
+    }
+
+    /// A class type that may be null, other types are already nullable or never are.
+    pub fn nullable(self) -> Ty {
+        match self {
+            Ty::Class(name) => return Ty::Nullable(name),
+            _ => return self,
+        }
+    }
+
+    /// The class a raw value is boxed into, `int` to `Integer` and `bool` to `Bool`.
+    pub fn box_class(self) -> Option<Ty> {
+        match self {
+            Ty::Int => return Some(Ty::INTEGER),
+            Ty::RawBool => return Some(Ty::BOOL),
+            _ => return None,
+        }
+    }
+
+    /// The raw type a box class unboxes to.
+    pub fn unbox_type(self) -> Option<Ty> {
+        match self {
+            Ty::INTEGER => return Some(Ty::Int),
+            Ty::BOOL => return Some(Ty::RawBool),
+            _ => return None,
+        }
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Int => return f.write_str(INT),
+            Ty::RawBool => return f.write_str(RAW_BOOL),
+            Ty::RawStr => return f.write_str("str"),
+            Ty::RawVoid => return f.write_str("void"),
+            Ty::Null => return f.write_str(NULL_TYPE),
+            Ty::Class(name) => return write!(f, "{}", name),
+            Ty::Nullable(name) => return write!(f, "{}?", name),
+        }
+    }
+}
+
+impl fmt::Debug for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self);
+    }
+}
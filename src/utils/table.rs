@@ -1,12 +1,15 @@
 use std::{
     collections::{HashMap, HashSet},
-    fmt::Display,
     hash::Hash,
 };
 
-use crate::parser::ast::{
-    class::{Class, ConstructorDecl, Feature},
-    Type,
+use crate::{
+    lexer::Position,
+    parser::ast::{
+        class::{Class, ConstructorDecl, Feature, MethodDecl, VarDecl},
+        is_nullable, is_primitive,
+    },
+    ty::{Symbol, Ty},
 };
 
 #[derive(Debug, Clone)]
@@ -24,59 +27,209 @@ impl Tables {
         }
     }
 }
+/// Where a class is in the arena of the class table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassId(usize);
+
+/// The declared classes, each kept once and handed out by reference.
 #[derive(Debug, Clone)]
 pub struct ClassTable {
-    pub classes: HashMap<Type, Class>,
-    pub inheritance: HashMap<Type, Vec<Class>>,
-    pub class_constructors: HashMap<Type, Vec<ConstructorDecl>>,
+    classes: Vec<Class>,
+    /// The interned name of each class, by id.
+    symbols: Vec<Symbol>,
+    ids: HashMap<Symbol, ClassId>,
+    /// The classes each class inherits, root first and ending with the class itself.
+    inheritance: HashMap<ClassId, Vec<ClassId>>,
+    constructors: HashMap<Symbol, Vec<ConstructorDecl>>,
 }
 
 impl ClassTable {
     pub fn new() -> ClassTable {
         ClassTable {
-            classes: HashMap::new(),
+            classes: Vec::new(),
+            symbols: Vec::new(),
+            ids: HashMap::new(),
             inheritance: HashMap::new(),
-            class_constructors: HashMap::new(),
+            constructors: HashMap::new(),
         }
     }
-    pub fn get_classes(&mut self) -> HashMap<String, Class> {
-        return self.classes.clone();
+
+    /// Adds a class whose name isn't taken yet.
+    pub fn add(&mut self, class_: Class) -> ClassId {
+        let id = ClassId(self.classes.len());
+        let name = Symbol::intern(&class_.name);
+        self.ids.insert(name, id);
+        self.symbols.push(name);
+        self.classes.push(class_);
+        return id;
+    }
+
+    pub fn id(&self, name: Symbol) -> Option<ClassId> {
+        return self.ids.get(&name).copied();
     }
 
-    pub fn get_inheritance(&self) -> HashMap<String, Vec<Class>> {
-        return self.inheritance.clone();
+    pub fn class(&self, id: ClassId) -> &Class {
+        return &self.classes[id.0];
     }
 
-    pub fn is_less_or_equal(&self, child: &Type, parent: &Type) -> bool {
+    pub fn get(&self, name: Symbol) -> Option<&Class> {
+        return self.id(name).map(|id| self.class(id));
+    }
+
+    pub fn contains(&self, name: Symbol) -> bool {
+        return self.ids.contains_key(&name);
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &Class> {
+        return self.classes.iter();
+    }
+
+    /// Every class name, for suggesting one in place of an unknown name.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        return self.classes.iter().map(|c| &c.name);
+    }
+
+    pub fn set_inheritance(&mut self, id: ClassId, chain: Vec<ClassId>) {
+        self.inheritance.insert(id, chain);
+    }
+
+    /// The classes `name` inherits, root first and ending with the class itself.
+    pub fn inheritance(
+        &self,
+        name: Symbol,
+    ) -> Option<impl DoubleEndedIterator<Item = &Class> + ExactSizeIterator + Clone> {
+        let chain = self.inheritance.get(&self.id(name)?)?;
+        return Some(chain.iter().map(move |id| self.class(*id)));
+    }
+
+    pub fn set_constructors(&mut self, name: Symbol, constructors: Vec<ConstructorDecl>) {
+        self.constructors.insert(name, constructors);
+    }
+
+    pub fn constructors(&self, name: Symbol) -> &[ConstructorDecl] {
+        return self.constructors.get(&name).map_or(&[], |c| c.as_slice());
+    }
+
+    fn inherits(&self, child: Symbol, parent: Symbol) -> bool {
+        let (child, parent) = match (self.id(child), self.id(parent)) {
+            (Some(child), Some(parent)) => (child, parent),
+            _ => return false,
+        };
+        return self
+            .inheritance
+            .get(&child)
+            .map_or(false, |chain| chain.contains(&parent));
+    }
+
+    pub fn is_less_or_equal(&self, child: Ty, parent: Ty) -> bool {
         if child == parent {
             return true;
         }
-        if let Some(v) = self.inheritance.get(child) {
-            for i in v {
-                if &i.name == parent {
-                    return true;
+        //* a raw value fits wherever its box class does
+        if let Some(class_name) = child.box_class() {
+            return !parent.is_primitive() && self.is_less_or_equal(class_name, parent);
+        }
+        //* null fits every nullable type, a nullable type never fits a non-nullable one
+        if child == Ty::Null {
+            return parent.is_nullable();
+        }
+        if child.is_nullable() && !parent.is_nullable() {
+            return false;
+        }
+        match (child.class_name(), parent.class_name()) {
+            (Some(child), Some(parent)) => return child == parent || self.inherits(child, parent),
+            _ => return false,
+        }
+    }
+
+    /// Like `is_less_or_equal`, but a raw type only fits itself, as boxing changes how the
+    /// value is passed.
+    pub fn is_less_or_equal_unboxed(&self, child: Ty, parent: Ty) -> bool {
+        if child.is_primitive() || parent.is_primitive() {
+            return child == parent;
+        }
+        return self.is_less_or_equal(child, parent);
+    }
+
+    /// The methods of `class_` that may override `method` of a superclass: the ones with its
+    /// name taking every argument it takes. Their return types aren't checked here.
+    pub fn overriders<'a>(&self, class_: &'a Class, method: &MethodDecl) -> Vec<&'a MethodDecl> {
+        let mut overriders = vec![];
+        for feature in &class_.features {
+            if let Feature::Method(m) = feature {
+                if m.name == method.name
+                    && m.param.len() == method.param.len()
+                    && m.param_tys
+                        .iter()
+                        .zip(method.param_tys.iter())
+                        .all(|(p, q)| self.is_less_or_equal_unboxed(*q, *p))
+                {
+                    overriders.push(m);
                 }
             }
         }
-        return false;
+        return overriders;
     }
 
-    pub fn get_parent(&self, child: &Type) -> String {
-        let binding = self.get_inheritance();
-        let v = binding.get(child).unwrap();
-        v.get(v.len() - 2).unwrap().name.clone()
+    /// The method of `class_` that overrides `method`: one with the same parameter types,
+    /// otherwise the one with the most specific parameters. None when no method or more than
+    /// one equally specific method overrides it.
+    pub fn overrider<'a>(&self, class_: &'a Class, method: &MethodDecl) -> Option<&'a MethodDecl> {
+        let overriders = self.overriders(class_, method);
+        if let Some(exact) = overriders.iter().find(|m| m.param_types() == method.param_types()) {
+            return Some(exact);
+        }
+        let params: Vec<Vec<Ty>> = overriders.iter().map(|m| m.param_tys.clone()).collect();
+        let mut best = overriders.iter().zip(params.iter()).filter(|(_, m)| {
+            params.iter().all(|other| {
+                m.iter().zip(other.iter()).all(|(p, q)| self.is_less_or_equal_unboxed(*p, *q))
+            })
+        });
+        match (best.next(), best.next()) {
+            (Some((m, _)), None) => return Some(m),
+            _ => return None,
+        }
+    }
+
+    /// The fields of a class and its parents that are neither nullable nor initialized where
+    /// they are declared, with the file declaring them. Every constructor has to assign them.
+    pub fn constructor_fields(&self, class_name: Symbol) -> Vec<(&VarDecl, &String)> {
+        let mut fields = vec![];
+        for class_ in self.inheritance(class_name).into_iter().flatten() {
+            for feature in &class_.features {
+                if let Feature::Attribute(attr) = feature {
+                    let type_ = attr.type_.as_ref().unwrap();
+                    if attr.init.is_none() && !is_primitive(type_) && !is_nullable(type_) {
+                        fields.push((attr, &class_.file_name));
+                    }
+                }
+            }
+        }
+        return fields;
     }
 
-    pub fn get_attr_num_recursive(&mut self, child: &Type) -> usize {
-        let mut attr_num = 0;
-        for curr_class in self.inheritance.get(child).unwrap() {
-            for attr_ in &curr_class.features {
-                if let Feature::Attribute(_) = attr_ {
-                    attr_num += 1;
+    /// The closest common ancestor of two types, if there is one.
+    pub fn join(&self, a: Ty, b: Ty) -> Option<Ty> {
+        if self.is_less_or_equal(a, b) {
+            return Some(b);
+        }
+        if self.is_less_or_equal(b, a) {
+            return Some(a);
+        }
+        if a == Ty::Null || b == Ty::Null {
+            let other = if a == Ty::Null { b } else { a };
+            return self.inheritance(other.class_name()?).map(|_| other.nullable());
+        }
+        for id in self.inheritance.get(&self.id(a.class_name()?)?)?.iter().rev() {
+            let ancestor = Ty::Class(self.symbols[id.0]);
+            if self.is_less_or_equal(b.non_null(), ancestor) {
+                if a.is_nullable() || b.is_nullable() {
+                    return Some(ancestor.nullable());
                 }
+                return Some(ancestor);
             }
         }
-        return attr_num;
+        return None;
     }
 }
 
@@ -93,6 +246,7 @@ impl<K: PartialEq + Eq + Hash + Clone, V: PartialEq + Eq + Clone> SymbolTable<K,
     pub fn enter_scope(&mut self) {
         self.scopes.push(Scope {
             type_map: HashMap::new(),
+            positions: HashMap::new(),
         });
     }
 
@@ -109,6 +263,22 @@ impl<K: PartialEq + Eq + Hash + Clone, V: PartialEq + Eq + Clone> SymbolTable<K,
         return None;
     }
 
+    /// Every name in scope, for suggesting one in place of an unknown name.
+    pub fn names(&self) -> impl Iterator<Item = &K> {
+        return self.scopes.iter().flat_map(|s| s.type_map.keys());
+    }
+
+    /// Adds a declaration to the innermost scope, gives where that scope declared `k` before.
+    pub fn declare(&mut self, k: &K, v: &V, position: Position) -> Option<Position> {
+        let scope = self.scopes.last_mut()?;
+        if let Some(first) = scope.positions.get(k) {
+            return Some(*first);
+        }
+        scope.add(k, v);
+        scope.positions.insert(k.clone(), position);
+        return None;
+    }
+
     pub fn add(&mut self, k: &K, v: &V) {
         if let Some(s) = self.scopes.pop() {
             let mut s = s;
@@ -130,6 +300,8 @@ impl<K: PartialEq + Eq + Hash + Clone, V: PartialEq + Eq + Clone> SymbolTable<K,
 #[derive(Debug, Clone)]
 pub struct Scope<K: PartialEq + Eq + Hash, V: PartialEq + Eq> {
     pub type_map: HashMap<K, V>,
+    /// Where the names declared in this scope are, a narrowed name isn't declared again.
+    pub positions: HashMap<K, Position>,
 }
 
 impl<K: PartialEq + Eq + Hash + Clone, V: PartialEq + Eq + Clone> Scope<K, V> {
@@ -0,0 +1,57 @@
+//! Raw `int` and `bool` values are boxed into `Integer` and `Bool` where an object is expected,
+//! and unboxed next to raw values.
+
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn raw_values_are_boxed_and_unboxed() {
+    let src = "class Holder {
+    o : Object? ;
+    n : Integer = 5;
+    flag : bool;
+    public fn set(x:Object) -> Void { o = x; return; };
+    public fn twice(x:Integer) -> int { return x + x.get_val_(); };
+    public fn id(x:int) -> Integer { return x; };
+    public fn big(x:int) -> bool { return x > 40; };
+    public fn on() -> bool { return flag; };
+    public fn turn() -> Void { flag = true; return; };
+};
+class Main {
+    fn main() -> int {
+        let a = 41;
+        print(a.to_string());
+        let h = new Holder;
+        h.set(a);
+        let b: Integer = a;
+        let c = b + 1;
+        print(c.to_string());
+        print(h.twice(3).to_string());
+        print(h.id(9).to_string());
+        let t: bool = h.big(a);
+        if (t) { print(\"t\"); } else { print(\"f\"); }
+        if (t == false) { print(\"F\"); } else { print(\"T\"); }
+        print(t.to_string());
+        let u: bool = false;
+        if (!u) { print(\"u\"); } else { print(\"U\"); }
+        h.turn();
+        while (h.on()) { print(\"w\"); h = new Holder; }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("boxing-values", src), "414269tTtrueuw");
+}
+
+#[test]
+fn raw_bool_is_only_compared_with_equal() {
+    let src = "class Main {
+    fn main() -> int {
+        let t: bool = true;
+        if (t < false) { return 1; } else { return 0; }
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0213"]);
+}
@@ -0,0 +1,59 @@
+//! `is` tests the runtime class of a value and `as` casts it, checked when it goes down.
+
+mod common;
+
+use common::{errors, run};
+
+const SHAPES: &str = "class Shape { public fn name() -> String { return \"shape \"; }; };
+class Square inherits Shape { public fn side() -> String { return \"side \"; }; };
+class Circle inherits Shape {};
+";
+
+#[test]
+fn type_test_and_downcast_see_the_runtime_class() {
+    let main = "class Main {
+    fn main() -> int {
+        let shape: Shape = new Square;
+        if (shape is Square) { print(\"square \"); } else { print(\"other \"); }
+        if (shape is Circle) { print(\"circle \"); } else { print(\"other \"); }
+        let square = shape as Square;
+        print(square.side());
+        let up = square as Shape;
+        print(up.name());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("casts-down", &format!("{}{}", SHAPES, main)), "square other side shape ");
+}
+
+#[test]
+fn failed_downcast_throws_cast_error() {
+    let main = "class Main {
+    fn main() -> int {
+        let shape: Shape = new Circle;
+        try {
+            let square = shape as Square;
+            print(\"cast\");
+        } catch (e: CastError) {
+            print(\"CastError\");
+        }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("casts-error", &format!("{}{}", SHAPES, main)), "CastError");
+}
+
+#[test]
+fn cast_between_unrelated_classes_is_reported() {
+    let main = "class Main {
+    fn main() -> int {
+        let square = new Square;
+        let circle = square as Circle;
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(&format!("{}{}", SHAPES, main)), vec!["E0217"]);
+}
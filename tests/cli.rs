@@ -0,0 +1,106 @@
+//! The subcommands of the strawberry binary.
+
+mod common;
+
+use common::{stderr, stdout, Project};
+
+const MAIN: &str = "class Main {
+    fn main() -> int {
+        print(\"hi\");
+        return 3;
+    };
+};
+";
+
+#[test]
+fn check_writes_nothing() {
+    let project = Project::new("cli-check", &[("src/main.st", MAIN)]);
+    let check = project.strawberry(&["check"]);
+    assert!(check.status.success(), "{}", stderr(&check));
+    assert!(!project.path("build").exists());
+}
+
+#[test]
+fn check_of_a_wrong_program_fails() {
+    let src = "class Main { fn main() -> int { return x; }; };\n";
+    let project = Project::new("cli-check-error", &[("src/main.st", src)]);
+    let check = project.strawberry(&["check", "--message-format", "json"]);
+    assert_eq!(check.status.code(), Some(1));
+    assert!(stdout(&check).contains("E0200"));
+}
+
+#[test]
+fn run_prints_only_the_program_and_forwards_its_exit_code() {
+    let project = Project::new("cli-run", &[("src/main.st", MAIN)]);
+    let run = project.strawberry(&["run"]);
+    assert_eq!(run.status.code(), Some(3), "{}", stderr(&run));
+    assert_eq!(stdout(&run), "hi");
+}
+
+#[test]
+fn warnings_stay_off_the_standard_output() {
+    let src = "class Main {
+    fn main() -> int {
+        print(\"hi\");
+        return 3;
+        print(\"never\");
+    };
+};
+";
+    let project = Project::new("cli-warnings", &[("src/main.st", src)]);
+    let run = project.strawberry(&["run"]);
+    assert_eq!(run.status.code(), Some(3), "{}", stderr(&run));
+    assert_eq!(stdout(&run), "hi");
+    assert!(stderr(&run).contains("W0001"));
+    let ir = project.strawberry(&["build", "--emit", "ir"]);
+    assert!(ir.status.success());
+    assert!(!stdout(&ir).contains("W0001"));
+    assert!(stderr(&ir).contains("W0001"));
+}
+
+#[test]
+fn clean_removes_the_build_directory() {
+    let project = Project::new("cli-clean", &[("src/main.st", MAIN)]);
+    assert!(project.strawberry(&["build"]).status.success());
+    assert!(project.path("build").exists());
+    assert!(project.strawberry(&["clean"]).status.success());
+    assert!(!project.path("build").exists());
+}
+
+#[test]
+fn build_without_main_st_fails() {
+    let project = Project::new("cli-no-main", &[("src/other.st", "class Other {};\n")]);
+    let build = project.strawberry(&["build"]);
+    assert_eq!(build.status.code(), Some(1));
+    assert!(stderr(&build).contains("main.st"));
+    assert!(!project.path("build").exists());
+}
+
+#[test]
+fn check_without_main_st_only_warns() {
+    let project = Project::new("cli-check-no-main", &[("src/other.st", MAIN)]);
+    let check = project.strawberry(&["check"]);
+    assert!(check.status.success(), "{}", stderr(&check));
+    let stderr = stderr(&check);
+    assert!(stderr.contains("There is no main.st") && !stderr.contains("❌"), "{}", stderr);
+}
+
+#[test]
+fn single_file_builds_next_to_where_strawberry_runs() {
+    let project = Project::new("cli-single", &[("foo.st", MAIN)]);
+    let build = project.strawberry(&["build", "foo.st"]);
+    assert!(build.status.success(), "{}", stderr(&build));
+    let output = std::process::Command::new(project.path("foo")).output().unwrap();
+    assert_eq!(stdout(&output), "hi");
+}
+
+#[test]
+fn emit_writes_the_chosen_output() {
+    let project = Project::new("cli-emit", &[("src/main.st", MAIN)]);
+    let ir = project.strawberry(&["build", "--emit", "ir"]);
+    assert!(ir.status.success(), "{}", stderr(&ir));
+    assert!(stdout(&ir).contains("Main"));
+    let asm = project.strawberry(&["build", "--emit", "asm", "-o", "out.s"]);
+    assert!(asm.status.success(), "{}", stderr(&asm));
+    assert!(std::fs::read_to_string(project.path("out.s")).unwrap().contains("Main.main:"));
+}
@@ -0,0 +1,120 @@
+//! What the tests share: compiling a program of one file and running what it compiles to.
+
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use strawberry::{check, emit_asm, parse_source, Diagnostics, TypedProgram};
+
+/// The codes of the diagnostics, in the order they were found.
+pub fn codes(diagnostics: &Diagnostics) -> Vec<&'static str> {
+    return diagnostics.items.iter().map(|d| d.code).collect();
+}
+
+pub fn compile(src: &str) -> Result<TypedProgram, Diagnostics> {
+    let program = parse_source(src)?;
+    return check(program);
+}
+
+/// The codes of the errors that stop `src`, it must not compile.
+pub fn errors(src: &str) -> Vec<&'static str> {
+    match compile(src) {
+        Ok(_) => panic!("the program compiled"),
+        Err(diagnostics) => return codes(&diagnostics),
+    }
+}
+
+/// The codes of the warnings of `src`, it must compile.
+pub fn warnings(src: &str) -> Vec<&'static str> {
+    match compile(src) {
+        Ok(typed) => return codes(&typed.warnings),
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    }
+}
+
+/// What the program `src` prints, built with gcc like the driver does. `name` keeps the
+/// files of tests running at the same time apart.
+pub fn run(name: &str, src: &str) -> String {
+    match compile(src) {
+        Ok(typed) => return run_typed(name, &typed),
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    }
+}
+
+/// What the checked program `typed` prints, like `run`.
+pub fn run_typed(name: &str, typed: &TypedProgram) -> String {
+    let dir: PathBuf = std::env::temp_dir().join(format!("strawberry-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (asm, obj, exe) = (dir.join("a.s"), dir.join("a.o"), dir.join("a.out"));
+    fs::write(&asm, emit_asm(typed)).unwrap();
+    let assembled = Command::new("gcc").arg("-m64").arg("-c").arg(&asm).arg("-o").arg(&obj).status();
+    assert!(assembled.unwrap().success(), "gcc failed to assemble {}", asm.display());
+    let linked = Command::new("gcc")
+        .args(["-no-pie", "-static", "-m64"])
+        .arg(&obj)
+        .arg("-o")
+        .arg(&exe)
+        .output()
+        .unwrap();
+    assert!(linked.status.success(), "{}", String::from_utf8_lossy(&linked.stderr));
+    let output = Command::new(&exe).output().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    return String::from_utf8_lossy(&output.stdout).replace('\0', "");
+}
+
+/// A project directory under the temp directory, removed when the test is done with it,
+/// even when it fails.
+pub struct Project {
+    pub dir: PathBuf,
+}
+
+impl Project {
+    /// A fresh project named after `name`, with `files` written under it.
+    pub fn new(name: &str, files: &[(&str, &str)]) -> Project {
+        let dir = std::env::temp_dir().join(format!("strawberry-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (path, text) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        return Project { dir };
+    }
+
+    /// The strawberry binary with `args`, in the project and without a `CC` from the
+    /// environment.
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_strawberry"));
+        command.args(args).current_dir(&self.dir).env_remove("CC");
+        return command;
+    }
+
+    /// Runs the strawberry binary in the project.
+    pub fn strawberry(&self, args: &[&str]) -> Output {
+        return self.command(args).output().unwrap();
+    }
+
+    pub fn path(&self, path: &str) -> PathBuf {
+        return self.dir.join(path);
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The standard output of a program, without the NULs its strings are padded with.
+pub fn stdout(output: &Output) -> String {
+    return String::from_utf8_lossy(&output.stdout).replace('\0', "");
+}
+
+pub fn stderr(output: &Output) -> String {
+    return String::from_utf8_lossy(&output.stderr).to_string();
+}
@@ -0,0 +1,49 @@
+//! Compiles programs in-process through the library, from source text to assembly.
+
+mod common;
+
+use common::{codes, compile, errors};
+use strawberry::{check, emit_asm, parse_source, parse_sources, Source};
+
+#[test]
+fn parse_error() {
+    let diagnostics = match parse_source("class Main {\n    public fn main() -> void {\n") {
+        Ok(_) => panic!("a class without its end parsed"),
+        Err(diagnostics) => diagnostics,
+    };
+    assert!(codes(&diagnostics).contains(&"E0004"), "{:?}", codes(&diagnostics));
+    assert!(diagnostics.items.iter().all(|d| d.file_name == "main.st"));
+}
+
+#[test]
+fn check_error() {
+    let src = "class Main {\n    public fn main() -> void {\n        print(missing);\n    };\n};\n";
+    assert_eq!(errors(src), vec!["E0200"]);
+}
+
+#[test]
+fn emitted_asm() {
+    let src = "class Main {\n    public fn main() -> void {\n        print(\"hi\");\n    };\n};\n";
+    let typed = match compile(src) {
+        Ok(typed) => typed,
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    };
+    let asm = emit_asm(&typed);
+    assert!(asm.contains("Main.main:"));
+    assert!(asm.contains("Main_dispatch_table:"));
+    assert!(asm.contains("String_dispatch_table:"));
+}
+
+#[test]
+fn file_named_like_the_std_is_linted() {
+    let src = "class Main {\n    public fn main() -> void {\n        let unused: int = 1;\n    };\n};\n";
+    let program = match parse_sources(vec![Source::new("std/main.st", src)]) {
+        Ok(program) => program,
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    };
+    let typed = match check(program) {
+        Ok(typed) => typed,
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    };
+    assert_eq!(codes(&typed.warnings), vec!["W0002"]);
+}
@@ -0,0 +1,93 @@
+//! Constructors are overloaded like methods, and `new C` runs the one without parameters.
+
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn constructor_takes_a_subtype_of_its_parameter() {
+    let src = "class Shape { public fn name() -> String { return \"shape \"; }; };
+class Square inherits Shape { public fn name() -> String { return \"square \"; }; };
+
+class Holder {
+    item: Shape;
+    constructor() { item = new Shape; };
+    constructor(s: Shape) { item = s; };
+    public fn get() -> Shape { return item; };
+};
+
+class Main {
+    fn main() -> int {
+        let sq = new Square;
+        print(new Holder(sq).get().name());
+        print(new Holder.get().name());
+        print(new Holder().get().name());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("constructors-subtype", src), "square shape shape ");
+}
+
+#[test]
+fn constructor_takes_the_most_specific_overload() {
+    let src = "class Shape {};
+class Square inherits Shape {};
+
+class Holder {
+    kind: int;
+    constructor(s: Shape) { kind = 1; };
+    constructor(s: Square) { kind = 2; };
+    public fn get() -> int { return kind; };
+};
+
+class Main {
+    fn main() -> int {
+        let s: Shape = new Square;
+        print(new Integer(new Holder(s).get()).to_string());
+        print(new Integer(new Holder(new Square).get()).to_string());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("constructors-specific", src), "12");
+}
+
+#[test]
+fn ambiguous_constructor_call_is_reported() {
+    let src = "class A {};
+class C inherits A {};
+
+class Pair {
+    constructor(a: A, c: C) { };
+    constructor(c: C, a: A) { };
+};
+
+class Main {
+    fn main() -> int {
+        let p = new Pair(new C, new C);
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0208"]);
+}
+
+#[test]
+fn constructor_call_that_fits_no_constructor_is_reported() {
+    let src = "class Shape {};
+
+class Holder {
+    constructor(s: Shape) { };
+};
+
+class Main {
+    fn main() -> int {
+        let h = new Holder;
+        let k = new Holder(1);
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0209", "E0209"]);
+}
@@ -0,0 +1,117 @@
+//! Every independent error of a compile is reported, in the order of the file.
+
+mod common;
+
+use common::{codes, compile, errors};
+use strawberry::parse_source;
+
+#[test]
+fn syntax_error_of_each_statement_is_reported() {
+    let src = "class Main {
+    fn main() -> int {
+        let a: int = ;
+        let b: int = 1 +;
+        return 0;
+    };
+};
+";
+    let diagnostics = match parse_source(src) {
+        Ok(_) => panic!("the program parsed"),
+        Err(diagnostics) => diagnostics,
+    };
+    assert_eq!(codes(&diagnostics), vec!["E0002", "E0002"]);
+    let starts: Vec<usize> = diagnostics.items.iter().map(|d| d.position.unwrap().start).collect();
+    assert!(starts[0] < starts[1]);
+}
+
+#[test]
+fn body_is_checked_past_a_let_whose_type_is_unknown() {
+    let src = "class Main {
+    fn main() -> int {
+        let x = nothere();
+        let y = x + 1;
+        print(y);
+        let z: int = \"s\";
+        let w: int = undefined_name;
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0203", "E0211", "E0200"]);
+}
+
+#[test]
+fn errors_of_several_classes_are_all_reported() {
+    let src = "class A {
+    fn f() -> int { return missing; };
+};
+
+class Main {
+    fn main() -> int { return \"zero\"; };
+};
+";
+    assert_eq!(errors(src), vec!["E0200", "E0222"]);
+}
+
+#[test]
+fn duplicate_declarations_point_at_both() {
+    let src = "class Main {
+    a: int;
+    a: int;
+    fn f(x: int, x: int) -> int { return x; };
+    fn f(y: int, z: int) -> int { return y; };
+    fn main() -> int { return 0; };
+};
+";
+    let diagnostics = match compile(src) {
+        Ok(_) => panic!("the program compiled"),
+        Err(diagnostics) => diagnostics,
+    };
+    let mut found = codes(&diagnostics);
+    found.sort();
+    assert_eq!(found, vec!["E0118", "E0119", "E0120"]);
+    assert!(diagnostics.items.iter().all(|d| d.labels.len() == 1));
+}
+
+#[test]
+fn local_declared_twice_in_a_scope_is_reported() {
+    let src = "class Main {
+    fn main() -> int {
+        let k: int = 1;
+        let k: int = 2;
+        if (k > 0) { let k: int = 3; } else { }
+        return k;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0225"]);
+}
+
+#[test]
+fn catch_variable_is_declared_in_the_scope_of_its_body() {
+    let src = "class Main {
+    fn main() -> int {
+        try {
+            print(\"x\");
+        } catch (e: Exception) {
+            let e: int = 1;
+        }
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0225"]);
+}
+
+#[test]
+fn class_inheriting_from_a_cycle_is_reported_without_hanging() {
+    let src = "class A inherits B {};
+class B inherits A {};
+class C inherits A {};
+
+class Main {
+    fn main() -> int { return 0; };
+};
+";
+    assert_eq!(errors(src), vec!["E0105", "E0105", "E0105"]);
+}
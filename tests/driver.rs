@@ -0,0 +1,60 @@
+//! Assembling and linking with the C compiler, and how its failures fail the build.
+
+mod common;
+
+use common::{stderr, Project};
+
+const MAIN: &str = "class Main {
+    fn main() -> int {
+        print(\"hi\");
+        return 0;
+    };
+};
+";
+
+#[test]
+fn missing_c_compiler_fails_the_build() {
+    let project = Project::new("driver-cc", &[("src/main.st", MAIN)]);
+    let build = project.strawberry(&["build", "--cc", "strawberry-no-such-cc"]);
+    assert_eq!(build.status.code(), Some(1));
+    let message = stderr(&build);
+    assert!(message.contains("strawberry-no-such-cc could not be started"), "{}", message);
+    assert!(!message.contains("Congratulations you successfully built"), "{}", message);
+}
+
+#[test]
+fn c_compiler_is_taken_from_cc() {
+    let project = Project::new("driver-env", &[("src/main.st", MAIN)]);
+    let build = project.command(&["build"]).env("CC", "strawberry-no-such-cc").output().unwrap();
+    assert_eq!(build.status.code(), Some(1));
+    assert!(stderr(&build).contains("strawberry-no-such-cc"));
+}
+
+#[test]
+fn missing_linker_fails_the_build() {
+    let project = Project::new("driver-linker", &[("src/main.st", MAIN)]);
+    let build = project.strawberry(&["build", "--linker", "strawberry-no-such-linker"]);
+    assert_eq!(build.status.code(), Some(1));
+    assert!(stderr(&build).contains("--linker"));
+    assert!(!project.path("build").join(project.dir.file_name().unwrap()).exists());
+}
+
+#[test]
+fn assembler_error_names_its_method() {
+    let src = "class Main {
+    fn broken() -> int {
+        __asm__(\"movq %rax\");
+        return 0;
+    };
+
+    fn main() -> int {
+        return self.broken();
+    };
+};
+";
+    let project = Project::new("driver-asm", &[("src/main.st", src)]);
+    let build = project.strawberry(&["build"]);
+    assert_eq!(build.status.code(), Some(1));
+    let stderr = stderr(&build);
+    assert!(stderr.contains("in the assembly of Main.broken"), "{}", stderr);
+}
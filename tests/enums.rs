@@ -0,0 +1,74 @@
+//! Enums, their variants and `match`.
+
+mod common;
+
+use common::{errors, run};
+
+const SHAPE: &str = "enum Shape {
+    Circle(r: int),
+    Rect(w: int, h: int),
+    Empty;
+};
+";
+
+#[test]
+fn match_binds_fields_and_tests_guards() {
+    let src = format!(
+        "{}class Main {{
+    fn area(s: Shape) -> int {{
+        return match s {{
+            Circle(r) if r > 10 -> 100,
+            Circle(r) -> r * r,
+            Rect(w, h) -> w * h,
+            Empty -> 0,
+        }};
+    }};
+
+    fn main() -> int {{
+        print(Shape::Rect(2, 3).to_string());
+        print(new Integer(area(Shape::Rect(2, 3))).to_string());
+        print(new Integer(area(Shape::Circle(3))).to_string());
+        print(new Integer(area(Shape::Circle(11))).to_string());
+        print(new Integer(area(Shape::Empty)).to_string());
+        return 0;
+    }};
+}};
+",
+        SHAPE
+    );
+    assert_eq!(run("enums-match", &src), "Rect691000");
+}
+
+#[test]
+fn match_must_cover_every_variant() {
+    let src = format!(
+        "{}class Main {{
+    fn main() -> int {{
+        return match Shape::Empty {{
+            Circle(r) -> r,
+            Rect(w, _) -> w,
+        }};
+    }};
+}};
+",
+        SHAPE
+    );
+    assert_eq!(errors(&src), vec!["E0221"]);
+}
+
+#[test]
+fn pattern_binds_a_name_once() {
+    let src = format!(
+        "{}class Main {{
+    fn main() -> int {{
+        return match Shape::Empty {{
+            Rect(x, x) -> x,
+            _ -> 0,
+        }};
+    }};
+}};
+",
+        SHAPE
+    );
+    assert_eq!(errors(&src), vec!["E0225"]);
+}
@@ -0,0 +1,84 @@
+//! `throw`, `try`, `catch` and `finally`, and the runtime errors they catch.
+
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn catch_takes_the_first_fitting_clause_and_finally_runs() {
+    let src = "class IOError inherits Exception {
+    constructor(msg_: String) {
+        msg = msg_;
+    };
+};
+
+class Main {
+    fn main() -> int {
+        try {
+            throw new IOError(\"disk is full\");
+        } catch (e: IOError) {
+            print(e.get_message());
+        } catch (e: Exception) {
+            print(\"something else\");
+        } finally {
+            print(\" always runs\");
+        }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("exceptions-catch", src), "disk is full always runs");
+}
+
+#[test]
+fn division_by_zero_is_catchable() {
+    let src = "class Main {
+    fn main() -> int {
+        let zero: int = 0;
+        try {
+            print(new Integer(1 / zero).to_string());
+        } catch (e: Exception) {
+            print(\"caught\");
+        }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("exceptions-div", src), "caught");
+}
+
+#[test]
+fn division_rounds_toward_zero() {
+    let src = "class Main {
+    fn main() -> int {
+        let a: int = 0 - 7;
+        let b: int = 0 - 7;
+        print(new Integer(a / 2).to_string());
+        print(\" \");
+        print(new Integer(100 / b).to_string());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("exceptions-idiv", src), "-3 -14");
+}
+
+#[test]
+fn catch_after_a_catch_of_its_superclass_is_unreachable() {
+    let src = "class IOError inherits Exception {};
+
+class Main {
+    fn main() -> int {
+        try {
+            print(\"x\");
+        } catch (e: Exception) {
+            print(\"y\");
+        } catch (e: IOError) {
+            print(\"z\");
+        }
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0226"]);
+}
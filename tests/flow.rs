@@ -0,0 +1,165 @@
+//! The control-flow checks: every path of a method returns and every local is assigned
+//! before it is read.
+
+mod common;
+
+use common::{errors, run, warnings};
+
+#[test]
+fn method_may_return_from_both_branches_and_an_endless_loop() {
+    let src = "class Grader {
+    public fn grade(x: int) -> String {
+        if (x > 50) {
+            return \"pass \";
+        } else {
+            return \"fail \";
+        }
+    };
+
+    public fn sign(x: int) -> int {
+        while (true) {
+            if (x > 0) { return 1; } else { return 0; }
+        }
+    };
+};
+
+class Main {
+    fn main() -> int {
+        let g = new Grader;
+        print(g.grade(70));
+        print(g.grade(10));
+        print(g.sign(3).to_string());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("flow-returns", src), "pass fail 1");
+}
+
+#[test]
+fn method_that_may_run_off_its_end_is_reported() {
+    let src = "class Main {
+    fn sign(x: int) -> int {
+        if (x > 0) { return 1; } else { print(\"none\"); }
+    };
+
+    fn main() -> int {
+        return self.sign(1);
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0117"]);
+}
+
+#[test]
+fn code_after_a_return_is_unreachable() {
+    let src = "class Main {
+    fn main() -> int {
+        return 0;
+        print(\"never\");
+    };
+};
+";
+    assert_eq!(warnings(src), vec!["W0001"]);
+}
+
+#[test]
+fn local_assigned_on_every_path_may_be_read() {
+    let src = "class Node {
+    val: int;
+    constructor(v: int) { val = v; };
+    public fn get() -> int { return val; };
+};
+
+class Box2 {
+    item: Node;
+    constructor(n: int) {
+        if (n > 0) { item = new Node(n); } else { item = new Node(0); }
+    };
+    public fn get() -> Node { return item; };
+};
+
+class Main {
+    fn main() -> int {
+        let x: Node;
+        let k: int;
+        if (1 > 0) { x = new Node(4); k = 2; } else { return 1; }
+        print(x.get().to_string());
+        print(k.to_string());
+        print(new Box2(7).get().get().to_string());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("flow-assigned", src), "427");
+}
+
+#[test]
+fn local_read_before_it_is_assigned_is_reported() {
+    let src = "class Main {
+    fn main() -> int {
+        let k: int;
+        if (1 > 0) { k = 2; } else { print(\"no\"); }
+        return k;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0223"]);
+}
+
+#[test]
+fn constructor_leaving_a_field_unassigned_is_reported() {
+    let src = "class Node {};
+
+class Holder {
+    item: Node;
+    constructor(n: int) {
+        if (n > 0) { item = new Node; } else { print(\"none\"); }
+    };
+};
+
+class Main {
+    fn main() -> int {
+        let h = new Holder(1);
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0115"]);
+}
+
+#[test]
+fn every_field_a_constructor_leaves_unassigned_is_reported() {
+    let src = "class Node {};
+
+class Pair {
+    left: Node;
+    right: Node;
+    constructor(n: int) { print(\"none\"); };
+};
+
+class Main {
+    fn main() -> int {
+        let p = new Pair(1);
+        return 0;
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0115", "E0115"]);
+}
+
+#[test]
+fn every_field_of_a_class_without_constructor_is_reported() {
+    let src = "class Node {};
+
+class Pair {
+    left: Node;
+    right: Node;
+};
+
+class Main {
+    fn main() -> int { return 0; };
+};
+";
+    assert_eq!(errors(src), vec!["E0114", "E0114"]);
+}
@@ -0,0 +1,100 @@
+//! The lints, which warn about code that checks but is probably wrong, and their levels.
+
+mod common;
+
+use common::{codes, warnings, Project};
+use strawberry::{check, parse_source, Level, LintLevels};
+
+#[test]
+fn private_member_never_used_is_reported() {
+    let src = "class Counter {
+    count: int = 0;
+    fn helper() -> int { return 1; };
+};
+
+class Main {
+    fn main() -> int { let c = new Counter; return 0; };
+};
+";
+    assert_eq!(warnings(src), vec!["W0002", "W0003", "W0003"]);
+}
+
+#[test]
+fn local_named_like_a_field_is_reported() {
+    let src = "class Main {
+    public count: int = 0;
+    fn main() -> int {
+        let count: int = 1;
+        return count;
+    };
+};
+";
+    assert_eq!(warnings(src), vec!["W0004"]);
+}
+
+#[test]
+fn test_of_literals_is_reported() {
+    let src = "class Main {
+    fn main() -> int {
+        if (1 > 2) { return 1; } else { return 0; }
+    };
+};
+";
+    assert_eq!(warnings(src), vec!["W0005"]);
+}
+
+#[test]
+fn isnull_on_new_is_reported() {
+    let src = "class Main {
+    fn main() -> int {
+        if (isnull(new Main)) { return 1; } else { return 0; }
+    };
+};
+";
+    assert_eq!(warnings(src), vec!["W0006"]);
+}
+
+#[test]
+fn allowed_lint_is_quiet_and_denied_lint_is_an_error() {
+    let src = "class Main {
+    fn main() -> int {
+        let unused: int = 1;
+        return 0;
+    };
+};
+";
+    let mut allow = LintLevels::new();
+    allow.set("unused_variables", Level::Allow).unwrap();
+    let typed = match check(parse_source(src).unwrap().with_lints(allow)) {
+        Ok(typed) => typed,
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    };
+    assert!(typed.warnings.items.is_empty());
+
+    let mut deny = LintLevels::new();
+    deny.set("unused_variables", Level::Deny).unwrap();
+    match check(parse_source(src).unwrap().with_lints(deny)) {
+        Ok(_) => panic!("a denied lint let the program through"),
+        Err(diagnostics) => assert_eq!(codes(&diagnostics), vec!["W0002"]),
+    }
+}
+
+#[test]
+fn lint_levels_come_from_the_manifest_and_the_command_line() {
+    let src = "class Main {
+    fn main() -> int {
+        let unused: int = 1;
+        return 0;
+    };
+};
+";
+    let project = Project::new(
+        "lints-levels",
+        &[("strawberry.toml", "[lints]\nunused_variables = \"deny\"\n"), ("src/main.st", src)],
+    );
+    assert_eq!(project.strawberry(&["check"]).status.code(), Some(1));
+    assert!(project.strawberry(&["check", "-A", "unused_variables"]).status.success());
+    std::fs::remove_file(project.path("strawberry.toml")).unwrap();
+    assert!(project.strawberry(&["check"]).status.success());
+    assert_eq!(project.strawberry(&["check", "-D", "warnings"]).status.code(), Some(1));
+}
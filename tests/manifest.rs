@@ -0,0 +1,88 @@
+//! `strawberry.toml`, which `build` reads and `new` writes.
+
+mod common;
+
+use common::{stderr, stdout, Project};
+
+const MAIN: &str = "class Main {
+    fn main() -> int {
+        print(\"hi\");
+        return 0;
+    };
+};
+";
+
+#[test]
+fn new_project_builds_and_runs() {
+    let project = Project::new("manifest-new", &[]);
+    assert!(project.strawberry(&["new", "hello"]).status.success());
+    let hello = project.path("hello");
+    assert!(hello.join("strawberry.toml").exists());
+    let build = project.command(&["build"]).current_dir(&hello).output().unwrap();
+    assert!(build.status.success(), "{}", stderr(&build));
+    assert!(hello.join("build/hello").exists());
+    let run = project.command(&["run"]).current_dir(&hello).output().unwrap();
+    assert!(run.status.success(), "{}", stderr(&run));
+    assert_eq!(stdout(&run), "Hello world!");
+}
+
+#[test]
+fn output_and_entry_come_from_the_manifest() {
+    let project = Project::new(
+        "manifest-output",
+        &[
+            (
+                "strawberry.toml",
+                "[package]\nname = \"app\"\nversion = \"1.2.3\"\nentry = \"Start.go\"\noutput = \"app\"\n",
+            ),
+            (
+                "src/main.st",
+                "class Start {
+    fn go() -> int {
+        print(\"go\");
+        return 0;
+    };
+};
+",
+            ),
+        ],
+    );
+    let build = project.strawberry(&["build"]);
+    assert!(build.status.success(), "{}", stderr(&build));
+    let output = std::process::Command::new(project.path("build/app")).output().unwrap();
+    assert_eq!(stdout(&output), "go");
+}
+
+#[test]
+fn optimization_level_other_than_zero_fails_the_build() {
+    let project = Project::new(
+        "manifest-opt",
+        &[("strawberry.toml", "[package]\nopt-level = 2\n"), ("src/main.st", MAIN)],
+    );
+    let build = project.strawberry(&["build"]);
+    assert_eq!(build.status.code(), Some(1));
+    assert!(stderr(&build).contains("can only be 0"));
+}
+
+#[test]
+fn version_of_other_than_three_numbers_fails_the_build() {
+    let project = Project::new(
+        "manifest-version",
+        &[("strawberry.toml", "[package]\nversion = \"1.x\"\n"), ("src/main.st", MAIN)],
+    );
+    let build = project.strawberry(&["build"]);
+    assert_eq!(build.status.code(), Some(1));
+    assert!(stderr(&build).contains("three numbers"));
+}
+
+#[test]
+fn executable_is_named_after_the_package() {
+    let project = Project::new(
+        "manifest-name",
+        &[("strawberry.toml", "[package]\nname = \"greet\"\nversion = \"2.0.1\"\n"), ("src/main.st", MAIN)],
+    );
+    let build = project.strawberry(&["build"]);
+    assert!(build.status.success(), "{}", stderr(&build));
+    assert!(stderr(&build).contains("greet 2.0.1"));
+    assert!(project.path("build/greet").exists());
+}
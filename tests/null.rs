@@ -0,0 +1,94 @@
+//! Nullable types `T?`, `?.`, `??`, and the narrowing of a variable a branch proves non-null.
+
+mod common;
+
+use common::{errors, run};
+
+const NODE: &str = "class Node {
+    val: int = 0;
+    public next: Node?;
+
+    constructor(val_: int) { val = val_; };
+
+    public fn get_val() -> int { return val; };
+    public fn get_next() -> Node? { return next; };
+};
+";
+
+#[test]
+fn nullable_values_are_used_through_safe_calls_and_defaults() {
+    let main = "class Main {
+    fn main() -> int {
+        let n = new Node(1);
+        let e: Node? = null;
+        let d = e ?? n;
+        print(new Integer(d.get_val()).to_string());
+        let f = e?.get_next();
+        if (isnull(f)) { print(\" none\"); } else { print(\" some\"); }
+        let k = e?.next;
+        if (isnull(k)) { print(\" none\"); } else { print(\" some\"); }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("null-safe", &format!("{}{}", NODE, main)), "1 none none");
+}
+
+#[test]
+fn local_proven_non_null_is_narrowed() {
+    let main = "class Main {
+    fn main() -> int {
+        let h: Node? = new Node(3);
+        while (!isnull(h)) {
+            print(new Integer(h.get_val()).to_string());
+            h = h.get_next();
+        }
+        let k: Node? = new Node(4);
+        if (isnull(k)) { print(\"null\"); } else { print(new Integer(k.get_val()).to_string()); }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("null-narrow", &format!("{}{}", NODE, main)), "34");
+}
+
+#[test]
+fn nullable_value_used_without_safe_call_is_reported() {
+    let main = "class Main {
+    fn main() -> int {
+        let e: Node? = null;
+        return e.get_val();
+    };
+};
+";
+    assert_eq!(errors(&format!("{}{}", NODE, main)), vec!["E0215"]);
+}
+
+#[test]
+fn field_is_not_narrowed() {
+    let src = "class Node {
+    val: int = 0;
+    public fn get_val() -> int { return val; };
+};
+
+class Holder {
+    item: Node?;
+
+    public fn reset() -> Void { item = null; return; };
+
+    public fn get() -> int {
+        if (!isnull(item)) {
+            self.reset();
+            return item.get_val();
+        } else {
+            return 0;
+        }
+    };
+};
+
+class Main {
+    fn main() -> int { return new Holder.get(); };
+};
+";
+    assert_eq!(errors(src), vec!["E0215"]);
+}
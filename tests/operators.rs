@@ -0,0 +1,79 @@
+//! Operators on classes call the method the left operand's class defines for them.
+
+mod common;
+
+use common::{errors, run};
+
+const MONEY: &str = "class Money {
+    cents: int = 0;
+    constructor(cents_: int) { cents = cents_; };
+    public fn get_cents() -> int { return cents; };
+    public fn add(other: Money) -> Money { return new Money(cents + other.get_cents()); };
+    public fn minus(other: Money) -> Money { return new Money(cents - other.get_cents()); };
+    public fn less(other: Money) -> Bool { return cents < other.get_cents(); };
+    public fn less(other: int) -> Bool { return cents < other; };
+};
+";
+
+#[test]
+fn class_operators_call_its_methods() {
+    let main = "class Main {
+    fn main() -> int {
+        let price = new Money(250);
+        let tax = new Money(50);
+        let total = price + tax - new Money(100);
+        print(new Integer(total.get_cents()).to_string());
+        if (tax < price) { print(\" less\"); } else { print(\" more\"); }
+        if (price < 100) { print(\" less\"); } else { print(\" more\"); }
+        return 0;
+    };
+};
+";
+    assert_eq!(run("operators-money", &format!("{}{}", MONEY, main)), "200 less more");
+}
+
+#[test]
+fn operands_run_left_to_right() {
+    let src = "class Loud {
+    name: String = \"\";
+    constructor(name_: String) { name = name_; };
+    public fn add(other: Loud) -> Loud { return self; };
+};
+
+class Main {
+    fn make(name: String) -> Loud { print(name); return new Loud(name); };
+
+    fn main() -> int {
+        let l = self.make(\"a\") + self.make(\"b\");
+        return 0;
+    };
+};
+";
+    assert_eq!(run("operators-order", src), "ab");
+}
+
+#[test]
+fn operator_is_not_looked_up_on_the_right_operand() {
+    let main = "class Main {
+    fn main() -> int {
+        if (100 < new Money(250)) { return 1; } else { return 0; }
+    };
+};
+";
+    assert_eq!(errors(&format!("{}{}", MONEY, main)), vec!["E0213"]);
+}
+
+#[test]
+fn comparison_method_not_returning_bool_is_reported() {
+    let src = "class Odd {
+    public fn less(other: Odd) -> int { return 1; };
+};
+
+class Main {
+    fn main() -> int {
+        if (new Odd < new Odd) { return 1; } else { return 0; }
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0113"]);
+}
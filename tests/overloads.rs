@@ -0,0 +1,95 @@
+//! Methods overloaded by their parameter types.
+
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn call_takes_the_overload_of_its_argument_types() {
+    let src = "class Printer {
+    public fn show(x: int) -> Void { print(\"int \"); return; };
+    public fn show(s: String) -> Void { print(s); return; };
+};
+
+class Main {
+    fn main() -> int {
+        let p = new Printer;
+        p.show(1);
+        p.show(\"str\");
+        return 0;
+    };
+};
+";
+    assert_eq!(run("overloads-types", src), "int str");
+}
+
+#[test]
+fn call_takes_the_most_specific_overload() {
+    let src = "class Shape {};
+class Square inherits Shape {};
+
+class Painter {
+    public fn paint(s: Shape) -> int { return 1; };
+    public fn paint(s: Square) -> int { return 2; };
+};
+
+class Main {
+    fn main() -> int {
+        let p = new Painter;
+        let s: Shape = new Square;
+        print(new Integer(p.paint(s)).to_string());
+        print(new Integer(p.paint(new Square)).to_string());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("overloads-specific", src), "12");
+}
+
+#[test]
+fn ambiguous_call_is_reported() {
+    let src = "class A {};
+class B {};
+class C inherits A {};
+
+class Painter {
+    public fn paint(a: A, c: C) -> int { return 1; };
+    public fn paint(c: C, a: A) -> int { return 2; };
+};
+
+class Main {
+    fn main() -> int {
+        let p = new Painter;
+        return p.paint(new C, new C);
+    };
+};
+";
+    assert_eq!(errors(src), vec!["E0208"]);
+}
+
+#[test]
+fn overloads_whose_names_run_together_stay_apart() {
+    let src = "class Node {};
+class Node_ {};
+
+class A {
+    public fn foo(a: Node?) -> int { return 1; };
+    public fn foo(a: Node_) -> int { return 2; };
+    public fn foo_int() -> int { return 3; };
+    public fn foo(x: int) -> int { return 4; };
+};
+
+class Main {
+    fn main() -> int {
+        let a = new A;
+        let n: Node? = null;
+        print(new Integer(a.foo(n)).to_string());
+        print(new Integer(a.foo(new Node_)).to_string());
+        print(new Integer(a.foo_int()).to_string());
+        print(new Integer(a.foo(5)).to_string());
+        return 0;
+    };
+};
+";
+    assert_eq!(run("overloads-mangle", src), "1234");
+}
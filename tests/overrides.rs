@@ -0,0 +1,60 @@
+//! Overrides may return a subtype and take supertypes of what the method they override does.
+
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn covariant_return_and_contravariant_parameter_dispatch() {
+    let src = "class Shape { public fn name() -> String { return \"shape \"; }; };
+class Square inherits Shape { public fn name() -> String { return \"square \"; }; };
+
+class Painter {
+    public fn make() -> Shape { return new Shape; };
+    public fn draw(s: Square) -> String { print(\"painter:\"); return s.name(); };
+};
+
+class Fancy inherits Painter {
+    public fn make() -> Square { return new Square; };
+    public fn draw(s: Shape) -> String { print(\"fancy:\"); return s.name(); };
+};
+
+class Main {
+    fn main() -> int {
+        let p: Painter = new Fancy;
+        print(p.make().name());
+        print(p.draw(new Square));
+        let f = new Fancy;
+        let sq: Square = f.make();
+        print(sq.name());
+        print(f.draw(new Shape));
+        print(new Painter.draw(new Square));
+        return 0;
+    };
+};
+";
+    assert_eq!(
+        run("overrides-variance", src),
+        "square fancy:square square fancy:shape painter:square "
+    );
+}
+
+#[test]
+fn override_returning_a_supertype_is_reported() {
+    let src = "class Shape {};
+class Square inherits Shape {};
+
+class Painter {
+    public fn make() -> Square { return new Square; };
+};
+
+class Fancy inherits Painter {
+    public fn make() -> Shape { return new Shape; };
+};
+
+class Main {
+    fn main() -> int { return 0; };
+};
+";
+    assert_eq!(errors(src), vec!["E0108"]);
+}
@@ -0,0 +1,75 @@
+//! Every `.st` file under `src/` is compiled, and `package a.b;` names its classes `a.b.C`.
+
+mod common;
+
+use common::{codes, run_typed, stderr, stdout, Project};
+use strawberry::{check, parse_sources, Source};
+
+const GEO: &str = "package geo;
+class Circle { public fn name() -> String { return \"geo \"; }; };
+";
+
+const DRAW: &str = "package draw;
+class Circle { public fn name() -> String { return \"draw \"; }; };
+";
+
+#[test]
+fn packages_may_have_classes_of_the_same_name() {
+    let main = "from \"geo\" import Circle;
+class Main {
+    fn main() -> int {
+        let c: Circle = new Circle;
+        let d: draw.Circle = new draw.Circle;
+        print(c.name());
+        print(d.name());
+        return 0;
+    };
+};
+";
+    let sources = vec![
+        Source::new("src/draw/shapes.st", DRAW),
+        Source::new("src/geo/shapes.st", GEO),
+        Source::new("src/main.st", main),
+    ];
+    let typed = match parse_sources(sources).and_then(check) {
+        Ok(typed) => typed,
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    };
+    assert_eq!(run_typed("packages-same-name", &typed), "geo draw ");
+}
+
+#[test]
+fn class_of_another_package_needs_its_qualified_name() {
+    let main = "class Main {
+    fn main() -> int {
+        let c = new Circle;
+        return 0;
+    };
+};
+";
+    let sources = vec![Source::new("src/geo/shapes.st", GEO), Source::new("src/main.st", main)];
+    let diagnostics = match parse_sources(sources).and_then(check) {
+        Ok(_) => panic!("the program compiled"),
+        Err(diagnostics) => diagnostics,
+    };
+    assert_eq!(codes(&diagnostics), vec!["E0201"]);
+}
+
+#[test]
+fn files_of_sibling_directories_are_all_compiled() {
+    let main = "class Main {
+    fn main() -> int {
+        print(new geo.Circle.name());
+        print(new draw.Circle.name());
+        return 0;
+    };
+};
+";
+    let project = Project::new(
+        "packages-discovery",
+        &[("src/draw/shapes.st", DRAW), ("src/geo/shapes.st", GEO), ("src/main.st", main)],
+    );
+    let run = project.strawberry(&["run"]);
+    assert!(run.status.success(), "{}", stderr(&run));
+    assert_eq!(stdout(&run), "geo draw ");
+}
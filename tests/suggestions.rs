@@ -0,0 +1,64 @@
+//! A misspelled name gets a hint naming the closest one in scope.
+
+mod common;
+
+use common::compile;
+
+/// The hints of the errors that stop `src`.
+fn helps(src: &str) -> Vec<Option<String>> {
+    match compile(src) {
+        Ok(_) => panic!("the program compiled"),
+        Err(diagnostics) => return diagnostics.items.iter().map(|d| d.help.clone()).collect(),
+    }
+}
+
+#[test]
+fn misspelled_local_names_the_closest_one() {
+    let src = "class Main {
+    fn main() -> int {
+        let square: int = 4;
+        return squrae;
+    };
+};
+";
+    assert_eq!(helps(src), vec![Some("did you mean square?".to_string())]);
+}
+
+#[test]
+fn misspelled_members_classes_and_variants_name_the_closest_one() {
+    let src = "enum Shape { Circle(r: int), Rect(w: int, h: int); };
+
+class Counter {
+    public count: int = 0;
+    public fn increment() -> int { return 1; };
+};
+
+class Main {
+    fn main() -> int {
+        let c = new Countr;
+        let k = new Counter;
+        let i = k.incremnt();
+        let n = k.cuont;
+        let s = Shape::Circel(1);
+        return 0;
+    };
+};
+";
+    let helps = helps(src);
+    for name in ["Counter", "increment", "count", "Circle"] {
+        let help = Some(format!("did you mean {}?", name));
+        assert!(helps.contains(&help), "{:?} has no {:?}", helps, help);
+    }
+}
+
+#[test]
+fn name_far_from_every_other_gets_no_hint() {
+    let src = "class Main {
+    fn main() -> int {
+        let square: int = 4;
+        return xylophone;
+    };
+};
+";
+    assert_eq!(helps(src), vec![None]);
+}
@@ -0,0 +1,78 @@
+//! The lints walk the tree with `Visitor` and the package resolver rewrites it with
+//! `VisitorMut`, so what they find in nested bodies shows the walks reach every one of them.
+
+mod common;
+
+use common::{codes, run_typed, warnings};
+use strawberry::{check, parse_sources, Source};
+
+#[test]
+fn visitor_walks_into_every_nested_body() {
+    let src = "class Main {
+    public count: int = 0;
+    fn main() -> int {
+        if (count > 0) {
+            let count: int = 1;
+            print(count.to_string());
+        } else {
+            let count: int = 2;
+            print(count.to_string());
+        }
+        while (count > 0) {
+            let count: int = 3;
+            print(count.to_string());
+        }
+        try {
+            let count: int = 4;
+            print(count.to_string());
+        } catch (e: Exception) {
+            let count: int = 5;
+            print(e.get_message());
+            print(count.to_string());
+        } finally {
+            let count: int = 6;
+            print(count.to_string());
+        }
+        return 0;
+    };
+};
+";
+    assert_eq!(warnings(src), vec!["W0004"; 6]);
+}
+
+#[test]
+fn visitor_mut_walks_into_every_nested_body() {
+    let geo = "package geo;
+class Circle { public fn name() -> String { return \"circle \"; }; };
+";
+    let main = "from \"geo\" import Circle;
+class Main {
+    fn main() -> int {
+        let shape: Object = new Circle;
+        if (shape is Circle) {
+            let c: Circle = shape as Circle;
+            print(c.name());
+        } else {
+            print(\"not a circle \");
+        }
+        try {
+            let c: Circle = new Circle;
+            print(c.name());
+        } catch (e: Exception) {
+            print(e.get_message());
+        } finally {
+            let c: Circle? = null;
+            let d: Circle = c ?? new Circle;
+            print(d.name());
+        }
+        return 0;
+    };
+};
+";
+    let sources = vec![Source::new("src/geo/shapes.st", geo), Source::new("src/main.st", main)];
+    let typed = match parse_sources(sources).and_then(check) {
+        Ok(typed) => typed,
+        Err(diagnostics) => panic!("{:?}", codes(&diagnostics)),
+    };
+    assert_eq!(run_typed("visit-mut", &typed), "circle circle circle ");
+}